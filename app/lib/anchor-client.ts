@@ -350,6 +350,45 @@ export class AnchorClient {
     return Math.max(0, totalStreamable - stream.withdrawnAmount)
   }
 
+  static nonceStreamId(nonce: number | bigint): Buffer {
+    const streamId = Buffer.alloc(32)
+    streamId.writeBigUInt64LE(BigInt(nonce), 24)
+    return streamId
+  }
+
+  findSenderProfileAddress(sender: PublicKey): PublicKey {
+    const [address] = PublicKey.findProgramAddressSync(
+      [Buffer.from('sender_profile'), sender.toBuffer()],
+      this.program.programId
+    )
+    return address
+  }
+
+  findStreamAddress(creator: PublicKey, streamId: Buffer | Uint8Array): PublicKey {
+    if (streamId.length !== 32) {
+      throw new Error('Stream id must be 32 bytes')
+    }
+    const [address] = PublicKey.findProgramAddressSync(
+      [Buffer.from('stream'), creator.toBuffer(), Buffer.from(streamId)],
+      this.program.programId
+    )
+    return address
+  }
+
+  findStreamAddressByNonce(creator: PublicKey, nonce: number | bigint): PublicKey {
+    return this.findStreamAddress(creator, AnchorClient.nonceStreamId(nonce))
+  }
+
+  async getNextStreamId(sender: PublicKey): Promise<Buffer | null> {
+    const profile = await this.program.account.senderProfile.fetchNullable(
+      this.findSenderProfileAddress(sender)
+    )
+    if (!profile) {
+      return null
+    }
+    return AnchorClient.nonceStreamId(BigInt(profile.nextNonce.toString()))
+  }
+
   private async getAssociatedTokenAddress(mint: PublicKey, owner: PublicKey): Promise<PublicKey> {
     const [address] = PublicKey.findProgramAddressSync(
       [owner.toBuffer(), new PublicKey('TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA').toBuffer(), mint.toBuffer()],
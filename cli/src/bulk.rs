@@ -0,0 +1,392 @@
+//! CSV-driven bulk stream creation.
+//!
+//! Every row becomes a linear vesting stream whose id is derived from the sender,
+//! the row number and the row contents. Re-running the same file therefore targets
+//! the same stream addresses, and rows whose stream already exists are skipped
+//! even if the state file was lost.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anchor_lang::error::Error as AnchorError;
+use anyhow::{bail, ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::{hash, hashv};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use streamflow::state::constants::RATE_PRECISION;
+use streamflow::state::vesting::{AccelerationClause, LeaverPolicy, VestingSegment};
+use streamflow::state::{utils, Stream};
+use streamflow_sdk::{args, instructions, pda};
+
+use crate::client::{Client, TxOutcome};
+use crate::output::OutputFormat;
+
+/// Maximum stream name length accepted by the program
+const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Debug, Deserialize)]
+struct Row {
+    recipient: String,
+    amount: u64,
+    start: i64,
+    end: i64,
+    cliff: Option<i64>,
+    name: String,
+}
+
+/// A row that passed validation
+#[derive(Debug)]
+struct Entry {
+    line: usize,
+    start: i64,
+    stream: Pubkey,
+    instruction: Instruction,
+}
+
+#[derive(Debug, Serialize)]
+struct RowError {
+    line: usize,
+    error: String,
+}
+
+/// Progress persisted between runs
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    csv_hash: String,
+    sender: String,
+    mint: String,
+    /// Created streams keyed by CSV line number
+    created: BTreeMap<usize, CreatedStream>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CreatedStream {
+    stream: String,
+    signature: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Summary {
+    total: usize,
+    already_created: usize,
+    created: usize,
+    transactions: Vec<TxOutcome>,
+    state_file: String,
+}
+
+pub async fn bulk_create(
+    client: &Client,
+    format: OutputFormat,
+    file: &Path,
+    mint: Pubkey,
+    state_file: Option<PathBuf>,
+    max_per_transaction: usize,
+) -> Result<()> {
+    ensure!(max_per_transaction > 0, "--max-per-tx must be at least 1");
+
+    let sender = client.payer();
+    let contents =
+        std::fs::read(file).with_context(|| format!("failed to read {}", file.display()))?;
+    let csv_hash = hash(&contents).to_string();
+    let state_file = state_file.unwrap_or_else(|| file.with_extension("state.json"));
+
+    let (entries, errors) = parse(&contents, &sender, &mint);
+    if !errors.is_empty() {
+        report_errors(format, &errors)?;
+        bail!("{} invalid rows, nothing was sent", errors.len());
+    }
+
+    let mut state = load_state(&state_file, &csv_hash, &sender, &mint)?;
+
+    // Streams created by a run that died before saving its progress
+    let pending: Vec<&Entry> = entries
+        .iter()
+        .filter(|entry| !state.created.contains_key(&entry.line))
+        .collect();
+    let addresses: Vec<Pubkey> = pending.iter().map(|entry| entry.stream).collect();
+    let mut existing = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(100) {
+        existing.extend(client.rpc.get_multiple_accounts(chunk).await?);
+    }
+
+    let mut to_create = Vec::new();
+    for (entry, account) in pending.into_iter().zip(existing) {
+        if account.is_some() {
+            state.created.insert(
+                entry.line,
+                CreatedStream {
+                    stream: entry.stream.to_string(),
+                    signature: None,
+                },
+            );
+        } else {
+            to_create.push(entry);
+        }
+    }
+    save_state(&state_file, &state, client.dry_run)?;
+
+    // Rows created by an earlier run may have started since, only new ones must not
+    let current_time = client.cluster_time().await?;
+    let errors: Vec<RowError> = to_create
+        .iter()
+        .filter(|entry| entry.start < current_time)
+        .map(|entry| RowError {
+            line: entry.line,
+            error: "start time is in the past".to_string(),
+        })
+        .collect();
+    if !errors.is_empty() {
+        report_errors(format, &errors)?;
+        bail!("{} invalid rows, nothing was sent", errors.len());
+    }
+
+    let mut transactions = Vec::new();
+    let mut created = 0;
+    for batch in pack(&sender, &to_create, max_per_transaction)? {
+        let instructions: Vec<Instruction> =
+            batch.iter().map(|entry| entry.instruction.clone()).collect();
+        let outcome = client.execute(&instructions).await?;
+
+        if let TxOutcome::Sent { signature } = &outcome {
+            for entry in &batch {
+                state.created.insert(
+                    entry.line,
+                    CreatedStream {
+                        stream: entry.stream.to_string(),
+                        signature: Some(signature.clone()),
+                    },
+                );
+            }
+            created += batch.len();
+            save_state(&state_file, &state, client.dry_run)?;
+        }
+        transactions.push(outcome);
+    }
+
+    let summary = Summary {
+        total: entries.len(),
+        already_created: entries.len() - to_create.len(),
+        created,
+        transactions,
+        state_file: state_file.display().to_string(),
+    };
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+        OutputFormat::Table => {
+            println!(
+                "{} rows: {} already created, {} created in {} transactions",
+                summary.total,
+                summary.already_created,
+                summary.created,
+                summary.transactions.len()
+            );
+            println!("progress saved to {}", summary.state_file);
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse and validate every row, collecting all errors. Start times are checked
+/// against the cluster time later, once rows that already exist are known.
+fn parse(contents: &[u8], sender: &Pubkey, mint: &Pubkey) -> (Vec<Entry>, Vec<RowError>) {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(contents);
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, record) in reader.deserialize::<Row>().enumerate() {
+        // Line 1 is the header
+        let line = index + 2;
+        let result = record
+            .map_err(anyhow::Error::from)
+            .and_then(|row| validate(row, line, sender, mint));
+
+        match result {
+            Ok(entry) => entries.push(entry),
+            Err(err) => errors.push(RowError {
+                line,
+                error: err.to_string(),
+            }),
+        }
+    }
+
+    (entries, errors)
+}
+
+fn validate(row: Row, line: usize, sender: &Pubkey, mint: &Pubkey) -> Result<Entry> {
+    let recipient: Pubkey = row
+        .recipient
+        .parse()
+        .with_context(|| format!("invalid recipient {}", row.recipient))?;
+    let cliff = row.cliff.unwrap_or(row.start);
+
+    ensure!(recipient != *sender, "recipient cannot be the sender");
+    ensure!(
+        row.name.len() <= MAX_NAME_LENGTH,
+        "name is longer than {} bytes",
+        MAX_NAME_LENGTH
+    );
+    ensure!(row.end > row.start, "end time must be after start time");
+
+    // Same checks create_vesting_stream runs for a single linear segment
+    let duration = (row.end - row.start) as u64;
+    utils::validate_duration(duration).map_err(program_error)?;
+    Stream::validate_params(row.start, row.end, cliff, row.amount, row.amount, duration)
+        .map_err(program_error)?;
+
+    let stream_id = row_stream_id(sender, line, &row, cliff);
+    ensure!(
+        pda::is_custom_stream_id(&stream_id),
+        "row hashes to a reserved stream id, change its name"
+    );
+
+    let instruction = instructions::create_vesting_stream(
+        sender,
+        mint,
+        false,
+        None,
+        args::CreateVestingStream {
+            stream_id,
+            recipient,
+            start_time: row.start,
+            cliff_time: cliff,
+            segments: vec![VestingSegment {
+                duration,
+                amount: row.amount,
+                exponent: RATE_PRECISION as u32,
+                ..Default::default()
+            }],
+            cancelable_by_sender: true,
+            cancelable_by_recipient: false,
+            cancel_notice_period: 0,
+            leaver_policy: LeaverPolicy::default(),
+            acceleration: AccelerationClause::default(),
+            stream_name: row.name,
+            yield_share_bps: None,
+        },
+    );
+
+    Ok(Entry {
+        line,
+        start: row.start,
+        stream: pda::find_stream_address(sender, &stream_id).0,
+        instruction,
+    })
+}
+
+fn row_stream_id(sender: &Pubkey, line: usize, row: &Row, cliff: i64) -> [u8; 32] {
+    hashv(&[
+        b"streamflow-cli-bulk",
+        sender.as_ref(),
+        &(line as u64).to_le_bytes(),
+        row.recipient.as_bytes(),
+        &row.amount.to_le_bytes(),
+        &row.start.to_le_bytes(),
+        &row.end.to_le_bytes(),
+        &cliff.to_le_bytes(),
+        row.name.as_bytes(),
+    ])
+    .to_bytes()
+}
+
+fn program_error(err: AnchorError) -> anyhow::Error {
+    match err {
+        AnchorError::AnchorError(err) => anyhow::anyhow!(err.error_msg),
+        AnchorError::ProgramError(err) => anyhow::anyhow!(err.program_error.to_string()),
+    }
+}
+
+/// Group entries into as few transactions as fit the packet size
+fn pack<'a>(
+    payer: &Pubkey,
+    entries: &[&'a Entry],
+    max_per_transaction: usize,
+) -> Result<Vec<Vec<&'a Entry>>> {
+    let mut batches = Vec::new();
+    let mut batch: Vec<&Entry> = Vec::new();
+
+    for entry in entries {
+        batch.push(entry);
+
+        if batch.len() > max_per_transaction || !fits(payer, &batch)? {
+            let last = batch.pop().expect("batch is not empty");
+            ensure!(
+                !batch.is_empty(),
+                "line {}: instruction does not fit in a transaction",
+                entry.line
+            );
+            batches.push(std::mem::replace(&mut batch, vec![last]));
+        }
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    Ok(batches)
+}
+
+fn fits(payer: &Pubkey, batch: &[&Entry]) -> Result<bool> {
+    let instructions: Vec<Instruction> =
+        batch.iter().map(|entry| entry.instruction.clone()).collect();
+    let transaction = Transaction::new_with_payer(&instructions, Some(payer));
+    // Unsigned transactions still carry a placeholder for every signature
+    Ok(bincode::serialized_size(&transaction)? as usize <= PACKET_DATA_SIZE)
+}
+
+fn load_state(path: &Path, csv_hash: &str, sender: &Pubkey, mint: &Pubkey) -> Result<State> {
+    if !path.exists() {
+        return Ok(State {
+            csv_hash: csv_hash.to_string(),
+            sender: sender.to_string(),
+            mint: mint.to_string(),
+            created: BTreeMap::new(),
+        });
+    }
+
+    let state: State = serde_json::from_slice(&std::fs::read(path)?)
+        .with_context(|| format!("failed to parse state file {}", path.display()))?;
+    ensure!(
+        state.csv_hash == csv_hash,
+        "{} belongs to a different version of the CSV file",
+        path.display()
+    );
+    ensure!(
+        state.sender == sender.to_string() && state.mint == mint.to_string(),
+        "{} was created for sender {} and mint {}",
+        path.display(),
+        state.sender,
+        state.mint
+    );
+
+    Ok(state)
+}
+
+fn save_state(path: &Path, state: &State, dry_run: bool) -> Result<()> {
+    if dry_run {
+        return Ok(());
+    }
+
+    // Write then rename so a crash never leaves a truncated state file
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn report_errors(format: OutputFormat, errors: &[RowError]) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(errors)?),
+        OutputFormat::Table => {
+            for error in errors {
+                eprintln!("line {}: {}", error.line, error.error);
+            }
+        }
+    }
+    Ok(())
+}
+
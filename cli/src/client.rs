@@ -0,0 +1,106 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::account::from_account;
+use solana_sdk::clock::Clock;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::sysvar;
+use solana_sdk::transaction::Transaction;
+
+/// Result of submitting a transaction, or of simulating it with `--dry-run`
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TxOutcome {
+    Sent {
+        signature: String,
+    },
+    Simulated {
+        units_consumed: Option<u64>,
+        error: Option<String>,
+        logs: Vec<String>,
+    },
+}
+
+pub struct Client {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub dry_run: bool,
+}
+
+impl Client {
+    pub fn new(url: &str, keypair_path: &str, dry_run: bool) -> Result<Self> {
+        let path = expand_home(keypair_path);
+        let payer = read_keypair_file(&path)
+            .map_err(|err| anyhow!("failed to read keypair {}: {}", path, err))?;
+
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            payer,
+            dry_run,
+        })
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    /// Cluster time from the Clock sysvar, the same time the program sees
+    pub async fn cluster_time(&self) -> Result<i64> {
+        let account = self.rpc.get_account(&sysvar::clock::ID).await?;
+        let clock: Clock =
+            from_account(&account).ok_or_else(|| anyhow!("invalid Clock sysvar account"))?;
+        Ok(clock.unix_timestamp)
+    }
+
+    /// Sign and send `instructions` in one transaction, or simulate it on a dry run
+    pub async fn execute(&self, instructions: &[Instruction]) -> Result<TxOutcome> {
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer()),
+            &[&self.payer],
+            blockhash,
+        );
+
+        if self.dry_run {
+            let result = self
+                .rpc
+                .simulate_transaction_with_config(
+                    &transaction,
+                    RpcSimulateTransactionConfig {
+                        commitment: Some(self.rpc.commitment()),
+                        ..RpcSimulateTransactionConfig::default()
+                    },
+                )
+                .await?
+                .value;
+
+            return Ok(TxOutcome::Simulated {
+                units_consumed: result.units_consumed,
+                error: result.err.map(|err| err.to_string()),
+                logs: result.logs.unwrap_or_default(),
+            });
+        }
+
+        let signature = self
+            .rpc
+            .send_and_confirm_transaction(&transaction)
+            .await
+            .context("transaction failed")?;
+
+        Ok(TxOutcome::Sent {
+            signature: signature.to_string(),
+        })
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    }
+}
@@ -0,0 +1,231 @@
+use anchor_spl::associated_token::get_associated_token_address;
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::pubkey::Pubkey;
+use streamflow_sdk::{args, instructions, pda, rpc};
+
+use crate::client::Client;
+use crate::output::{self, CommandOutput, OutputFormat, StreamView};
+
+pub struct CreateParams {
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub start: i64,
+    pub end: i64,
+    pub cliff: Option<i64>,
+    pub period: u64,
+    pub stream_id: Option<[u8; 32]>,
+    pub cancelable_by_sender: bool,
+    pub cancelable_by_recipient: bool,
+    pub cancel_notice_period: u64,
+    pub transferable_by_sender: bool,
+    pub transferable_by_recipient: bool,
+}
+
+/// Parse a stream id given as 64 hex characters
+pub fn parse_stream_id(text: &str) -> Result<[u8; 32]> {
+    let text = text.trim_start_matches("0x");
+    if text.len() != 64 {
+        bail!("stream id must be 64 hex characters");
+    }
+
+    let mut stream_id = [0u8; 32];
+    for (byte, chunk) in stream_id.iter_mut().zip(text.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(chunk)?;
+        *byte = u8::from_str_radix(digits, 16)
+            .map_err(|_| anyhow!("invalid hex in stream id: {}", digits))?;
+    }
+    Ok(stream_id)
+}
+
+/// Random id that can never collide with sender profile nonce ids
+pub fn random_stream_id() -> [u8; 32] {
+    loop {
+        let stream_id: [u8; 32] = rand::random();
+        if pda::is_custom_stream_id(&stream_id) {
+            return stream_id;
+        }
+    }
+}
+
+pub async fn create(client: &Client, format: OutputFormat, params: CreateParams) -> Result<()> {
+    let sender = client.payer();
+
+    // An explicit id is used as is, otherwise the sender profile nonce takes precedence
+    let (stream_id, use_sender_profile) = match params.stream_id {
+        Some(stream_id) => (stream_id, !pda::is_custom_stream_id(&stream_id)),
+        None => match rpc::next_stream_id(&client.rpc, &sender).await? {
+            Some(stream_id) => (stream_id, true),
+            None => (random_stream_id(), false),
+        },
+    };
+    let stream = pda::find_stream_address(&sender, &stream_id).0;
+
+    let ix = instructions::create_stream(
+        &sender,
+        &params.mint,
+        use_sender_profile,
+        args::CreateStream {
+            stream_id,
+            recipient: params.recipient,
+            deposited_amount: params.amount,
+            start_time: params.start,
+            end_time: params.end,
+            cliff_time: params.cliff,
+            cancelable_by_sender: params.cancelable_by_sender,
+            cancelable_by_recipient: params.cancelable_by_recipient,
+            cancel_notice_period: params.cancel_notice_period,
+            transferable_by_sender: params.transferable_by_sender,
+            transferable_by_recipient: params.transferable_by_recipient,
+            automatic_withdrawal: false,
+            period: params.period,
+        },
+    );
+
+    let outcome = client.execute(&[ix]).await?;
+    output::print_output(
+        format,
+        &CommandOutput {
+            command: "create",
+            stream: Some(stream.to_string()),
+            outcome,
+        },
+    )
+}
+
+pub async fn list(
+    client: &Client,
+    format: OutputFormat,
+    sender: Option<Pubkey>,
+    recipient: Option<Pubkey>,
+) -> Result<()> {
+    let mut streams = match recipient {
+        Some(recipient) => rpc::fetch_streams_by_recipient(&client.rpc, &recipient).await?,
+        None => {
+            let sender = sender.unwrap_or_else(|| client.payer());
+            rpc::fetch_streams_by_sender(&client.rpc, &sender).await?
+        }
+    };
+    streams.sort_by_key(|(_, stream)| (stream.start_time, stream.end_time));
+
+    let current_time = client.cluster_time().await?;
+    let views = streams
+        .iter()
+        .map(|(address, stream)| StreamView::new(address, stream, current_time))
+        .collect::<Result<Vec<_>>>()?;
+
+    output::print_streams(format, &views)
+}
+
+pub async fn show(client: &Client, format: OutputFormat, address: &Pubkey) -> Result<()> {
+    let stream = rpc::fetch_stream(&client.rpc, address).await?;
+    let current_time = client.cluster_time().await?;
+
+    output::print_stream(format, &StreamView::new(address, &stream, current_time)?)
+}
+
+pub async fn withdraw(
+    client: &Client,
+    format: OutputFormat,
+    address: &Pubkey,
+    amount: Option<u64>,
+    destination: Option<Pubkey>,
+) -> Result<()> {
+    let authority = client.payer();
+    let stream = rpc::fetch_stream(&client.rpc, address).await?;
+
+    let amount = match amount {
+        Some(amount) => amount,
+        None => {
+            let current_time = client.cluster_time().await?;
+            streamflow_sdk::math::withdrawable_amount(&stream, current_time)?
+        }
+    };
+    if amount == 0 {
+        bail!("nothing to withdraw");
+    }
+
+    // A fixed destination set by the recipient is the only account the program accepts
+    let destination = destination.unwrap_or_else(|| {
+        if stream.withdraw_destination != Pubkey::default() {
+            stream.withdraw_destination
+        } else {
+            get_associated_token_address(&authority, &stream.mint)
+        }
+    });
+    let position_token_account = stream
+        .has_position_token()
+        .then(|| get_associated_token_address(&authority, &stream.position_mint));
+
+    let ix = instructions::withdraw(
+        address,
+        &stream,
+        &authority,
+        &destination,
+        position_token_account,
+        amount,
+    );
+    send(client, format, "withdraw", address, ix).await
+}
+
+pub async fn cancel(client: &Client, format: OutputFormat, address: &Pubkey) -> Result<()> {
+    let stream = rpc::fetch_stream(&client.rpc, address).await?;
+    let position_holder = rpc::fetch_position_holder(&client.rpc, &stream).await?;
+    let ix = instructions::cancel_stream(
+        address,
+        &stream,
+        &client.payer(),
+        position_holder.as_ref(),
+    );
+    send(client, format, "cancel", address, ix).await
+}
+
+pub async fn topup(
+    client: &Client,
+    format: OutputFormat,
+    address: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let stream = rpc::fetch_stream(&client.rpc, address).await?;
+    if stream.sender != client.payer() {
+        bail!("only the stream sender {} can top up", stream.sender);
+    }
+
+    let ix = instructions::topup_stream(address, &stream, amount);
+    send(client, format, "topup", address, ix).await
+}
+
+pub async fn transfer(
+    client: &Client,
+    format: OutputFormat,
+    address: &Pubkey,
+    new_recipient: Pubkey,
+) -> Result<()> {
+    let stream = rpc::fetch_stream(&client.rpc, address).await?;
+    let ix = instructions::transfer_stream(
+        address,
+        &stream,
+        &client.payer(),
+        new_recipient,
+        Vec::new(),
+    );
+    send(client, format, "transfer", address, ix).await
+}
+
+async fn send(
+    client: &Client,
+    format: OutputFormat,
+    command: &'static str,
+    stream: &Pubkey,
+    ix: solana_sdk::instruction::Instruction,
+) -> Result<()> {
+    let outcome = client.execute(&[ix]).await?;
+    output::print_output(
+        format,
+        &CommandOutput {
+            command,
+            stream: Some(stream.to_string()),
+            outcome,
+        },
+    )
+}
@@ -0,0 +1,204 @@
+//! Command-line tool for operating StreamFlow streams
+
+mod bulk;
+mod client;
+mod commands;
+mod output;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{ArgAction, Parser, Subcommand};
+use solana_sdk::pubkey::Pubkey;
+
+use client::Client;
+use output::OutputFormat;
+
+#[derive(Parser)]
+#[command(name = "streamflow-cli", version, about = "Operate StreamFlow token streams")]
+struct Cli {
+    /// RPC endpoint of the cluster
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        env = "STREAMFLOW_RPC_URL",
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// Keypair file that signs and pays for transactions
+    #[arg(
+        long,
+        short = 'k',
+        global = true,
+        env = "STREAMFLOW_KEYPAIR",
+        default_value = "~/.config/solana/id.json"
+    )]
+    keypair: String,
+
+    /// Output format
+    #[arg(long, short = 'o', global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
+    /// Simulate transactions and print compute units instead of sending them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a linear stream funded from the signer's token account
+    Create {
+        /// Recipient wallet
+        #[arg(long)]
+        recipient: Pubkey,
+        /// Mint of the streamed token
+        #[arg(long)]
+        mint: Pubkey,
+        /// Amount to deposit, in base units
+        #[arg(long)]
+        amount: u64,
+        /// Start time (Unix timestamp)
+        #[arg(long)]
+        start: i64,
+        /// End time (Unix timestamp)
+        #[arg(long)]
+        end: i64,
+        /// Cliff time (Unix timestamp)
+        #[arg(long)]
+        cliff: Option<i64>,
+        /// Seconds between unlocks
+        #[arg(long, default_value_t = 1)]
+        period: u64,
+        /// Stream id as 64 hex characters (defaults to the sender profile nonce or a random id)
+        #[arg(long)]
+        stream_id: Option<String>,
+        #[arg(long, action = ArgAction::Set, default_value_t = true)]
+        cancelable_by_sender: bool,
+        #[arg(long, action = ArgAction::Set, default_value_t = false)]
+        cancelable_by_recipient: bool,
+        /// Seconds of notice required before a cancellation takes effect
+        #[arg(long, default_value_t = 0)]
+        cancel_notice_period: u64,
+        #[arg(long, action = ArgAction::Set, default_value_t = false)]
+        transferable_by_sender: bool,
+        #[arg(long, action = ArgAction::Set, default_value_t = false)]
+        transferable_by_recipient: bool,
+    },
+    /// List streams sent by or paying a wallet (defaults to streams sent by the signer)
+    List {
+        #[arg(long, conflicts_with = "recipient")]
+        sender: Option<Pubkey>,
+        #[arg(long)]
+        recipient: Option<Pubkey>,
+    },
+    /// Show a single stream
+    Show { stream: Pubkey },
+    /// Withdraw unlocked tokens (defaults to everything withdrawable)
+    Withdraw {
+        stream: Pubkey,
+        #[arg(long)]
+        amount: Option<u64>,
+        /// Token account receiving the tokens (defaults to the signer's associated account)
+        #[arg(long)]
+        destination: Option<Pubkey>,
+    },
+    /// Cancel a stream
+    Cancel { stream: Pubkey },
+    /// Add tokens to a linear stream, extending its end time
+    Topup {
+        stream: Pubkey,
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Move a stream to a new recipient
+    Transfer {
+        stream: Pubkey,
+        #[arg(long)]
+        new_recipient: Pubkey,
+    },
+    /// Create one stream per row of a CSV file with the columns
+    /// recipient,amount,start,end,cliff,name
+    BulkCreate {
+        /// CSV file to read
+        file: PathBuf,
+        /// Mint of the streamed token
+        #[arg(long)]
+        mint: Pubkey,
+        /// Progress file used to resume a failed run (defaults to <file>.state.json)
+        #[arg(long)]
+        state_file: Option<PathBuf>,
+        /// Maximum number of streams created per transaction
+        #[arg(long, default_value_t = 4)]
+        max_per_tx: usize,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let client = Client::new(&cli.url, &cli.keypair, cli.dry_run)?;
+    let format = cli.output;
+
+    match cli.command {
+        Command::Create {
+            recipient,
+            mint,
+            amount,
+            start,
+            end,
+            cliff,
+            period,
+            stream_id,
+            cancelable_by_sender,
+            cancelable_by_recipient,
+            cancel_notice_period,
+            transferable_by_sender,
+            transferable_by_recipient,
+        } => {
+            let params = commands::CreateParams {
+                recipient,
+                mint,
+                amount,
+                start,
+                end,
+                cliff,
+                period,
+                stream_id: stream_id.as_deref().map(commands::parse_stream_id).transpose()?,
+                cancelable_by_sender,
+                cancelable_by_recipient,
+                cancel_notice_period,
+                transferable_by_sender,
+                transferable_by_recipient,
+            };
+            commands::create(&client, format, params).await
+        }
+        Command::List { sender, recipient } => {
+            commands::list(&client, format, sender, recipient).await
+        }
+        Command::Show { stream } => commands::show(&client, format, &stream).await,
+        Command::Withdraw {
+            stream,
+            amount,
+            destination,
+        } => commands::withdraw(&client, format, &stream, amount, destination).await,
+        Command::Cancel { stream } => commands::cancel(&client, format, &stream).await,
+        Command::Topup { stream, amount } => {
+            commands::topup(&client, format, &stream, amount).await
+        }
+        Command::Transfer {
+            stream,
+            new_recipient,
+        } => commands::transfer(&client, format, &stream, new_recipient).await,
+        Command::BulkCreate {
+            file,
+            mint,
+            state_file,
+            max_per_tx,
+        } => bulk::bulk_create(&client, format, &file, mint, state_file, max_per_tx).await,
+    }
+}
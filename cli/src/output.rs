@@ -0,0 +1,175 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use streamflow_sdk::{math, Stream};
+
+use crate::client::TxOutcome;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Table,
+}
+
+/// Flattened view of a stream for display
+#[derive(Debug, Serialize)]
+pub struct StreamView {
+    pub address: String,
+    pub stream_id: String,
+    pub status: &'static str,
+    pub sender: String,
+    pub recipient: String,
+    pub mint: String,
+    pub deposited_amount: u64,
+    pub withdrawn_amount: u64,
+    pub withdrawable_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub cliff_time: i64,
+}
+
+impl StreamView {
+    pub fn new(address: &Pubkey, stream: &Stream, current_time: i64) -> Result<Self> {
+        Ok(Self {
+            address: address.to_string(),
+            stream_id: hex(&stream.stream_id),
+            status: status(stream, current_time),
+            sender: stream.sender.to_string(),
+            recipient: stream.recipient.to_string(),
+            mint: stream.mint.to_string(),
+            deposited_amount: stream.deposited_amount,
+            withdrawn_amount: stream.withdrawn_amount,
+            withdrawable_amount: math::withdrawable_amount(stream, current_time)?,
+            start_time: stream.start_time,
+            end_time: stream.end_time,
+            cliff_time: stream.cliff_time,
+        })
+    }
+}
+
+/// Outcome of a command that sends a transaction
+#[derive(Debug, Serialize)]
+pub struct CommandOutput {
+    pub command: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<String>,
+    #[serde(flatten)]
+    pub outcome: TxOutcome,
+}
+
+fn status(stream: &Stream, current_time: i64) -> &'static str {
+    if stream.cancelled.get() {
+        "cancelled"
+    } else if stream.closed.get() {
+        "closed"
+    } else if stream.paused.get() {
+        "paused"
+    } else if current_time < stream.start_time {
+        "scheduled"
+    } else if current_time >= stream.end_time {
+        "completed"
+    } else {
+        "streaming"
+    }
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn print_streams(format: OutputFormat, streams: &[StreamView]) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(streams)?),
+        OutputFormat::Table => {
+            let rows = streams
+                .iter()
+                .map(|view| {
+                    vec![
+                        view.address.clone(),
+                        view.status.to_string(),
+                        view.recipient.clone(),
+                        view.deposited_amount.to_string(),
+                        view.withdrawn_amount.to_string(),
+                        view.withdrawable_amount.to_string(),
+                        view.end_time.to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            print_table(
+                &[
+                    "STREAM",
+                    "STATUS",
+                    "RECIPIENT",
+                    "DEPOSITED",
+                    "WITHDRAWN",
+                    "WITHDRAWABLE",
+                    "END",
+                ],
+                &rows,
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn print_stream(format: OutputFormat, stream: &StreamView) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(stream)?),
+        OutputFormat::Table => print_fields(&serde_json::to_value(stream)?),
+    }
+    Ok(())
+}
+
+pub fn print_output(format: OutputFormat, output: &CommandOutput) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(output)?),
+        OutputFormat::Table => print_fields(&serde_json::to_value(output)?),
+    }
+    Ok(())
+}
+
+/// Print an object as aligned `key  value` lines
+fn print_fields(value: &serde_json::Value) {
+    let Some(fields) = value.as_object() else {
+        println!("{}", value);
+        return;
+    };
+    let width = fields.keys().map(|key| key.len()).max().unwrap_or(0);
+
+    for (key, value) in fields {
+        match value {
+            serde_json::Value::Array(lines) => {
+                println!("{:width$}", key, width = width);
+                for line in lines {
+                    println!("  {}", line.as_str().unwrap_or_default());
+                }
+            }
+            serde_json::Value::String(text) => println!("{:width$}  {}", key, text, width = width),
+            other => println!("{:width$}  {}", key, other, width = width),
+        }
+    }
+}
+
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = *width))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    println!("{}", line(headers.to_vec()));
+    for row in rows {
+        println!("{}", line(row.iter().map(String::as_str).collect()));
+    }
+}
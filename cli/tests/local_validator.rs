@@ -0,0 +1,163 @@
+//! Runs the CLI against a local test validator with the program loaded.
+//!
+//! Needs `streamflow.so` from `anchor build` (found through `BPF_OUT_DIR` or
+//! `tests/fixtures`), so it is ignored by default:
+//! `BPF_OUT_DIR=target/deploy cargo test -p streamflow-cli -- --ignored`
+
+use std::path::Path;
+use std::process::Command;
+
+use serde_json::Value;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{write_keypair_file, Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use solana_test_validator::TestValidatorGenesis;
+use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::instruction::create_associated_token_account;
+
+const DEPOSIT: u64 = 1_000_000;
+
+fn cli(url: &str, keypair: &Path, args: &[&str]) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_streamflow-cli"))
+        .args(["--url", url, "--keypair", keypair.to_str().unwrap()])
+        .args(["--output", "json"])
+        .args(args)
+        .output()
+        .expect("failed to run streamflow-cli");
+
+    assert!(
+        output.status.success(),
+        "streamflow-cli {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).expect("output is not json")
+}
+
+async fn create_funded_mint(rpc: &RpcClient, payer: &Keypair) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = rpc
+        .get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)
+        .await
+        .unwrap();
+    let token_account = get_associated_token_address(&payer.pubkey(), &mint.pubkey());
+
+    let instructions = [
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            rent,
+            spl_token::state::Mint::LEN as u64,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &payer.pubkey(), None, 6)
+            .unwrap(),
+        create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint.pubkey(), &spl_token::ID),
+        spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &mint.pubkey(),
+            &token_account,
+            &payer.pubkey(),
+            &[],
+            10 * DEPOSIT,
+        )
+        .unwrap(),
+    ];
+
+    let blockhash = rpc.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[payer, &mint],
+        blockhash,
+    );
+    rpc.send_and_confirm_transaction(&transaction).await.unwrap();
+
+    mint.pubkey()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires streamflow.so built with `anchor build`"]
+async fn create_list_show_withdraw_topup_cancel() {
+    let (validator, payer) = TestValidatorGenesis::default()
+        .add_program("streamflow", streamflow::ID)
+        .start_async()
+        .await;
+    let url = validator.rpc_url();
+    let rpc = validator.get_async_rpc_client();
+
+    let keypair_path = std::env::temp_dir().join(format!("streamflow-cli-{}.json", payer.pubkey()));
+    write_keypair_file(&payer, &keypair_path).unwrap();
+
+    let mint = create_funded_mint(&rpc, &payer).await;
+    let recipient = Keypair::new().pubkey();
+    let start = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+        + 5;
+    let end = start + 3_600;
+
+    let created = cli(
+        &url,
+        &keypair_path,
+        &[
+            "create",
+            "--recipient",
+            &recipient.to_string(),
+            "--mint",
+            &mint.to_string(),
+            "--amount",
+            &DEPOSIT.to_string(),
+            "--start",
+            &start.to_string(),
+            "--end",
+            &end.to_string(),
+        ],
+    );
+    assert_eq!(created["status"], "sent");
+    let stream = created["stream"].as_str().unwrap().to_string();
+
+    let listed = cli(&url, &keypair_path, &["list"]);
+    let listed = listed.as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["address"], stream.as_str());
+    assert_eq!(listed[0]["deposited_amount"], DEPOSIT);
+
+    let shown = cli(&url, &keypair_path, &["show", &stream]);
+    assert_eq!(shown["recipient"], recipient.to_string());
+    assert_eq!(shown["withdrawn_amount"], 0);
+
+    // The signer is not the recipient, so the simulation fails without sending anything
+    let simulated = cli(
+        &url,
+        &keypair_path,
+        &["--dry-run", "withdraw", &stream, "--amount", "1"],
+    );
+    assert_eq!(simulated["status"], "simulated");
+    assert!(simulated["units_consumed"].as_u64().unwrap() > 0);
+    assert!(!simulated["error"].is_null());
+
+    let simulated = cli(
+        &url,
+        &keypair_path,
+        &["--dry-run", "topup", &stream, "--amount", "500"],
+    );
+    assert_eq!(simulated["status"], "simulated");
+    assert!(simulated["error"].is_null());
+
+    let topped_up = cli(&url, &keypair_path, &["topup", &stream, "--amount", "500"]);
+    assert_eq!(topped_up["status"], "sent");
+    let shown = cli(&url, &keypair_path, &["show", &stream]);
+    assert_eq!(shown["deposited_amount"], DEPOSIT + 500);
+
+    let cancelled = cli(&url, &keypair_path, &["cancel", &stream]);
+    assert_eq!(cancelled["status"], "sent");
+    let shown = cli(&url, &keypair_path, &["show", &stream]);
+    assert_eq!(shown["status"], "cancelled");
+
+    std::fs::remove_file(keypair_path).ok();
+}
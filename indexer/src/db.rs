@@ -0,0 +1,690 @@
+//! SQLite storage for indexed events.
+//!
+//! `events` keeps every raw payload and is the source of truth. The normalized
+//! `streams`, `withdrawals` and `cancellations` tables are derived from it, so a
+//! reorg only has to delete the affected transactions and replay what is left.
+//! Replays follow chain order: slot, position of the transaction within its slot,
+//! then position of the event within its transaction.
+
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::decode::{self, Event};
+use crate::source::IndexedTransaction;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    tx_index INTEGER NOT NULL,
+    block_time INTEGER
+);
+CREATE INDEX IF NOT EXISTS transactions_slot ON transactions (slot, tx_index);
+
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL REFERENCES transactions (signature) ON DELETE CASCADE,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    data BLOB NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS events_slot ON events (slot);
+
+CREATE TABLE IF NOT EXISTS streams (
+    address TEXT PRIMARY KEY,
+    sender TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    mint TEXT,
+    deposited_amount INTEGER NOT NULL,
+    withdrawn_amount INTEGER NOT NULL DEFAULT 0,
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active',
+    created_signature TEXT NOT NULL,
+    updated_slot INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS streams_sender ON streams (sender);
+CREATE INDEX IF NOT EXISTS streams_recipient ON streams (recipient);
+
+CREATE TABLE IF NOT EXISTS withdrawals (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    stream TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS withdrawals_stream ON withdrawals (stream);
+
+CREATE TABLE IF NOT EXISTS cancellations (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    stream TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    paid_to_recipient INTEGER NOT NULL,
+    returned_to_sender INTEGER NOT NULL,
+    cancelled_at INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS checkpoint (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL
+);
+";
+
+/// Position of one event in the chain
+struct Origin<'a> {
+    signature: &'a str,
+    event_index: u32,
+    slot: u64,
+    block_time: Option<i64>,
+}
+
+pub struct Database {
+    conn: Connection,
+    program_id: Pubkey,
+}
+
+impl Database {
+    pub fn open(path: &Path, program_id: Pubkey) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn, program_id })
+    }
+
+    /// Last indexed transaction
+    pub fn checkpoint(&self) -> Result<Option<(String, u64)>> {
+        Ok(self
+            .conn
+            .query_row("SELECT signature, slot FROM checkpoint WHERE id = 1", [], |row| {
+                Ok((row.get(0)?, row.get::<_, i64>(1)? as u64))
+            })
+            .optional()?)
+    }
+
+    /// Start indexing after `signature` regardless of what was indexed before
+    pub fn set_checkpoint(&mut self, signature: &str, slot: u64) -> Result<()> {
+        write_checkpoint(&self.conn, signature, slot)
+    }
+
+    /// Transactions of `transactions` that come after the checkpoint, or after
+    /// `from_signature` when given. Fails if that signature is not among them.
+    pub fn unindexed<'a>(
+        &self,
+        transactions: &'a [IndexedTransaction],
+        from_signature: Option<&str>,
+    ) -> Result<&'a [IndexedTransaction]> {
+        let resume_from = match from_signature {
+            Some(signature) => Some(signature.to_string()),
+            None => self.checkpoint()?.map(|(signature, _)| signature),
+        };
+        let Some(signature) = resume_from else {
+            return Ok(transactions);
+        };
+
+        let position = transactions
+            .iter()
+            .position(|tx| tx.signature == signature)
+            .ok_or_else(|| anyhow!("checkpoint {} is not among the transactions", signature))?;
+        Ok(&transactions[position + 1..])
+    }
+
+    /// Signatures of indexed transactions newer than `slot`
+    pub fn signatures_after(&self, slot: u64) -> Result<Vec<(String, u64)>> {
+        let mut statement = self.conn.prepare(
+            "SELECT signature, slot FROM transactions WHERE slot > ?1 ORDER BY slot, tx_index",
+        )?;
+        let rows = statement.query_map([slot as i64], |row| {
+            Ok((row.get(0)?, row.get::<_, i64>(1)? as u64))
+        })?;
+        Ok(rows.collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// Index a batch of transactions atomically and advance the checkpoint.
+    /// Transactions must come in chain order; each is placed after those already
+    /// indexed in its slot. Transactions indexed before are skipped. Returns the
+    /// number of events stored.
+    pub fn index(&mut self, transactions: &[IndexedTransaction]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut stored = 0;
+
+        for transaction in transactions {
+            let inserted = tx.execute(
+                "INSERT OR IGNORE INTO transactions (signature, slot, tx_index, block_time)
+                 VALUES (?1, ?2, (SELECT COUNT(*) FROM transactions WHERE slot = ?2), ?3)",
+                params![transaction.signature, transaction.slot as i64, transaction.block_time],
+            )?;
+            if inserted == 0 {
+                continue;
+            }
+
+            for raw in decode::raw_events(&transaction.logs, &self.program_id) {
+                let origin = Origin {
+                    signature: &transaction.signature,
+                    event_index: raw.index,
+                    slot: transaction.slot,
+                    block_time: transaction.block_time,
+                };
+                store_event(&tx, &origin, &raw.data)?;
+                stored += 1;
+            }
+        }
+
+        if let Some(last) = transactions.last() {
+            write_checkpoint(&tx, &last.signature, last.slot)?;
+        }
+        tx.commit()?;
+        Ok(stored)
+    }
+
+    /// Forget everything indexed at or after `slot` and rebuild the derived tables
+    pub fn rollback(&mut self, slot: u64) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute("DELETE FROM transactions WHERE slot >= ?1", [slot as i64])?;
+        tx.execute("DELETE FROM checkpoint", [])?;
+        let previous = tx
+            .query_row(
+                "SELECT signature, slot FROM transactions ORDER BY slot DESC, tx_index DESC LIMIT 1",
+                [],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64)),
+            )
+            .optional()?;
+        if let Some((signature, slot)) = previous {
+            write_checkpoint(&tx, &signature, slot)?;
+        }
+
+        rebuild(&tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn write_checkpoint(conn: &Connection, signature: &str, slot: u64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO checkpoint (id, signature, slot) VALUES (1, ?1, ?2)
+         ON CONFLICT (id) DO UPDATE SET signature = excluded.signature, slot = excluded.slot",
+        params![signature, slot as i64],
+    )?;
+    Ok(())
+}
+
+/// Replay all stored events into freshly emptied derived tables
+fn rebuild(tx: &Transaction) -> Result<()> {
+    tx.execute_batch("DELETE FROM streams; DELETE FROM withdrawals; DELETE FROM cancellations;")?;
+
+    let mut statement = tx.prepare(
+        "SELECT e.signature, e.event_index, e.slot, t.block_time, e.data
+         FROM events e JOIN transactions t ON t.signature = e.signature
+         ORDER BY e.slot, t.tx_index, e.event_index",
+    )?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, i64>(2)? as u64,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, Vec<u8>>(4)?,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    for (signature, event_index, slot, block_time, data) in rows {
+        if let Some(event) = Event::decode(&data)? {
+            let origin = Origin {
+                signature: &signature,
+                event_index,
+                slot,
+                block_time,
+            };
+            apply(tx, &origin, &event)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn store_event(tx: &Transaction, origin: &Origin, data: &[u8]) -> Result<()> {
+    let event = Event::decode(data)?;
+    let name = event.as_ref().map_or("Unknown", Event::name);
+
+    tx.execute(
+        "INSERT INTO events (signature, event_index, slot, name, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![origin.signature, origin.event_index, origin.slot as i64, name, data],
+    )?;
+
+    if let Some(event) = event {
+        apply(tx, origin, &event)?;
+    }
+    Ok(())
+}
+
+/// SQLite integers are signed; refuse amounts that would wrap
+fn amount(value: u64) -> Result<i64> {
+    i64::try_from(value).map_err(|_| anyhow!("amount {} does not fit in SQLite", value))
+}
+
+/// Update the normalized tables for one event
+fn apply(tx: &Transaction, origin: &Origin, event: &Event) -> Result<()> {
+    let slot = origin.slot as i64;
+
+    match event {
+        Event::StreamCreated(e) => {
+            insert_stream(
+                tx,
+                origin,
+                &e.stream,
+                &e.sender,
+                &e.recipient,
+                None,
+                e.deposit_amount,
+                e.start_time,
+                e.end_time,
+            )?;
+        }
+        Event::VestingStreamCreated(e) => {
+            insert_stream(
+                tx,
+                origin,
+                &e.stream,
+                &e.sender,
+                &e.recipient,
+                Some(&e.mint),
+                e.deposited_amount,
+                e.start_time,
+                e.end_time,
+            )?;
+        }
+        Event::Withdrawal(e) => {
+            tx.execute(
+                "INSERT OR REPLACE INTO withdrawals
+                 (signature, event_index, slot, block_time, stream, recipient, amount)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    origin.signature,
+                    origin.event_index,
+                    slot,
+                    origin.block_time,
+                    e.stream.to_string(),
+                    e.recipient.to_string(),
+                    amount(e.amount)?
+                ],
+            )?;
+            tx.execute(
+                "UPDATE streams SET withdrawn_amount = ?2, updated_slot = ?3 WHERE address = ?1",
+                params![e.stream.to_string(), amount(e.withdrawn_amount)?, slot],
+            )?;
+        }
+        Event::StreamCanceled(e) => {
+            // The event carries the withdrawn total, the payout is the difference
+            let previously_withdrawn: i64 = tx
+                .query_row(
+                    "SELECT withdrawn_amount FROM streams WHERE address = ?1",
+                    [e.stream.to_string()],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .unwrap_or(0);
+            let paid = e.withdrawn_amount.saturating_sub(previously_withdrawn as u64);
+            insert_cancellation(
+                tx,
+                origin,
+                &e.stream,
+                "cancel",
+                paid,
+                e.returned_amount,
+                e.canceled_at,
+            )?;
+            tx.execute(
+                "UPDATE streams SET status = 'cancelled', withdrawn_amount = ?2, updated_slot = ?3
+                 WHERE address = ?1",
+                params![e.stream.to_string(), amount(e.withdrawn_amount)?, slot],
+            )?;
+        }
+        Event::GoodLeaverTerminated(e) => {
+            insert_cancellation(
+                tx,
+                origin,
+                &e.stream,
+                "good_leaver",
+                e.paid_to_recipient,
+                e.returned_to_sender,
+                e.terminated_at,
+            )?;
+            terminate(tx, &e.stream, e.paid_to_recipient, slot)?;
+        }
+        Event::BadLeaverTerminated(e) => {
+            insert_cancellation(
+                tx,
+                origin,
+                &e.stream,
+                "bad_leaver",
+                e.paid_to_recipient,
+                e.returned_to_sender,
+                e.terminated_at,
+            )?;
+            terminate(tx, &e.stream, e.paid_to_recipient, slot)?;
+        }
+        Event::StreamTransferred(e) => {
+            tx.execute(
+                "UPDATE streams SET recipient = ?2, updated_slot = ?3 WHERE address = ?1",
+                params![e.stream.to_string(), e.new_recipient.to_string(), slot],
+            )?;
+        }
+        Event::SenderTransferred(e) => {
+            tx.execute(
+                "UPDATE streams SET sender = ?2, updated_slot = ?3 WHERE address = ?1",
+                params![e.stream.to_string(), e.new_sender.to_string(), slot],
+            )?;
+        }
+        Event::StreamToppedUp(e) => {
+            tx.execute(
+                "UPDATE streams SET deposited_amount = ?2, end_time = ?3, updated_slot = ?4
+                 WHERE address = ?1",
+                params![e.stream.to_string(), amount(e.deposited_amount)?, e.end_time, slot],
+            )?;
+        }
+        Event::StreamAmended(e) => {
+            tx.execute(
+                "UPDATE streams SET end_time = ?2, updated_slot = ?3 WHERE address = ?1",
+                params![e.stream.to_string(), e.terms.end_time, slot],
+            )?;
+        }
+        Event::StreamSplit(e) => {
+            tx.execute(
+                "INSERT OR REPLACE INTO streams
+                 (address, sender, recipient, mint, deposited_amount, withdrawn_amount,
+                  start_time, end_time, status, created_signature, updated_slot)
+                 SELECT ?2, sender, ?3, mint, ?4, ?5, start_time, end_time, 'active', ?6, ?7
+                 FROM streams WHERE address = ?1",
+                params![
+                    e.stream.to_string(),
+                    e.new_stream.to_string(),
+                    e.new_recipient.to_string(),
+                    amount(e.new_deposited_amount)?,
+                    amount(e.new_withdrawn_amount)?,
+                    origin.signature,
+                    slot
+                ],
+            )?;
+            tx.execute(
+                "UPDATE streams SET deposited_amount = deposited_amount - ?2,
+                 withdrawn_amount = withdrawn_amount - ?3, updated_slot = ?4 WHERE address = ?1",
+                params![
+                    e.stream.to_string(),
+                    amount(e.new_deposited_amount)?,
+                    amount(e.new_withdrawn_amount)?,
+                    slot
+                ],
+            )?;
+        }
+        Event::StreamsMerged(e) => {
+            for merged in &e.merged_streams {
+                tx.execute(
+                    "UPDATE streams SET status = 'merged', updated_slot = ?2 WHERE address = ?1",
+                    params![merged.to_string(), slot],
+                )?;
+            }
+            tx.execute(
+                "UPDATE streams SET deposited_amount = ?2, withdrawn_amount = 0, start_time = ?3,
+                 end_time = ?4, updated_slot = ?5 WHERE address = ?1",
+                params![
+                    e.stream.to_string(),
+                    amount(e.deposited_amount)?,
+                    e.merged_at,
+                    e.end_time,
+                    slot
+                ],
+            )?;
+        }
+        Event::DelegatedWithdrawal(_)
+        | Event::StreamUpdated(_)
+        | Event::TreasuryCreated(_)
+        | Event::StreamAddedToTreasury(_)
+        | Event::LienCreated(_)
+        | Event::LienRepaid(_)
+        | Event::LienReleased(_)
+        | Event::CancelRequested(_)
+        | Event::AmendmentProposed(_)
+        | Event::AmendmentWithdrawn(_)
+        | Event::TreasuryAuditLogCreated(_)
+        | Event::TreasuryManagerSet(_)
+        | Event::SpendingLimitsSet(_)
+        | Event::TreasuryDeposited(_)
+        | Event::TreasuryWithdrawn(_)
+        | Event::ComplianceRegistryCreated(_)
+        | Event::ComplianceRegistryUpdated(_)
+        | Event::TreasuryComplianceRegistrySet(_)
+        | Event::StreamWithdrawalsFrozen(_)
+        | Event::MilestoneApproved(_)
+        | Event::YieldVaultApproved(_)
+        | Event::YieldVaultRevoked(_)
+        | Event::EscrowYieldEnabled(_)
+        | Event::EscrowYieldAccepted(_)
+        | Event::EscrowYieldRebalanced(_)
+        | Event::EscrowYieldHarvested(_) => {}
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_stream(
+    tx: &Transaction,
+    origin: &Origin,
+    stream: &Pubkey,
+    sender: &Pubkey,
+    recipient: &Pubkey,
+    mint: Option<&Pubkey>,
+    deposited_amount: u64,
+    start_time: i64,
+    end_time: i64,
+) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO streams
+         (address, sender, recipient, mint, deposited_amount, withdrawn_amount,
+          start_time, end_time, status, created_signature, updated_slot)
+         VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7, 'active', ?8, ?9)",
+        params![
+            stream.to_string(),
+            sender.to_string(),
+            recipient.to_string(),
+            mint.map(Pubkey::to_string),
+            amount(deposited_amount)?,
+            start_time,
+            end_time,
+            origin.signature,
+            origin.slot as i64
+        ],
+    )?;
+    Ok(())
+}
+
+fn insert_cancellation(
+    tx: &Transaction,
+    origin: &Origin,
+    stream: &Pubkey,
+    kind: &str,
+    paid_to_recipient: u64,
+    returned_to_sender: u64,
+    cancelled_at: i64,
+) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO cancellations
+         (signature, event_index, slot, stream, kind, paid_to_recipient, returned_to_sender, cancelled_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            origin.signature,
+            origin.event_index,
+            origin.slot as i64,
+            stream.to_string(),
+            kind,
+            amount(paid_to_recipient)?,
+            amount(returned_to_sender)?,
+            cancelled_at
+        ],
+    )?;
+    Ok(())
+}
+
+fn terminate(tx: &Transaction, stream: &Pubkey, paid_to_recipient: u64, slot: i64) -> Result<()> {
+    tx.execute(
+        "UPDATE streams SET status = 'terminated', withdrawn_amount = withdrawn_amount + ?2,
+         updated_slot = ?3 WHERE address = ?1",
+        params![stream.to_string(), amount(paid_to_recipient)?, slot],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event as _;
+    use base64::Engine;
+
+    const STREAM: Pubkey = Pubkey::new_from_array([1; 32]);
+    const SENDER: Pubkey = Pubkey::new_from_array([2; 32]);
+    const RECIPIENT: Pubkey = Pubkey::new_from_array([3; 32]);
+
+    fn open() -> Database {
+        Database::open(Path::new(":memory:"), streamflow::ID).unwrap()
+    }
+
+    fn transaction(signature: &str, slot: u64, events: &[Vec<u8>]) -> IndexedTransaction {
+        let program_id = streamflow::ID.to_string();
+        let mut logs = vec![format!("Program {} invoke [1]", program_id)];
+        for data in events {
+            let data = base64::engine::general_purpose::STANDARD.encode(data);
+            logs.push(format!("Program data: {}", data));
+        }
+        logs.push(format!("Program {} success", program_id));
+
+        IndexedTransaction {
+            signature: signature.to_string(),
+            slot,
+            block_time: Some(slot as i64),
+            logs,
+        }
+    }
+
+    fn created() -> Vec<u8> {
+        streamflow::StreamCreated {
+            stream: STREAM,
+            sender: SENDER,
+            recipient: RECIPIENT,
+            deposit_amount: 1_000,
+            start_time: 0,
+            end_time: 100,
+        }
+        .data()
+    }
+
+    fn withdrawal(amount: u64, withdrawn_amount: u64) -> Vec<u8> {
+        streamflow::Withdrawal {
+            stream: STREAM,
+            recipient: RECIPIENT,
+            amount,
+            withdrawn_amount,
+        }
+        .data()
+    }
+
+    fn withdrawn_amount(db: &Database) -> i64 {
+        db.conn
+            .query_row(
+                "SELECT withdrawn_amount FROM streams WHERE address = ?1",
+                [STREAM.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    fn withdrawals(db: &Database) -> i64 {
+        db.conn
+            .query_row("SELECT COUNT(*) FROM withdrawals", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn rollback_forgets_later_slots() {
+        let mut db = open();
+        db.index(&[
+            transaction("create", 1, &[created()]),
+            transaction("first", 2, &[withdrawal(100, 100)]),
+            transaction("second", 3, &[withdrawal(50, 150)]),
+        ])
+        .unwrap();
+        assert_eq!(withdrawn_amount(&db), 150);
+
+        db.rollback(3).unwrap();
+        assert_eq!(withdrawn_amount(&db), 100);
+        assert_eq!(withdrawals(&db), 1);
+        assert_eq!(db.checkpoint().unwrap(), Some(("first".to_string(), 2)));
+        assert_eq!(
+            db.signatures_after(0).unwrap(),
+            vec![("create".to_string(), 1), ("first".to_string(), 2)]
+        );
+
+        db.rollback(1).unwrap();
+        assert_eq!(db.checkpoint().unwrap(), None);
+        assert!(db.signatures_after(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rebuild_follows_chain_order() {
+        let mut db = open();
+        // Signatures sort opposite to block order, and the slot spans two batches
+        db.index(&[
+            transaction("z-create", 5, &[created()]),
+            transaction("y-first", 5, &[withdrawal(100, 100)]),
+        ])
+        .unwrap();
+        db.index(&[
+            transaction("x-second", 5, &[withdrawal(50, 150), withdrawal(25, 175)]),
+            transaction("later", 6, &[]),
+        ])
+        .unwrap();
+        assert_eq!(withdrawn_amount(&db), 175);
+
+        // Dropping slot 6 replays slot 5 from the events table
+        db.rollback(6).unwrap();
+        assert_eq!(withdrawn_amount(&db), 175);
+        assert_eq!(withdrawals(&db), 3);
+        assert_eq!(db.checkpoint().unwrap(), Some(("x-second".to_string(), 5)));
+    }
+
+    #[test]
+    fn resumes_after_checkpoint() {
+        let recorded = vec![
+            transaction("create", 1, &[created()]),
+            transaction("first", 2, &[withdrawal(100, 100)]),
+            transaction("second", 3, &[withdrawal(50, 150)]),
+        ];
+
+        let mut db = open();
+        assert_eq!(db.unindexed(&recorded, None).unwrap().len(), 3);
+        db.index(&recorded[..2]).unwrap();
+
+        let remaining = db.unindexed(&recorded, None).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].signature, "second");
+        db.index(remaining).unwrap();
+        assert_eq!(withdrawn_amount(&db), 150);
+        assert!(db.unindexed(&recorded, None).unwrap().is_empty());
+
+        // An explicit signature overrides the checkpoint
+        assert_eq!(db.unindexed(&recorded, Some("create")).unwrap().len(), 2);
+        assert!(db.unindexed(&recorded[1..], Some("unknown")).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! Extraction of Anchor events from transaction logs
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::Result;
+use base64::Engine;
+use solana_sdk::pubkey::Pubkey;
+
+/// Log prefix Anchor uses for `emit!`
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// Raw event payload emitted by the StreamFlow program
+#[derive(Debug, Clone)]
+pub struct RawEvent {
+    /// Position of the event within its transaction
+    pub index: u32,
+    /// Discriminator followed by the Borsh-encoded event
+    pub data: Vec<u8>,
+}
+
+/// Collect the event payloads emitted directly by `program_id`, including
+/// through CPI, while ignoring data logged by any other program
+pub fn raw_events(logs: &[String], program_id: &Pubkey) -> Vec<RawEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        if let Some(rest) = log.strip_prefix("Program ") {
+            if let Some(data) = log.strip_prefix(PROGRAM_DATA) {
+                if stack.last() == Some(&program_id.as_str()) {
+                    if let Ok(data) = base64::engine::general_purpose::STANDARD.decode(data) {
+                        events.push(RawEvent {
+                            index: events.len() as u32,
+                            data,
+                        });
+                    }
+                }
+                continue;
+            }
+
+            let mut words = rest.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => stack.push(program),
+                (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    events
+}
+
+macro_rules! events {
+    ($($event:ident),* $(,)?) => {
+        /// Decoded StreamFlow program event
+        // Events the normalized tables ignore are only kept as raw payloads
+        #[allow(dead_code)]
+        pub enum Event {
+            $($event(streamflow::$event),)*
+        }
+
+        impl Event {
+            /// Decode a raw payload, returning `None` for events the indexer does not know
+            pub fn decode(data: &[u8]) -> Result<Option<Self>> {
+                if data.len() < 8 {
+                    return Ok(None);
+                }
+                let (discriminator, mut payload) = data.split_at(8);
+                $(
+                    if discriminator == streamflow::$event::DISCRIMINATOR {
+                        let event = streamflow::$event::deserialize(&mut payload)?;
+                        return Ok(Some(Event::$event(event)));
+                    }
+                )*
+                Ok(None)
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Event::$event(_) => stringify!($event),)*
+                }
+            }
+        }
+    };
+}
+
+events!(
+    StreamCreated,
+    VestingStreamCreated,
+    Withdrawal,
+    DelegatedWithdrawal,
+    StreamCanceled,
+    StreamTransferred,
+    StreamUpdated,
+    StreamToppedUp,
+    StreamSplit,
+    StreamsMerged,
+    SenderTransferred,
+    GoodLeaverTerminated,
+    BadLeaverTerminated,
+    TreasuryCreated,
+    StreamAddedToTreasury,
+    LienCreated,
+    LienRepaid,
+    LienReleased,
+    CancelRequested,
+    AmendmentProposed,
+    AmendmentWithdrawn,
+    StreamAmended,
+    TreasuryAuditLogCreated,
+    TreasuryManagerSet,
+    SpendingLimitsSet,
+    TreasuryDeposited,
+    TreasuryWithdrawn,
+    ComplianceRegistryCreated,
+    ComplianceRegistryUpdated,
+    TreasuryComplianceRegistrySet,
+    StreamWithdrawalsFrozen,
+    MilestoneApproved,
+    YieldVaultApproved,
+    YieldVaultRevoked,
+    EscrowYieldEnabled,
+    EscrowYieldAccepted,
+    EscrowYieldRebalanced,
+    EscrowYieldHarvested,
+);
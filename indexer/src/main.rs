@@ -0,0 +1,165 @@
+//! Indexes StreamFlow program events into SQLite
+
+mod db;
+mod decode;
+mod source;
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+
+use db::Database;
+
+#[derive(Parser)]
+#[command(name = "streamflow-indexer", version, about = "Index StreamFlow events into SQLite")]
+struct Cli {
+    /// SQLite database file
+    #[arg(long, env = "STREAMFLOW_INDEXER_DB", default_value = "streamflow.sqlite")]
+    db: PathBuf,
+
+    /// Program whose events are indexed
+    #[arg(long, default_value_t = streamflow::ID)]
+    program_id: Pubkey,
+
+    /// Start after this signature instead of the stored checkpoint
+    #[arg(long)]
+    from_signature: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Follow a validator over RPC
+    Rpc {
+        #[arg(
+            long,
+            short = 'u',
+            env = "STREAMFLOW_RPC_URL",
+            default_value = "http://127.0.0.1:8899"
+        )]
+        url: String,
+        /// Seconds between polls
+        #[arg(long, default_value_t = 5)]
+        poll_interval: u64,
+        /// Index what is available and exit instead of polling
+        #[arg(long)]
+        once: bool,
+    },
+    /// Replay a directory of recorded `getTransaction` JSON responses, named so
+    /// that transactions of the same slot sort in block order
+    Replay { dir: PathBuf },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut db = Database::open(&cli.db, cli.program_id)?;
+
+    match cli.command {
+        Command::Rpc {
+            url,
+            poll_interval,
+            once,
+        } => {
+            let rpc = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            if let Some(signature) = &cli.from_signature {
+                let slot = transaction_slot(&rpc, signature).await?;
+                db.set_checkpoint(signature, slot)?;
+            }
+
+            loop {
+                handle_reorgs(&rpc, &mut db).await?;
+
+                let until = db.checkpoint()?.map(|(signature, _)| signature);
+                let transactions =
+                    source::fetch_since(&rpc, &cli.program_id, until.as_deref()).await?;
+                if !transactions.is_empty() {
+                    let events = db.index(&transactions)?;
+                    println!(
+                        "indexed {} transactions with {} events",
+                        transactions.len(),
+                        events
+                    );
+                }
+
+                if once {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_secs(poll_interval)).await;
+            }
+        }
+        Command::Replay { dir } => {
+            let recorded = source::read_directory(&dir)?;
+            let transactions = db
+                .unindexed(&recorded, cli.from_signature.as_deref())
+                .with_context(|| format!("cannot resume replay of {}", dir.display()))?;
+
+            let events = db.index(transactions)?;
+            println!(
+                "indexed {} transactions with {} events",
+                transactions.len(),
+                events
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Roll back transactions that were confirmed but never finalized
+async fn handle_reorgs(rpc: &RpcClient, db: &mut Database) -> Result<()> {
+    let finalized = rpc
+        .get_slot_with_commitment(CommitmentConfig::finalized())
+        .await?;
+    let unfinalized = db.signatures_after(finalized)?;
+    if unfinalized.is_empty() {
+        return Ok(());
+    }
+
+    let signatures: Vec<String> = unfinalized
+        .iter()
+        .map(|(signature, _)| signature.clone())
+        .collect();
+    let dropped = source::dropped_signatures(rpc, &signatures).await?;
+    let rollback_slot = unfinalized
+        .iter()
+        .filter(|(signature, _)| dropped.contains(signature))
+        .map(|(_, slot)| *slot)
+        .min();
+
+    if let Some(slot) = rollback_slot {
+        println!(
+            "{} transactions were dropped by a fork, rolling back to slot {}",
+            dropped.len(),
+            slot
+        );
+        db.rollback(slot)?;
+    }
+
+    Ok(())
+}
+
+async fn transaction_slot(rpc: &RpcClient, signature: &str) -> Result<u64> {
+    let transaction = rpc
+        .get_transaction_with_config(
+            &Signature::from_str(signature)?,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(rpc.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?;
+    Ok(transaction.slot)
+}
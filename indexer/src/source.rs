@@ -0,0 +1,173 @@
+//! Transaction sources: a live validator or a directory of recorded transactions
+
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiTransactionEncoding,
+};
+
+/// Page size of `getSignaturesForAddress`
+const SIGNATURE_PAGE_SIZE: usize = 1_000;
+
+/// A successful transaction that touched the program
+#[derive(Debug, Clone)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub logs: Vec<String>,
+}
+
+impl IndexedTransaction {
+    /// Convert an RPC transaction, returning `None` for failed transactions whose
+    /// events never took effect
+    pub fn from_encoded(
+        signature: Option<String>,
+        transaction: EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<Option<Self>> {
+        let meta = transaction
+            .transaction
+            .meta
+            .ok_or_else(|| anyhow!("transaction has no status meta"))?;
+        if meta.err.is_some() {
+            return Ok(None);
+        }
+
+        let signature = match signature {
+            Some(signature) => signature,
+            None => first_signature(&transaction.transaction.transaction)?,
+        };
+        let logs = match meta.log_messages {
+            OptionSerializer::Some(logs) => logs,
+            _ => Vec::new(),
+        };
+
+        Ok(Some(Self {
+            signature,
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+            logs,
+        }))
+    }
+}
+
+fn first_signature(transaction: &EncodedTransaction) -> Result<String> {
+    match transaction {
+        EncodedTransaction::Json(ui) => ui.signatures.first().cloned(),
+        other => other
+            .decode()
+            .and_then(|tx| tx.signatures.first().map(|signature| signature.to_string())),
+    }
+    .ok_or_else(|| anyhow!("transaction has no signature"))
+}
+
+/// Read recorded `getTransaction` responses (one JSON file each) ordered by slot.
+/// Responses carry no position within their block, so transactions of the same
+/// slot are ordered by file name.
+pub fn read_directory(dir: &Path) -> Result<Vec<IndexedTransaction>> {
+    let mut transactions = Vec::new();
+
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+
+        let encoded: EncodedConfirmedTransactionWithStatusMeta =
+            serde_json::from_slice(&std::fs::read(&path)?)
+                .with_context(|| format!("failed to parse {}", path.display()))?;
+        if let Some(transaction) = IndexedTransaction::from_encoded(None, encoded)? {
+            transactions.push((path, transaction));
+        }
+    }
+
+    transactions.sort_by(|(a_path, a), (b_path, b)| (a.slot, a_path).cmp(&(b.slot, b_path)));
+    Ok(transactions
+        .into_iter()
+        .map(|(_, transaction)| transaction)
+        .collect())
+}
+
+/// Fetch every program transaction newer than `until`, oldest first
+pub async fn fetch_since(
+    rpc: &RpcClient,
+    program_id: &solana_sdk::pubkey::Pubkey,
+    until: Option<&str>,
+) -> Result<Vec<IndexedTransaction>> {
+    let until = until.map(Signature::from_str).transpose()?;
+    let mut before = None;
+    let mut signatures = Vec::new();
+
+    loop {
+        let page = rpc
+            .get_signatures_for_address_with_config(
+                program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURE_PAGE_SIZE),
+                    commitment: Some(rpc.commitment()),
+                },
+            )
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+        let full_page = page.len() == SIGNATURE_PAGE_SIZE;
+        signatures.extend(page.into_iter().filter(|status| status.err.is_none()));
+        if !full_page {
+            break;
+        }
+    }
+
+    let mut transactions = Vec::with_capacity(signatures.len());
+    for status in signatures.into_iter().rev() {
+        let encoded = rpc
+            .get_transaction_with_config(
+                &Signature::from_str(&status.signature)?,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Json),
+                    commitment: Some(rpc.commitment()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await?;
+        let signature = Some(status.signature);
+        if let Some(transaction) = IndexedTransaction::from_encoded(signature, encoded)? {
+            transactions.push(transaction);
+        }
+    }
+
+    Ok(transactions)
+}
+
+/// Signatures among `signatures` that the cluster no longer knows about
+pub async fn dropped_signatures(rpc: &RpcClient, signatures: &[String]) -> Result<Vec<String>> {
+    let mut dropped = Vec::new();
+
+    for chunk in signatures.chunks(256) {
+        let parsed = chunk
+            .iter()
+            .map(|signature| Signature::from_str(signature))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let statuses = rpc.get_signature_statuses(&parsed).await?.value;
+
+        for (signature, status) in chunk.iter().zip(statuses) {
+            if status.is_none() {
+                dropped.push(signature.clone());
+            }
+        }
+    }
+
+    Ok(dropped)
+}
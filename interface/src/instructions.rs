@@ -0,0 +1,555 @@
+//! Instructions of the StreamFlow program
+
+interface! {
+    accounts {
+        CreateStream {
+            stream: writable,
+            sender: writable signer,
+            sender_profile: optional writable,
+            recipient,
+            mint,
+            sender_token_account: writable,
+            escrow_token_account: writable,
+            token_program,
+            system_program,
+            rent,
+        }
+
+        Withdraw {
+            stream: writable,
+            /// Recipient, position holder or delegated withdraw authority
+            recipient: signer,
+            escrow_token_account: writable,
+            /// Destination of the withdrawal, checked against the stream in the instruction
+            recipient_token_account: writable,
+            /// Position token held by the recipient when the stream is tokenized
+            position_token_account: optional,
+            /// Lender repayment account, required while the stream has a lien
+            lender_token_account: optional writable,
+            /// Yield vault program, required while part of the escrow is deposited in it
+            yield_vault_program: optional,
+            yield_vault: optional writable,
+            yield_vault_token_account: optional writable,
+            token_program,
+        }
+
+        CancelStream {
+            stream: writable,
+            authority: signer,
+            escrow_token_account: writable,
+            sender_token_account: writable,
+            recipient_token_account: writable,
+            /// Position token account of the holder when the stream is tokenized
+            position_token_account: optional,
+            /// Yield vault program, required once escrow yield is enabled
+            yield_vault_program: optional,
+            yield_vault: optional writable,
+            yield_vault_token_account: optional writable,
+            token_program,
+        }
+
+        TransferStream {
+            stream: writable,
+            authority: signer,
+            /// Required when the stream is bound to a compliance registry
+            compliance_registry: optional,
+        }
+
+        UpdateStream {
+            stream: writable,
+            sender: signer,
+        }
+
+        CreateTreasury {
+            treasury: writable,
+            authority: writable signer,
+            system_program,
+        }
+
+        AddStreamToTreasury {
+            treasury: writable,
+            stream: writable,
+            authority: signer,
+            /// Required once the treasury has an audit log
+            audit_log: optional writable,
+            /// Required when the treasury is bound to a compliance registry
+            compliance_registry: optional,
+        }
+
+        CreateVestingStream {
+            stream: writable,
+            escrow_token_account: writable,
+            sender_token_account: writable,
+            sender: writable signer,
+            /// Supplies the stream id when the sender uses sequential ids
+            sender_profile: optional writable,
+            mint,
+            /// Approval of the yield vault program, required with `yield_share_bps`
+            approved_yield_vault: optional,
+            yield_vault: optional,
+            yield_vault_token_account: optional,
+            token_program,
+            system_program,
+            rent,
+        }
+
+        ApproveMilestone {
+            stream: writable,
+            instructions,
+        }
+
+        TerminateVesting {
+            stream: writable,
+            sender: writable signer,
+            sender_token_account: writable,
+            recipient_token_account: writable,
+            /// Position token account of the holder when the stream is tokenized
+            position_token_account: optional,
+            escrow_token_account: writable,
+            token_program,
+        }
+
+        FireAccelerationTrigger {
+            stream: writable,
+            authority: signer,
+        }
+
+        SplitStream {
+            stream: writable,
+            new_stream: writable,
+            escrow_token_account: writable,
+            new_escrow_token_account: writable,
+            recipient: writable signer,
+            mint,
+            /// Required when the stream is bound to a compliance registry
+            compliance_registry: optional,
+            token_program,
+            system_program,
+            rent,
+        }
+
+        MergeStreams {
+            stream: writable,
+            escrow_token_account: writable,
+            recipient_token_account: writable,
+            sender: writable signer,
+            recipient: signer,
+            token_program,
+        }
+
+        MintPositionToken {
+            stream: writable,
+            position_mint: writable,
+            recipient_position_account: writable,
+            metadata: writable,
+            sender: writable signer,
+            recipient,
+            token_program,
+            associated_token_program,
+            token_metadata_program,
+            system_program,
+            rent,
+        }
+
+        SetWithdrawDelegate {
+            stream: writable,
+            recipient: signer,
+            /// Required when setting a destination
+            destination_token_account: optional,
+            /// Required when the stream is bound to a compliance registry
+            compliance_registry: optional,
+        }
+
+        ProposeSenderTransfer {
+            stream: writable,
+            sender: signer,
+        }
+
+        AcceptSenderTransfer {
+            stream: writable,
+            new_sender: signer,
+        }
+
+        CreateSenderProfile {
+            sender_profile: writable,
+            sender: writable signer,
+            system_program,
+        }
+
+        TopupStream {
+            stream: writable,
+            escrow_token_account: writable,
+            sender_token_account: writable,
+            sender: signer,
+            token_program,
+        }
+
+        CreateLien {
+            stream: writable,
+            recipient: signer,
+            /// Required when the sender can cancel or terminate the stream
+            sender: optional signer,
+            /// Account receiving repayments, owned by the lender
+            lender_token_account,
+            /// Required when the stream is bound to a compliance registry
+            compliance_registry: optional,
+        }
+
+        RepayLien {
+            stream: writable,
+            payer: signer,
+            payer_token_account: writable,
+            lender_token_account: writable,
+            token_program,
+        }
+
+        ReleaseLien {
+            stream: writable,
+            lender: signer,
+        }
+
+        RequestCancel {
+            stream: writable,
+            authority: signer,
+            /// Position token account of the holder when the stream is tokenized
+            position_token_account: optional,
+        }
+
+        FinalizeCancel {
+            stream: writable,
+            sender_token_account: writable,
+            recipient_token_account: writable,
+            /// Position token account of the holder when the stream is tokenized
+            position_token_account: optional,
+            escrow_token_account: writable,
+            /// Yield vault program, required once escrow yield is enabled
+            yield_vault_program: optional,
+            yield_vault: optional writable,
+            yield_vault_token_account: optional writable,
+            token_program,
+        }
+
+        ProposeAmendment {
+            stream: writable,
+            sender: signer,
+        }
+
+        AcceptAmendment {
+            stream: writable,
+            recipient: signer,
+        }
+
+        InitTreasuryAuditLog {
+            treasury: writable,
+            audit_log: writable,
+            authority: writable signer,
+            system_program,
+        }
+
+        ManageTreasury {
+            treasury: writable,
+            authority: signer,
+            /// Required once the treasury has an audit log
+            audit_log: optional writable,
+        }
+
+        DepositToTreasury {
+            treasury,
+            treasury_vault: writable,
+            depositor: signer,
+            depositor_token_account: writable,
+            mint,
+            /// Required once the treasury has an audit log
+            audit_log: optional writable,
+            token_program,
+        }
+
+        WithdrawFromTreasury {
+            treasury,
+            treasury_vault: writable,
+            authority: signer,
+            destination_token_account: writable,
+            mint,
+            /// Required once the treasury has an audit log
+            audit_log: optional writable,
+            token_program,
+        }
+
+        CreateTreasuryStream {
+            treasury: writable,
+            treasury_vault: writable,
+            stream: writable,
+            escrow_token_account: writable,
+            /// The treasury authority or one of its managers
+            manager: writable signer,
+            mint,
+            /// Required once the treasury has an audit log
+            audit_log: optional writable,
+            /// Required when the treasury is bound to a compliance registry
+            compliance_registry: optional,
+            token_program,
+            system_program,
+            rent,
+        }
+
+        CreateComplianceRegistry {
+            registry: writable,
+            authority: writable signer,
+            system_program,
+        }
+
+        UpdateComplianceRegistry {
+            registry: writable,
+            authority: signer,
+        }
+
+        SetTreasuryComplianceRegistry {
+            treasury: writable,
+            authority: signer,
+            registry: optional,
+            /// Required once the treasury has an audit log
+            audit_log: optional writable,
+        }
+
+        FreezeStreamWithdrawals {
+            stream: writable,
+            registry,
+            authority: signer,
+        }
+
+        ApproveYieldVault {
+            approved_yield_vault: writable,
+            vault_program,
+            authority: writable signer,
+            program,
+            program_data,
+            system_program,
+        }
+
+        RevokeYieldVault {
+            approved_yield_vault: writable,
+            authority: writable signer,
+            program,
+            program_data,
+        }
+
+        AcceptEscrowYield {
+            stream: writable,
+            recipient: signer,
+        }
+
+        RebalanceEscrowYield {
+            stream: writable,
+            escrow_token_account: writable,
+            approved_yield_vault,
+            vault_program,
+            vault: writable,
+            vault_token_account: writable,
+            token_program,
+        }
+
+        HarvestEscrowYield {
+            stream: writable,
+            escrow_token_account: writable,
+            sender_token_account: writable,
+            recipient_token_account: writable,
+            /// Position token account of the holder when the stream is tokenized
+            position_token_account: optional,
+            /// Lender repayment account, required while the stream has a lien
+            lender_token_account: optional writable,
+            vault_program,
+            vault: writable,
+            vault_token_account: writable,
+            token_program,
+        }
+
+        ViewStream {
+            stream,
+        }
+    }
+    instructions {
+        create_stream(
+            CreateStream,
+            stream_id: [u8; 32],
+            recipient: Pubkey,
+            deposited_amount: u64,
+            start_time: i64,
+            end_time: i64,
+            cliff_time: Option<i64>,
+            cancelable_by_sender: bool,
+            cancelable_by_recipient: bool,
+            cancel_notice_period: u64,
+            transferable_by_sender: bool,
+            transferable_by_recipient: bool,
+            automatic_withdrawal: bool,
+            period: u64,
+        )
+            = CreateStream [71, 188, 111, 127, 108, 40, 229, 158];
+        withdraw(Withdraw, amount: u64) = Withdraw [183, 18, 70, 156, 148, 109, 161, 34];
+        cancel_stream(CancelStream) = CancelStream [218, 221, 38, 25, 177, 207, 188, 91];
+        transfer_stream(TransferStream, new_recipient: Pubkey, compliance_proof: Vec<[u8; 32]>)
+            = TransferStream [247, 122, 103, 172, 236, 35, 228, 204];
+        update_stream(
+            UpdateStream,
+            cancelable_by_sender: Option<bool>,
+            cancelable_by_recipient: Option<bool>,
+            automatic_withdrawal: Option<bool>,
+        )
+            = UpdateStream [132, 239, 118, 208, 84, 153, 47, 100];
+        create_treasury(CreateTreasury, name: String, description: String, auto_close: bool)
+            = CreateTreasury [254, 98, 217, 51, 25, 88, 140, 45];
+        add_stream_to_treasury(AddStreamToTreasury, compliance_proof: Vec<[u8; 32]>)
+            = AddStreamToTreasury [74, 189, 120, 33, 178, 110, 243, 187];
+        create_vesting_stream(
+            CreateVestingStream,
+            stream_id: [u8; 32],
+            recipient: Pubkey,
+            start_time: i64,
+            cliff_time: i64,
+            segments: Vec<VestingSegment>,
+            cancelable_by_sender: bool,
+            cancelable_by_recipient: bool,
+            cancel_notice_period: u64,
+            leaver_policy: LeaverPolicy,
+            acceleration: AccelerationClause,
+            stream_name: String,
+            yield_share_bps: Option<u16>,
+        )
+            = CreateVestingStream [27, 198, 41, 1, 207, 153, 80, 84];
+        create_monthly_vesting_stream(
+            CreateVestingStream,
+            stream_id: [u8; 32],
+            recipient: Pubkey,
+            start_time: i64,
+            cliff_time: i64,
+            deposited_amount: u64,
+            months: u16,
+            cancelable_by_sender: bool,
+            cancelable_by_recipient: bool,
+            cancel_notice_period: u64,
+            leaver_policy: LeaverPolicy,
+            acceleration: AccelerationClause,
+            stream_name: String,
+            yield_share_bps: Option<u16>,
+        )
+            = CreateMonthlyVestingStream [103, 143, 102, 212, 187, 77, 131, 46];
+        create_milestone_stream(
+            CreateVestingStream,
+            stream_id: [u8; 32],
+            recipient: Pubkey,
+            start_time: i64,
+            end_time: i64,
+            attestor: Pubkey,
+            milestone_amounts: Vec<u64>,
+            cancelable_by_sender: bool,
+            cancelable_by_recipient: bool,
+            cancel_notice_period: u64,
+            leaver_policy: LeaverPolicy,
+            acceleration: AccelerationClause,
+            stream_name: String,
+            yield_share_bps: Option<u16>,
+        )
+            = CreateMilestoneStream [162, 112, 235, 171, 104, 156, 63, 203];
+        approve_milestone(ApproveMilestone, milestone_index: u8)
+            = ApproveMilestone [145, 85, 92, 60, 50, 130, 219, 106];
+        terminate_vesting(TerminateVesting, termination: TerminationKind)
+            = TerminateVesting [247, 225, 163, 102, 129, 79, 93, 40];
+        fire_acceleration_trigger(FireAccelerationTrigger, trigger: AccelerationTrigger)
+            = FireAccelerationTrigger [76, 199, 127, 98, 36, 203, 110, 36];
+        split_stream(
+            SplitStream,
+            new_recipient: Pubkey,
+            split_amount: SplitAmount,
+            compliance_proof: Vec<[u8; 32]>,
+        )
+            = SplitStream [125, 14, 91, 8, 20, 53, 5, 212];
+        merge_streams(MergeStreams, end_time: i64)
+            = MergeStreams [232, 128, 177, 83, 107, 36, 16, 141];
+        mint_position_token(MintPositionToken)
+            = MintPositionToken [156, 132, 97, 158, 211, 21, 23, 3];
+        set_withdraw_delegate(
+            SetWithdrawDelegate,
+            withdraw_authority: Option<Pubkey>,
+            destination: Option<Pubkey>,
+            authority_proof: Vec<[u8; 32]>,
+            destination_proof: Vec<[u8; 32]>,
+        )
+            = SetWithdrawDelegate [31, 78, 248, 148, 225, 12, 111, 150];
+        propose_sender_transfer(ProposeSenderTransfer, new_sender: Pubkey)
+            = ProposeSenderTransfer [61, 26, 172, 133, 41, 249, 228, 57];
+        accept_sender_transfer(AcceptSenderTransfer)
+            = AcceptSenderTransfer [237, 95, 213, 59, 207, 222, 254, 22];
+        create_sender_profile(CreateSenderProfile)
+            = CreateSenderProfile [201, 9, 50, 104, 191, 17, 59, 74];
+        topup_stream(TopupStream, amount: u64) = TopupStream [131, 75, 71, 246, 244, 114, 183, 167];
+        create_lien(CreateLien, lender: Pubkey, amount: u64, compliance_proof: Vec<[u8; 32]>)
+            = CreateLien [2, 142, 211, 169, 207, 72, 191, 1];
+        repay_lien(RepayLien, amount: u64) = RepayLien [156, 155, 140, 231, 240, 155, 121, 58];
+        release_lien(ReleaseLien) = ReleaseLien [157, 159, 226, 227, 86, 43, 30, 169];
+        request_cancel(RequestCancel, effective_at: i64)
+            = RequestCancel [244, 78, 42, 227, 165, 174, 94, 167];
+        finalize_cancel(FinalizeCancel) = FinalizeCancel [6, 200, 45, 123, 144, 47, 207, 102];
+        propose_amendment(ProposeAmendment, terms: StreamTerms)
+            = ProposeAmendment [237, 97, 111, 166, 137, 200, 30, 39];
+        accept_amendment(AcceptAmendment, terms: StreamTerms)
+            = AcceptAmendment [180, 212, 55, 94, 76, 191, 6, 165];
+        init_treasury_audit_log(InitTreasuryAuditLog)
+            = InitTreasuryAuditLog [132, 19, 4, 12, 9, 211, 63, 134];
+        set_treasury_manager(ManageTreasury, manager: Pubkey, access_level: AccessLevel)
+            = SetTreasuryManager [86, 146, 89, 57, 48, 114, 152, 70];
+        remove_treasury_manager(ManageTreasury, manager: Pubkey)
+            = RemoveTreasuryManager [82, 21, 184, 234, 157, 232, 48, 70];
+        set_spending_limits(
+            ManageTreasury,
+            daily_limit: u64,
+            monthly_limit: u64,
+            admin_max_per_stream: u64,
+            manager_max_per_stream: u64,
+        )
+            = SetSpendingLimits [130, 18, 12, 226, 195, 58, 85, 40];
+        deposit_to_treasury(DepositToTreasury, amount: u64)
+            = DepositToTreasury [10, 195, 112, 242, 107, 206, 240, 198];
+        withdraw_from_treasury(WithdrawFromTreasury, amount: u64)
+            = WithdrawFromTreasury [0, 164, 86, 76, 56, 72, 12, 170];
+        create_treasury_stream(
+            CreateTreasuryStream,
+            stream_id: [u8; 32],
+            recipient: Pubkey,
+            deposited_amount: u64,
+            start_time: i64,
+            end_time: i64,
+            cliff_time: i64,
+            period: u64,
+            cancelable_by_sender: bool,
+            cancelable_by_recipient: bool,
+            stream_name: String,
+            compliance_proof: Vec<[u8; 32]>,
+        )
+            = CreateTreasuryStream [136, 164, 84, 225, 25, 34, 186, 56];
+        create_compliance_registry(CreateComplianceRegistry, mode: ComplianceMode)
+            = CreateComplianceRegistry [212, 170, 125, 185, 87, 70, 16, 208];
+        update_compliance_entries(UpdateComplianceRegistry, add: Vec<Pubkey>, remove: Vec<Pubkey>)
+            = UpdateComplianceEntries [210, 227, 94, 21, 160, 53, 231, 188];
+        set_compliance_merkle_root(UpdateComplianceRegistry, merkle_root: [u8; 32])
+            = SetComplianceMerkleRoot [91, 231, 178, 102, 180, 18, 187, 248];
+        set_treasury_compliance_registry(SetTreasuryComplianceRegistry)
+            = SetTreasuryComplianceRegistry [101, 226, 36, 187, 154, 127, 104, 227];
+        freeze_stream_withdrawals(FreezeStreamWithdrawals, frozen: bool)
+            = FreezeStreamWithdrawals [1, 215, 107, 227, 208, 25, 147, 211];
+        approve_yield_vault(ApproveYieldVault, adapter: YieldAdapter)
+            = ApproveYieldVault [152, 221, 207, 58, 118, 19, 150, 162];
+        revoke_yield_vault(RevokeYieldVault)
+            = RevokeYieldVault [42, 21, 122, 164, 20, 187, 176, 19];
+        accept_escrow_yield(AcceptEscrowYield)
+            = AcceptEscrowYield [107, 51, 249, 33, 31, 106, 103, 148];
+        rebalance_escrow_yield(RebalanceEscrowYield)
+            = RebalanceEscrowYield [24, 40, 130, 129, 240, 255, 214, 90];
+        harvest_escrow_yield(HarvestEscrowYield)
+            = HarvestEscrowYield [90, 77, 70, 14, 140, 133, 135, 59];
+        get_withdrawable(ViewStream) -> StreamQuote
+            = GetWithdrawable [135, 127, 40, 216, 176, 35, 139, 103];
+        get_streamed(ViewStream) -> StreamQuote = GetStreamed [65, 223, 83, 165, 72, 179, 25, 176];
+        get_stream_summary(ViewStream) -> StreamSummary
+            = GetStreamSummary [61, 62, 207, 170, 23, 45, 114, 13];
+    }
+}
@@ -0,0 +1,40 @@
+//! Stable CPI interface of the StreamFlow program
+//!
+//! Other on-chain programs depend on this crate instead of the program crate.
+//! It does not link the program: the account layouts, instruction data and CPI
+//! helpers are defined here and checked against the program by the crate tests.
+//!
+//! - [`cpi`]: one function per instruction taking a `CpiContext` over the account
+//!   structs in [`cpi::accounts`]. The quote instructions (`get_withdrawable`,
+//!   `get_streamed`, `get_stream_summary`) return their result as `Return<T>`.
+//! - [`instruction`]: instruction data, for callers building instructions by hand.
+//! - [`state`]: account layouts and the types used in instruction arguments.
+//! - [`pda`]: seeds and address derivation for every program derived account.
+//!
+//! When the stream sender or recipient is itself a PDA of the calling program,
+//! sign the CPI with that PDA's own seeds through `CpiContext::new_with_signer`;
+//! the signature carries over to the account creation and token transfers the
+//! program performs on the caller's behalf.
+
+use anchor_lang::prelude::*;
+
+#[macro_use]
+mod macros;
+mod instructions;
+pub mod pda;
+pub mod state;
+
+pub use instructions::{cpi, instruction};
+pub use state::{StreamQuote, StreamSummary};
+
+declare_id!("11111111111111111111111111111112");
+
+/// The StreamFlow program, for `Program<'info, Streamflow>` accounts
+#[derive(Clone)]
+pub struct Streamflow;
+
+impl anchor_lang::Id for Streamflow {
+    fn id() -> Pubkey {
+        ID
+    }
+}
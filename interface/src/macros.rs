@@ -0,0 +1,166 @@
+//! Declarative equivalent of the `instruction` and `cpi` modules Anchor generates
+//! for the program, so callers do not have to link the program itself.
+//!
+//! Account flags follow the order `optional writable signer`. Discriminators are
+//! the first 8 bytes of `sha256("global:<instruction name>")`, written out so the
+//! interface does not depend on the program to compute them.
+
+/// Declares the `instruction` module and the `cpi` module with its `accounts`
+macro_rules! interface {
+    (
+        accounts {$(
+            $(#[doc = $doc:literal])*
+            $accounts:ident {
+                $(
+                    $(#[doc = $account_doc:literal])*
+                    $account:ident $(: $($flag:ident)+)?
+                ),* $(,)?
+            }
+        )*}
+        instructions {$(
+            $name:ident($ix_accounts:ident $(, $arg:ident: $arg_ty:ty)* $(,)?) $(-> $ret:ty)?
+                = $data:ident $discriminator:expr;
+        )*}
+    ) => {
+        /// Instruction data, for callers building instructions by hand
+        pub mod instruction {
+            use anchor_lang::prelude::*;
+            #[allow(unused_imports)]
+            use crate::state::*;
+
+            $(
+                #[derive(AnchorSerialize, AnchorDeserialize)]
+                pub struct $data {
+                    $(pub $arg: $arg_ty,)*
+                }
+
+                impl anchor_lang::Discriminator for $data {
+                    const DISCRIMINATOR: [u8; 8] = $discriminator;
+                }
+
+                impl anchor_lang::InstructionData for $data {}
+            )*
+        }
+
+        /// One function per instruction taking a `CpiContext` over the account
+        /// structs in [`accounts`](cpi::accounts)
+        pub mod cpi {
+            use anchor_lang::prelude::*;
+            use anchor_lang::solana_program::instruction::Instruction;
+            use anchor_lang::solana_program::program::{get_return_data, invoke_signed};
+            use anchor_lang::InstructionData;
+            use std::marker::PhantomData;
+            #[allow(unused_imports)]
+            use crate::state::*;
+
+            /// Accounts of every instruction, in the order the program expects them
+            pub mod accounts {
+                use anchor_lang::prelude::*;
+
+                $(
+                    $(#[doc = $doc])*
+                    pub struct $accounts<'info> {
+                        $(
+                            $(#[doc = $account_doc])*
+                            pub $account: account_type!('info; $($($flag)+)?),
+                        )*
+                    }
+
+                    impl<'info> ToAccountMetas for $accounts<'info> {
+                        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+                            vec![$(account_meta!(self.$account; $($($flag)+)?)),*]
+                        }
+                    }
+
+                    impl<'info> ToAccountInfos<'info> for $accounts<'info> {
+                        fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+                            [$(account_info!(self.$account; $($($flag)+)?)),*]
+                                .into_iter()
+                                .flatten()
+                                .collect()
+                        }
+                    }
+                )*
+            }
+
+            /// Value returned by an instruction through `set_return_data`
+            pub struct Return<T> {
+                phantom: PhantomData<T>,
+            }
+
+            impl<T: AnchorDeserialize> Return<T> {
+                pub fn get(&self) -> T {
+                    let (_key, data) = get_return_data().unwrap();
+                    T::try_from_slice(&data).unwrap()
+                }
+            }
+
+            fn invoke<'info, T: ToAccountMetas + ToAccountInfos<'info>>(
+                ctx: CpiContext<'_, '_, '_, 'info, T>,
+                data: impl InstructionData,
+            ) -> Result<()> {
+                let instruction = Instruction {
+                    program_id: *ctx.program.key,
+                    accounts: ctx.to_account_metas(None),
+                    data: data.data(),
+                };
+                invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds)
+                    .map_err(Into::into)
+            }
+
+            $(
+                cpi_fn!(
+                    $name($ix_accounts $(, $arg: $arg_ty)*) $(-> $ret)?
+                        = $data
+                );
+            )*
+        }
+    };
+}
+
+macro_rules! account_type {
+    ($lt:lifetime; optional $($flag:ident)*) => { Option<AccountInfo<$lt>> };
+    ($lt:lifetime; $($flag:ident)*) => { AccountInfo<$lt> };
+}
+
+macro_rules! account_meta {
+    ($account:expr; optional $($flag:ident)*) => {
+        match &$account {
+            Some(account) => account_meta!(account; $($flag)*),
+            None => AccountMeta::new_readonly(crate::ID, false),
+        }
+    };
+    ($account:expr; writable signer) => { AccountMeta::new(*$account.key, true) };
+    ($account:expr; writable) => { AccountMeta::new(*$account.key, false) };
+    ($account:expr; signer) => { AccountMeta::new_readonly(*$account.key, true) };
+    ($account:expr;) => { AccountMeta::new_readonly(*$account.key, false) };
+}
+
+macro_rules! account_info {
+    ($account:expr; optional $($flag:ident)*) => { $account.clone() };
+    ($account:expr; $($flag:ident)*) => { Some($account.clone()) };
+}
+
+macro_rules! cpi_fn {
+    ($name:ident($accounts:ident $(, $arg:ident: $arg_ty:ty)*) -> $ret:ty = $data:ident) => {
+        #[allow(clippy::too_many_arguments)]
+        pub fn $name<'a, 'b, 'c, 'info>(
+            ctx: CpiContext<'a, 'b, 'c, 'info, accounts::$accounts<'info>>,
+            $($arg: $arg_ty,)*
+        ) -> Result<Return<$ret>> {
+            invoke(ctx, super::instruction::$data { $($arg,)* })?;
+            Ok(Return {
+                phantom: PhantomData,
+            })
+        }
+    };
+    ($name:ident($accounts:ident $(, $arg:ident: $arg_ty:ty)*) = $data:ident) => {
+        #[allow(clippy::too_many_arguments)]
+        pub fn $name<'a, 'b, 'c, 'info>(
+            ctx: CpiContext<'a, 'b, 'c, 'info, accounts::$accounts<'info>>,
+            $($arg: $arg_ty,)*
+        ) -> Result<()> {
+            invoke(ctx, super::instruction::$data { $($arg,)* })
+        }
+    };
+}
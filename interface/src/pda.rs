@@ -0,0 +1,101 @@
+//! Seeds and addresses of the StreamFlow program derived accounts
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::ID;
+
+/// `[STREAM_SEED, creator, stream_id]`
+pub const STREAM_SEED: &[u8] = b"stream";
+
+/// Hashed with the parent stream and split index into the id of a split stream
+pub const SPLIT_SEED: &[u8] = b"split";
+
+/// `[ESCROW_SEED, stream]`
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// `[SENDER_PROFILE_SEED, sender]`
+pub const SENDER_PROFILE_SEED: &[u8] = b"sender_profile";
+
+/// `[TREASURY_SEED, authority]`
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// `[TREASURY_AUDIT_LOG_SEED, treasury]`
+pub const TREASURY_AUDIT_LOG_SEED: &[u8] = b"treasury_audit_log";
+
+/// `[COMPLIANCE_REGISTRY_SEED, authority]`
+pub const COMPLIANCE_REGISTRY_SEED: &[u8] = b"compliance_registry";
+
+/// `[APPROVED_YIELD_VAULT_SEED, vault_program]`
+pub const APPROVED_YIELD_VAULT_SEED: &[u8] = b"yield_vault";
+
+/// `[POSITION_SEED, stream]`
+pub const POSITION_SEED: &[u8] = b"position";
+
+/// Stream account created by `creator` under `stream_id`
+pub fn find_stream_address(creator: &Pubkey, stream_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[STREAM_SEED, creator.as_ref(), stream_id.as_ref()],
+        &ID,
+    )
+}
+
+/// Stream account created by `creator` from its sender profile nonce
+pub fn find_stream_address_by_nonce(creator: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    find_stream_address(creator, &nonce_stream_id(nonce))
+}
+
+/// Escrow token account holding the funds of `stream`
+pub fn find_escrow_address(stream: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ESCROW_SEED, stream.as_ref()], &ID)
+}
+
+/// Sender profile handing out sequential stream ids to `sender`
+pub fn find_sender_profile_address(sender: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SENDER_PROFILE_SEED, sender.as_ref()], &ID)
+}
+
+/// Treasury managed by `authority`
+pub fn find_treasury_address(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED, authority.as_ref()], &ID)
+}
+
+/// Audit log of `treasury`
+pub fn find_treasury_audit_log_address(treasury: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_AUDIT_LOG_SEED, treasury.as_ref()], &ID)
+}
+
+/// Compliance registry maintained by `authority`
+pub fn find_compliance_registry_address(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COMPLIANCE_REGISTRY_SEED, authority.as_ref()], &ID)
+}
+
+/// Approval of `vault_program` for escrow yield
+pub fn find_approved_yield_vault_address(vault_program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[APPROVED_YIELD_VAULT_SEED, vault_program.as_ref()],
+        &ID,
+    )
+}
+
+/// Mint of the position token of `stream`
+pub fn find_position_mint_address(stream: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POSITION_SEED, stream.as_ref()], &ID)
+}
+
+/// Stream id for the given sender profile nonce
+pub fn nonce_stream_id(nonce: u64) -> [u8; 32] {
+    let mut stream_id = [0u8; 32];
+    stream_id[24..].copy_from_slice(&nonce.to_le_bytes());
+    stream_id
+}
+
+/// Stream id of the `split_index`-th stream split off `parent`
+pub fn split_stream_id(parent: &Pubkey, split_index: u64) -> [u8; 32] {
+    hashv(&[SPLIT_SEED, parent.as_ref(), &split_index.to_le_bytes()]).to_bytes()
+}
+
+/// Check that a caller supplied id cannot collide with nonce ids
+pub fn is_custom_stream_id(stream_id: &[u8; 32]) -> bool {
+    stream_id[..24].iter().any(|byte| *byte != 0)
+}
@@ -0,0 +1,686 @@
+//! Account layouts and instruction argument types
+//!
+//! These mirror the program state byte for byte. Only the data is defined here;
+//! the program keeps the behaviour attached to it.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use bytemuck::{Pod, Zeroable};
+use std::fmt;
+use std::io::{Read, Write};
+
+use constants::{MAX_MILESTONES, MAX_TREASURY_MANAGERS, MAX_VESTING_SEGMENTS};
+
+/// Common constants shared with the program
+pub mod constants {
+    /// Maximum number of recipients per stream
+    pub const MAX_RECIPIENTS: usize = 100;
+
+    /// Maximum number of vesting schedules per account
+    pub const MAX_VESTING_SCHEDULES: usize = 50;
+
+    /// Maximum number of streams folded into a target by one merge
+    pub const MAX_MERGE_STREAMS: usize = 8;
+
+    /// Maximum number of treasury managers
+    pub const MAX_TREASURY_MANAGERS: usize = 10;
+
+    /// Minimum stream duration in seconds (1 minute)
+    pub const MIN_STREAM_DURATION: u64 = 60;
+
+    /// Maximum stream duration in seconds (10 years)
+    pub const MAX_STREAM_DURATION: u64 = 315_360_000;
+
+    /// Default cliff period in seconds (30 days)
+    pub const DEFAULT_CLIFF_PERIOD: u64 = 2_592_000;
+
+    /// Maximum number of custom vesting points
+    pub const MAX_VESTING_POINTS: usize = 365;
+
+    /// Maximum number of segments in a custom vesting curve
+    pub const MAX_VESTING_SEGMENTS: usize = 20;
+
+    /// Maximum vesting curve exponent (16.0, scaled by RATE_PRECISION)
+    pub const MAX_VESTING_EXPONENT: u32 = 16_000_000;
+
+    /// Maximum number of calendar months in a monthly vesting schedule (10 years)
+    pub const MAX_VESTING_MONTHS: u16 = 120;
+
+    /// Maximum number of attested milestones in a milestone stream
+    pub const MAX_MILESTONES: usize = 16;
+
+    /// Precision for rate calculations (6 decimal places)
+    pub const RATE_PRECISION: u64 = 1_000_000;
+
+    /// Fee basis points (0.1% = 10 basis points)
+    pub const DEFAULT_FEE_BASIS_POINTS: u16 = 10;
+
+    /// Maximum fee basis points (10% = 1000 basis points)
+    pub const MAX_FEE_BASIS_POINTS: u16 = 1000;
+}
+
+/// Maximum length of a treasury name in bytes
+pub const MAX_TREASURY_NAME_LENGTH: usize = 50;
+
+/// Maximum length of a treasury description in bytes
+pub const MAX_TREASURY_DESCRIPTION_LENGTH: usize = 200;
+
+/// Number of hourly buckets in the daily spending window
+pub const DAILY_WINDOW_BUCKETS: usize = 24;
+
+/// Number of daily buckets in the monthly spending window
+pub const MONTHLY_WINDOW_BUCKETS: usize = 30;
+
+/// Number of entries kept by a treasury audit log
+pub const MAX_AUDIT_LOG_ENTRIES: usize = 64;
+
+/// Maximum number of accounts listed in a compliance registry
+pub const MAX_COMPLIANCE_ENTRIES: usize = 64;
+
+/// Maximum depth of a Merkle proof against a compliance registry root
+pub const MAX_COMPLIANCE_PROOF_LENGTH: usize = 24;
+
+/// Denominator of `EscrowYield::recipient_share_bps`
+pub const YIELD_SHARE_DENOMINATOR: u16 = 10_000;
+
+/// Prefix of the message an attestor signs to approve a milestone
+pub const MILESTONE_APPROVAL_DOMAIN: &[u8] = b"streamflow:milestone-approval";
+
+/// A `bool` stored as one byte, so zero-copy accounts holding flags are `Pod`.
+///
+/// Serializes exactly like `bool`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Pod, Zeroable)]
+#[repr(transparent)]
+pub struct PodBool(u8);
+
+impl PodBool {
+    pub const fn new(value: bool) -> Self {
+        Self(value as u8)
+    }
+
+    pub const fn get(self) -> bool {
+        self.0 != 0
+    }
+}
+
+impl From<bool> for PodBool {
+    fn from(value: bool) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<PodBool> for bool {
+    fn from(value: PodBool) -> Self {
+        value.get()
+    }
+}
+
+impl fmt::Debug for PodBool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl AnchorSerialize for PodBool {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.get().serialize(writer)
+    }
+}
+
+impl AnchorDeserialize for PodBool {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        bool::deserialize_reader(reader).map(Self::new)
+    }
+}
+
+/// Vesting type, stored as its `u8` discriminant in `Stream::vesting_type`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[repr(u8)]
+pub enum VestingType {
+    /// Linear vesting over time
+    #[default]
+    Linear,
+    /// Cliff vesting with unlock at specific time
+    Cliff,
+    /// Custom vesting schedule with multiple unlock points
+    Custom,
+    /// Equal tranches unlocked on the same day of each calendar month (UTC)
+    Monthly,
+    /// Tranches unlocked by attestor approvals of milestones
+    Milestone,
+}
+
+/// Treasury operation recorded in the audit log
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum TreasuryOperation {
+    /// Deposit tokens into treasury
+    Deposit,
+    /// Withdraw tokens from treasury
+    Withdraw,
+    /// Create new stream from treasury
+    CreateStream,
+    /// Attach an existing stream of the treasury authority
+    AddStream,
+    /// Add, change or remove a manager
+    SetManager,
+    /// Change the spending limits
+    SetSpendingLimits,
+    /// Attach or detach a compliance registry
+    SetComplianceRegistry,
+}
+
+/// Portion of a stream moved by `split_stream`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SplitAmount {
+    /// Share of the stream in basis points
+    Percentage(u16),
+    /// Exact amount of the unwithdrawn balance
+    Amount(u64),
+}
+
+/// Access control levels for treasury management
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum AccessLevel {
+    /// Full administrative access
+    Admin,
+    /// Can create and manage streams
+    Manager,
+    /// Can only view treasury information
+    #[default]
+    Viewer,
+}
+
+/// How a sender may terminate a vesting stream, fixed when the stream is created
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct LeaverPolicy {
+    /// Seconds before termination whose vested but unwithdrawn tokens a bad leaver
+    /// forfeits back to the sender (0 leaves vested tokens with the recipient)
+    pub bad_leaver_clawback_window: i64,
+    /// Share of the unvested amount released to a good leaver, in basis points
+    pub good_leaver_acceleration_bps: u16,
+    /// Whether the stream can be terminated with `terminate_vesting`
+    pub enabled: PodBool,
+    pub _padding: [u8; 5],
+}
+
+/// Kind of termination applied by `terminate_vesting`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum TerminationKind {
+    /// Recipient leaves on good terms and receives accelerated vesting
+    GoodLeaver,
+    /// Recipient leaves on bad terms and forfeits unvested tokens
+    BadLeaver,
+}
+
+/// Events that can fire an acceleration clause
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AccelerationTrigger {
+    /// The company is acquired or otherwise changes control
+    ChangeOfControl,
+    /// The recipient is terminated without cause after a change of control
+    Termination,
+}
+
+/// Acceleration terms fixed at creation together with their trigger state
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct AccelerationClause {
+    /// Additional authority allowed to fire triggers besides the sender
+    pub trigger_authority: Pubkey,
+    /// Time the clause took effect (0 while pending)
+    pub accelerated_at: i64,
+    /// Share of the unvested amount accelerated, in basis points (0 disables)
+    pub percentage_bps: u16,
+    /// Whether both triggers are required instead of just change of control
+    pub double_trigger: PodBool,
+    /// Bitmask of triggers that have fired
+    pub triggers_fired: u8,
+    pub _padding: [u8; 4],
+}
+
+/// A single piece of a custom vesting schedule.
+///
+/// Within a segment the unlocked portion of `amount` follows
+/// `(elapsed / duration) ^ exponent`, so an exponent of `RATE_PRECISION` is linear.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct VestingSegment {
+    /// Length of the segment in seconds
+    pub duration: u64,
+    /// Amount of tokens unlocked over the segment
+    pub amount: u64,
+    /// Curve exponent scaled by `constants::RATE_PRECISION`
+    pub exponent: u32,
+    pub _padding: [u8; 4],
+}
+
+/// Claim of a lender on the future withdrawals of a stream
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct Lien {
+    /// Lender the stream is pledged to (default when there is no lien)
+    pub lender: Pubkey,
+    /// Token account of the lender receiving repayments
+    pub lender_token_account: Pubkey,
+    /// Amount pledged to the lender
+    pub amount: u64,
+    /// Amount repaid so far, from withdrawals or directly
+    pub repaid_amount: u64,
+    /// Time the lien was created
+    pub created_at: i64,
+}
+
+/// Complete set of amendable stream terms
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct StreamTerms {
+    pub end_time: i64,
+    pub cliff_time: i64,
+    pub rate_amount: u64,
+    pub rate_interval_in_seconds: u64,
+    pub cancelable_by_sender: PodBool,
+    pub cancelable_by_recipient: PodBool,
+    pub transferable_by_sender: PodBool,
+    pub transferable_by_recipient: PodBool,
+    pub _padding: [u8; 4],
+}
+
+/// Tranches of a milestone stream and the attestor approvals unlocking them
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct MilestoneSchedule {
+    /// Key whose ed25519 signature approves a milestone
+    pub attestor: Pubkey,
+    /// Amount unlocked by each milestone
+    pub amounts: [u64; MAX_MILESTONES],
+    /// Time each milestone was approved (0 while pending)
+    pub approved_at: [i64; MAX_MILESTONES],
+    /// Number of milestones used in `amounts` and `approved_at`
+    pub count: u8,
+    pub _padding: [u8; 7],
+}
+
+/// Interface an escrow yield vault program speaks
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum YieldAdapter {
+    /// Minimal lending vault with `deposit`, `redeem` and `position_value`
+    LendingVault,
+}
+
+/// Opt-in deposit of the unvested escrow balance into a yield vault
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct EscrowYield {
+    /// Program of the yield vault (default when yield is disabled)
+    pub vault_program: Pubkey,
+    /// Vault state account
+    pub vault: Pubkey,
+    /// Token account of the vault holding deposited tokens
+    pub vault_token_account: Pubkey,
+    /// Principal currently held by the vault on behalf of the escrow
+    pub deposited_principal: u64,
+    /// Total yield paid out so far
+    pub harvested_yield: u64,
+    /// Share of the yield paid to the recipient in basis points, the sender gets the rest
+    pub recipient_share_bps: u16,
+    /// `YieldAdapter` used to talk to `vault_program`
+    pub adapter: u8,
+    /// Whether the recipient accepted the vault and yield split
+    pub recipient_consented: PodBool,
+    pub _padding: [u8; 4],
+}
+
+/// A token stream and its escrow terms.
+///
+/// Streams are zero-copy accounts with a `repr(C)` layout. Flags are stored as
+/// `PodBool` and enums as their `u8` discriminant.
+#[account(zero_copy)]
+#[derive(Debug)]
+pub struct Stream {
+    /// The authority that can modify the stream
+    pub sender: Pubkey,
+    /// The recipient of the stream
+    pub recipient: Pubkey,
+    /// The mint of the token being streamed
+    pub mint: Pubkey,
+    /// The escrow token account holding the funds
+    pub escrow_tokens: Pubkey,
+    /// The authority that cancelled the stream
+    pub cancelled_by: Pubkey,
+    /// Mint of the 1-of-1 token representing the recipient position (default when not tokenized)
+    pub position_mint: Pubkey,
+    /// Additional authority the recipient allows to trigger withdrawals (default when unset)
+    pub withdraw_authority: Pubkey,
+    /// Token account every withdrawal must be paid into (default when unset)
+    pub withdraw_destination: Pubkey,
+    /// The original sender, used for PDA derivation since `sender` can change
+    pub creator: Pubkey,
+    /// Sender nominated by `propose_sender_transfer` (default when none is pending)
+    pub pending_sender: Pubkey,
+    /// Party that requested the pending cancellation
+    pub cancel_requested_by: Pubkey,
+    /// Compliance registry recipients must be approved by (default when none)
+    pub compliance_registry: Pubkey,
+    /// Id chosen at creation, used with `creator` for PDA derivation
+    pub stream_id: [u8; 32],
+    /// The name/description of the stream
+    pub name: [u8; 64],
+    /// Additional metadata
+    pub metadata: [u8; 128],
+    /// The amount of tokens deposited in the stream
+    pub deposited_amount: u64,
+    /// The amount of tokens withdrawn from the stream
+    pub withdrawn_amount: u64,
+    /// The start time of the stream (Unix timestamp)
+    pub start_time: i64,
+    /// The end time of the stream (Unix timestamp)
+    pub end_time: i64,
+    /// The cliff time (Unix timestamp) - tokens are locked until this time
+    pub cliff_time: i64,
+    /// The rate of tokens per second
+    pub rate_amount: u64,
+    /// The interval for rate calculation (in seconds)
+    pub rate_interval_in_seconds: u64,
+    /// The time when the stream was cancelled
+    pub cancelled_at: i64,
+    /// The time when the stream was closed
+    pub closed_at: i64,
+    /// The time when the stream was paused
+    pub paused_at: i64,
+    /// Total time the stream has been paused
+    pub total_paused_time: i64,
+    /// The last time the stream was updated
+    pub last_withdrawn_at: i64,
+    /// Minimum seconds between `request_cancel` and the cancellation taking effect
+    pub cancel_notice_period: u64,
+    /// Time a requested cancellation takes effect (0 when none is pending)
+    pub cancel_effective_at: i64,
+    /// Time the pending amendment was proposed (0 when none is pending)
+    pub amendment_proposed_at: i64,
+    /// Number of streams split off this one, numbering the id of the next split
+    pub split_count: u64,
+    /// Curve segments used when `vesting_type` is `Custom`
+    pub vesting_segments: [VestingSegment; MAX_VESTING_SEGMENTS],
+    /// Termination terms the sender can enforce with `terminate_vesting`
+    pub leaver_policy: LeaverPolicy,
+    /// Change-of-control acceleration terms and trigger state
+    pub acceleration: AccelerationClause,
+    /// Claim of a lender on withdrawals (inactive when `lien.lender` is default)
+    pub lien: Lien,
+    /// Terms proposed by the sender and awaiting the recipient's countersignature
+    pub pending_amendment: StreamTerms,
+    /// Attested tranches of a milestone stream
+    pub milestones: MilestoneSchedule,
+    /// Yield vault the unvested balance is deposited in (disabled by default)
+    pub escrow_yield: EscrowYield,
+    /// Number of monthly tranches when `vesting_type` is `Monthly`
+    pub vesting_months: u16,
+    /// Whether the stream can be cancelled by the sender
+    pub cancelable_by_sender: PodBool,
+    /// Whether the stream can be cancelled by the recipient
+    pub cancelable_by_recipient: PodBool,
+    /// Whether the sender can transfer the stream to a new recipient
+    pub transferable_by_sender: PodBool,
+    /// Whether the recipient can transfer the stream to a new recipient
+    pub transferable_by_recipient: PodBool,
+    /// Whether automatic withdrawal is enabled
+    pub automatic_withdrawal: PodBool,
+    /// Whether the stream has been cancelled
+    pub cancelled: PodBool,
+    /// Whether the stream has been closed
+    pub closed: PodBool,
+    /// The current pause state of the stream
+    pub paused: PodBool,
+    /// Whether the compliance authority froze withdrawals pending review
+    pub withdrawals_frozen: PodBool,
+    /// `VestingType` deciding how unlocked tokens are calculated
+    pub vesting_type: u8,
+    /// Number of populated entries in `vesting_segments`
+    pub vesting_segment_count: u8,
+    /// The bump seed for the PDA
+    pub bump: u8,
+    /// Aligns `_reserved` to 8 bytes
+    pub _padding: [u8; 2],
+    /// Reserved space for future upgrades
+    pub _reserved: [u8; 248],
+}
+
+impl Stream {
+    /// Size of the account including the discriminator
+    pub const LEN: usize = 8 + std::mem::size_of::<Stream>();
+}
+
+/// Stream state returned by the quote instructions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct StreamData {
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub deposited_amount: u64,
+    pub withdrawn_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub cliff_time: i64,
+    pub cancelable_by_sender: bool,
+    pub cancelable_by_recipient: bool,
+    pub automatic_withdrawal: bool,
+    pub rate_amount: u64,
+    pub rate_interval_in_seconds: u64,
+    pub name: String,
+    pub paused: bool,
+    pub cancelled: bool,
+}
+
+/// Stream state together with a single computed amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct StreamQuote {
+    pub stream: StreamData,
+    pub amount: u64,
+    /// Cluster time the amount was computed at
+    pub quoted_at: i64,
+}
+
+/// Stream state together with every computed amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct StreamSummary {
+    pub stream: StreamData,
+    pub streamed_amount: u64,
+    pub withdrawable_amount: u64,
+    pub remaining_balance: u64,
+    pub progress_percentage: u8,
+    pub is_completed: bool,
+    /// Cluster time the amounts were computed at
+    pub quoted_at: i64,
+}
+
+#[account]
+#[derive(Debug, Default)]
+pub struct Treasury {
+    /// The authority that manages the treasury
+    pub authority: Pubkey,
+    /// Display name of the treasury
+    pub name: String,
+    /// Free-form description of the treasury
+    pub description: String,
+    /// Whether the treasury closes itself once all streams are finished
+    pub auto_close: bool,
+    /// Number of streams ever added to the treasury
+    pub total_streams: u64,
+    /// Number of streams that were active when added
+    pub active_streams: u64,
+    /// Total amount deposited into streams of the treasury
+    pub total_deposited: u64,
+    /// Total amount withdrawn from streams of the treasury
+    pub total_withdrawn: u64,
+    /// Creation time (Unix timestamp)
+    pub created_at: i64,
+    /// Audit log recording treasury operations (default when not enabled)
+    pub audit_log: Pubkey,
+    /// Number of entries used in `managers`
+    pub manager_count: u8,
+    /// Signers allowed to act for the treasury besides the authority
+    pub managers: [TreasuryManager; MAX_TREASURY_MANAGERS],
+    /// Caps on the amount committed to new streams
+    pub spending_limits: SpendingLimits,
+    /// Compliance registry approving stream recipients (default when none)
+    pub compliance_registry: Pubkey,
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+/// Signer allowed to act for a treasury
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TreasuryManager {
+    pub key: Pubkey,
+    pub access_level: AccessLevel,
+}
+
+/// Caps on the amount a treasury commits to new streams, with the counters
+/// enforcing them. A limit of 0 disables the cap.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpendingLimits {
+    /// Maximum amount committed over the daily window
+    pub daily_limit: u64,
+    /// Maximum amount committed over the monthly window
+    pub monthly_limit: u64,
+    /// Maximum deposit of a single stream created by an admin manager
+    pub admin_max_per_stream: u64,
+    /// Maximum deposit of a single stream created by a manager
+    pub manager_max_per_stream: u64,
+    /// Amount committed per hour, indexed by hour modulo the bucket count
+    pub hourly_committed: [u64; DAILY_WINDOW_BUCKETS],
+    /// Amount committed per day, indexed by day modulo the bucket count
+    pub daily_committed: [u64; MONTHLY_WINDOW_BUCKETS],
+    /// Time of the last commitment
+    pub last_committed_at: i64,
+}
+
+/// One recorded treasury operation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct AuditEntry {
+    /// Position of the entry in the full history of the treasury, starting at 0
+    pub sequence: u64,
+    /// Kind of operation
+    pub operation: TreasuryOperation,
+    /// Signer that performed the operation
+    pub actor: Pubkey,
+    /// Token amount moved or committed (0 when none)
+    pub amount: u64,
+    /// Stream the operation concerns (default when none)
+    pub stream: Pubkey,
+    /// Time of the operation
+    pub timestamp: i64,
+}
+
+/// Append-only ring of the operations performed on a treasury
+#[account]
+#[derive(Debug)]
+pub struct TreasuryAuditLog {
+    /// Treasury the log belongs to
+    pub treasury: Pubkey,
+    /// Sequence number of the next entry, i.e. the number of entries ever recorded
+    pub next_sequence: u64,
+    /// Ring of entries, the slot of an entry is its sequence modulo the capacity
+    pub entries: [AuditEntry; MAX_AUDIT_LOG_ENTRIES],
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+/// How a compliance registry decides whether a recipient is approved
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ComplianceMode {
+    /// Only listed accounts are approved
+    Allowlist,
+    /// Every account except the listed ones is approved
+    Denylist,
+    /// Only accounts proven against `merkle_root` are approved
+    MerkleAllowlist,
+}
+
+/// Registry of approved stream recipients, maintained by a compliance authority
+#[account]
+#[derive(Debug)]
+pub struct ComplianceRegistry {
+    /// Compliance authority maintaining the registry
+    pub authority: Pubkey,
+    /// How `entries` or `merkle_root` are interpreted
+    pub mode: ComplianceMode,
+    /// Number of entries used in `entries`
+    pub entry_count: u16,
+    /// Listed accounts, approved or denied depending on `mode`
+    pub entries: [Pubkey; MAX_COMPLIANCE_ENTRIES],
+    /// Root of the Merkle tree of approved accounts in `MerkleAllowlist` mode
+    pub merkle_root: [u8; 32],
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+/// Per-sender counter used to derive stream addresses
+#[account]
+#[derive(Default)]
+pub struct SenderProfile {
+    /// The sender this profile belongs to
+    pub authority: Pubkey,
+    /// Nonce of the next stream created with this profile
+    pub next_nonce: u64,
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+/// Vault program the escrow of a stream may be deposited in, approved by the
+/// program upgrade authority
+#[account]
+pub struct ApprovedYieldVault {
+    /// Approved vault program
+    pub vault_program: Pubkey,
+    /// `YieldAdapter` used to talk to `vault_program`
+    pub adapter: u8,
+    /// Timestamp of the approval
+    pub approved_at: i64,
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+/// Merkle leaf of an approved account
+pub fn compliance_leaf(account: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[account.as_ref()]).0
+}
+
+/// Check `leaf` against `root`, hashing each pair of nodes in sorted order
+pub fn verify_merkle_proof(root: &[u8; 32], leaf: &[u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(*leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).0
+        } else {
+            keccak::hashv(&[sibling, &node]).0
+        }
+    });
+    computed == *root
+}
+
+/// Message the attestor signs to approve milestone `index` of `stream` unlocking
+/// `amount`
+pub fn milestone_approval_message(stream: &Pubkey, index: u8, amount: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(MILESTONE_APPROVAL_DOMAIN.len() + 32 + 1 + 8);
+    message.extend_from_slice(MILESTONE_APPROVAL_DOMAIN);
+    message.extend_from_slice(stream.as_ref());
+    message.push(index);
+    message.extend_from_slice(&amount.to_le_bytes());
+    message
+}
@@ -0,0 +1,364 @@
+//! A sample downstream program paying and claiming grants through the interface.
+//!
+//! Grants are paid from, and claimed into, token accounts owned by a DAO vault PDA
+//! of the grant program, so every StreamFlow call is signed with the vault seeds.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer as _};
+use solana_sdk::system_instruction;
+use streamflow_interface::pda;
+use streamflow_interface::state::constants::RATE_PRECISION;
+use streamflow_interface::state::{AccelerationClause, LeaverPolicy, Stream, VestingSegment};
+use streamflow_interface::Streamflow;
+
+#[path = "../../programs/streamflow/tests/common/mod.rs"]
+mod common;
+
+use common::{
+    create_mint, create_token_account, fetch_account, process, process_streamflow, token_balance,
+};
+
+declare_id!("7W3QvNqjf7MaPJpeonEvhk68ZTvnBJAQaPFjwYJeBfYR");
+
+const VAULT_SEED: &[u8] = b"vault";
+
+#[program]
+pub mod grants {
+    use super::*;
+    use streamflow_interface::cpi;
+
+    /// Stream `amount` linearly from the DAO vault to `recipient`
+    pub fn pay_grant(
+        ctx: Context<PayGrant>,
+        stream_id: [u8; 32],
+        recipient: Pubkey,
+        start_time: i64,
+        duration: u64,
+        amount: u64,
+    ) -> Result<()> {
+        let dao = ctx.accounts.dao.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, dao.as_ref(), &[ctx.bumps.vault]]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.streamflow_program.to_account_info(),
+            cpi::accounts::CreateVestingStream {
+                stream: ctx.accounts.stream.to_account_info(),
+                escrow_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                sender_token_account: ctx.accounts.vault_token_account.to_account_info(),
+                sender: ctx.accounts.vault.to_account_info(),
+                sender_profile: None,
+                mint: ctx.accounts.mint.to_account_info(),
+                approved_yield_vault: None,
+                yield_vault: None,
+                yield_vault_token_account: None,
+                token_program: ctx.accounts.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        cpi::create_vesting_stream(
+            cpi_ctx,
+            stream_id,
+            recipient,
+            start_time,
+            start_time,
+            vec![VestingSegment {
+                duration,
+                amount,
+                exponent: RATE_PRECISION as u32,
+                ..Default::default()
+            }],
+            true,
+            false,
+            0,
+            LeaverPolicy::default(),
+            AccelerationClause::default(),
+            "grant".to_string(),
+            None,
+        )
+    }
+
+    /// Withdraw everything currently unlocked into the DAO vault
+    pub fn claim_grant(ctx: Context<ClaimGrant>) -> Result<()> {
+        let quote = cpi::get_withdrawable(CpiContext::new(
+            ctx.accounts.streamflow_program.to_account_info(),
+            cpi::accounts::ViewStream {
+                stream: ctx.accounts.stream.to_account_info(),
+            },
+        ))?
+        .get();
+
+        let dao = ctx.accounts.dao.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, dao.as_ref(), &[ctx.bumps.vault]]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.streamflow_program.to_account_info(),
+            cpi::accounts::Withdraw {
+                stream: ctx.accounts.stream.to_account_info(),
+                recipient: ctx.accounts.vault.to_account_info(),
+                escrow_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                recipient_token_account: ctx.accounts.vault_token_account.to_account_info(),
+                position_token_account: None,
+                lender_token_account: None,
+                yield_vault_program: None,
+                yield_vault: None,
+                yield_vault_token_account: None,
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        cpi::withdraw(cpi_ctx, quote.amount)
+    }
+}
+
+#[derive(Accounts)]
+pub struct PayGrant<'info> {
+    pub dao: Signer<'info>,
+
+    #[account(mut, seeds = [VAULT_SEED, dao.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: created and checked by StreamFlow
+    #[account(mut)]
+    pub stream: UncheckedAccount<'info>,
+
+    /// CHECK: created and checked by StreamFlow
+    #[account(mut)]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    pub streamflow_program: Program<'info, Streamflow>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimGrant<'info> {
+    pub dao: Signer<'info>,
+
+    #[account(seeds = [VAULT_SEED, dao.key().as_ref()], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut, token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: checked by StreamFlow
+    #[account(mut)]
+    pub stream: UncheckedAccount<'info>,
+
+    /// CHECK: checked by StreamFlow
+    #[account(mut)]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    pub streamflow_program: Program<'info, Streamflow>,
+    pub token_program: Program<'info, Token>,
+}
+
+// Anchor entrypoints tie the account slice to the lifetime of its contents, see
+// `common::process_streamflow`
+fn process_grants(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    entry(program_id, accounts, data)
+}
+
+const GRANT: u64 = 1_000_000;
+const DURATION: u64 = 1_000;
+
+struct Setup {
+    context: ProgramTestContext,
+    mint: Pubkey,
+    payer_dao: Keypair,
+    payer_vault: Pubkey,
+    payer_vault_tokens: Pubkey,
+    grantee_dao: Keypair,
+    grantee_vault: Pubkey,
+    grantee_vault_tokens: Pubkey,
+}
+
+fn vault_address(dao: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_SEED, dao.as_ref()], &ID).0
+}
+
+async fn setup() -> Setup {
+    let mut program_test =
+        ProgramTest::new("streamflow", streamflow::ID, processor!(process_streamflow));
+    program_test.add_program("grants", ID, processor!(process_grants));
+    let mut context = program_test.start_with_context().await;
+
+    let mint = create_mint(&mut context).await;
+
+    let payer_dao = Keypair::new();
+    let payer_vault = vault_address(&payer_dao.pubkey());
+    let payer_vault_tokens = create_token_account(&mut context, &mint, &payer_vault).await;
+
+    let grantee_dao = Keypair::new();
+    let grantee_vault = vault_address(&grantee_dao.pubkey());
+    let grantee_vault_tokens = create_token_account(&mut context, &mint, &grantee_vault).await;
+
+    // The paying vault funds the stream accounts and the grant itself
+    let payer = context.payer.pubkey();
+    let instructions = [
+        system_instruction::transfer(&payer, &payer_vault, 1_000_000_000),
+        spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &mint,
+            &payer_vault_tokens,
+            &payer,
+            &[],
+            GRANT,
+        )
+        .unwrap(),
+    ];
+    process(&mut context, &instructions, &[]).await.unwrap();
+
+    Setup {
+        context,
+        mint,
+        payer_dao,
+        payer_vault,
+        payer_vault_tokens,
+        grantee_dao,
+        grantee_vault,
+        grantee_vault_tokens,
+    }
+}
+
+fn pay_grant(setup: &Setup, stream_id: [u8; 32], start_time: i64) -> (Instruction, Pubkey) {
+    let stream = pda::find_stream_address(&setup.payer_vault, &stream_id).0;
+    let instruction = Instruction {
+        program_id: ID,
+        accounts: accounts::PayGrant {
+            dao: setup.payer_dao.pubkey(),
+            vault: setup.payer_vault,
+            vault_token_account: setup.payer_vault_tokens,
+            stream,
+            escrow_token_account: pda::find_escrow_address(&stream).0,
+            mint: setup.mint,
+            streamflow_program: streamflow_interface::ID,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: solana_sdk::sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::PayGrant {
+            stream_id,
+            recipient: setup.grantee_vault,
+            start_time,
+            duration: DURATION,
+            amount: GRANT,
+        }
+        .data(),
+    };
+    (instruction, stream)
+}
+
+fn claim_grant(dao: &Pubkey, vault_tokens: &Pubkey, stream: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::ClaimGrant {
+            dao: *dao,
+            vault: vault_address(dao),
+            vault_token_account: *vault_tokens,
+            stream: *stream,
+            escrow_token_account: pda::find_escrow_address(stream).0,
+            streamflow_program: streamflow_interface::ID,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimGrant {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn pda_sender_creates_stream_through_cpi() {
+    let mut setup = setup().await;
+    let clock = setup.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let start_time = clock.unix_timestamp + 10;
+
+    let (instruction, stream) = pay_grant(&setup, [7; 32], start_time);
+    process(&mut setup.context, &[instruction], &[&setup.payer_dao])
+        .await
+        .unwrap();
+
+    let created = fetch_account::<Stream>(&mut setup.context, &stream).await;
+    assert_eq!(created.sender, setup.payer_vault);
+    assert_eq!(created.creator, setup.payer_vault);
+    assert_eq!(created.recipient, setup.grantee_vault);
+    assert_eq!(created.deposited_amount, GRANT);
+    assert_eq!(created.end_time, start_time + DURATION as i64);
+
+    let escrow = pda::find_escrow_address(&stream).0;
+    assert_eq!(token_balance(&mut setup.context, &escrow).await, GRANT);
+    assert_eq!(
+        token_balance(&mut setup.context, &setup.payer_vault_tokens).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn pda_recipient_withdraws_through_cpi() {
+    let mut setup = setup().await;
+    let clock = setup.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let start_time = clock.unix_timestamp + 10;
+
+    let (instruction, stream) = pay_grant(&setup, [8; 32], start_time);
+    process(&mut setup.context, &[instruction], &[&setup.payer_dao])
+        .await
+        .unwrap();
+
+    // Halfway through the grant
+    let mut clock = setup.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = start_time + DURATION as i64 / 2;
+    setup.context.set_sysvar(&clock);
+
+    let instruction = claim_grant(
+        &setup.grantee_dao.pubkey(),
+        &setup.grantee_vault_tokens,
+        &stream,
+    );
+    process(&mut setup.context, &[instruction], &[&setup.grantee_dao])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        token_balance(&mut setup.context, &setup.grantee_vault_tokens).await,
+        GRANT / 2
+    );
+    let claimed = fetch_account::<Stream>(&mut setup.context, &stream).await;
+    assert_eq!(claimed.withdrawn_amount, GRANT / 2);
+}
+
+#[tokio::test]
+async fn withdrawal_signed_for_another_vault_fails() {
+    let mut setup = setup().await;
+    let clock = setup.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let start_time = clock.unix_timestamp + 10;
+
+    let (instruction, stream) = pay_grant(&setup, [9; 32], start_time);
+    process(&mut setup.context, &[instruction], &[&setup.payer_dao])
+        .await
+        .unwrap();
+
+    let mut clock = setup.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = start_time + DURATION as i64;
+    setup.context.set_sysvar(&clock);
+
+    // The paying DAO signs for its own vault, which is not the stream recipient
+    let instruction = claim_grant(&setup.payer_dao.pubkey(), &setup.payer_vault_tokens, &stream);
+    assert!(process(&mut setup.context, &[instruction], &[&setup.payer_dao])
+        .await
+        .is_err());
+}
@@ -0,0 +1,493 @@
+//! Checks the interface against the program it describes.
+//!
+//! The interface does not link the program, so these tests are what keeps its
+//! discriminators, account metas and layouts in step with the program.
+
+use anchor_lang::prelude::*;
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator, InstructionData};
+use std::mem::{offset_of, size_of};
+use streamflow_interface::{cpi, instruction, state};
+
+macro_rules! assert_same_discriminators {
+    ($module:ident: $($name:ident),* $(,)?) => {
+        $(
+            assert_eq!(
+                streamflow_interface::$module::$name::DISCRIMINATOR,
+                streamflow::$module::$name::DISCRIMINATOR,
+                stringify!($name),
+            );
+        )*
+    };
+}
+
+macro_rules! assert_same_layout {
+    ($name:ident: $($field:ident),* $(,)?) => {
+        assert_eq!(size_of::<state::$name>(), size_of::<streamflow::state::$name>());
+        $(
+            assert_eq!(
+                offset_of!(state::$name, $field),
+                offset_of!(streamflow::state::$name, $field),
+                concat!(stringify!($name), "::", stringify!($field)),
+            );
+        )*
+    };
+}
+
+/// Serialize `value` and read it back as the program type, which must serialize
+/// to the same bytes
+fn assert_same_account<T: AccountSerialize, P: AccountSerialize + AccountDeserialize>(value: &T) {
+    let mut bytes = Vec::new();
+    value.try_serialize(&mut bytes).unwrap();
+    let program = P::try_deserialize(&mut bytes.as_slice()).unwrap();
+    let mut program_bytes = Vec::new();
+    program.try_serialize(&mut program_bytes).unwrap();
+    assert_eq!(bytes, program_bytes);
+}
+
+fn account_info(key: Pubkey) -> AccountInfo<'static> {
+    AccountInfo::new(
+        Box::leak(Box::new(key)),
+        false,
+        false,
+        Box::leak(Box::new(0)),
+        Box::leak(Vec::new().into_boxed_slice()),
+        Box::leak(Box::new(Pubkey::default())),
+        false,
+        0,
+    )
+}
+
+#[test]
+fn program_id_matches() {
+    assert_eq!(streamflow_interface::ID, streamflow::ID);
+}
+
+#[test]
+fn instruction_discriminators_match() {
+    assert_same_discriminators!(instruction:
+        CreateStream,
+        Withdraw,
+        CancelStream,
+        TransferStream,
+        UpdateStream,
+        CreateTreasury,
+        AddStreamToTreasury,
+        CreateVestingStream,
+        CreateMonthlyVestingStream,
+        CreateMilestoneStream,
+        ApproveMilestone,
+        TerminateVesting,
+        FireAccelerationTrigger,
+        SplitStream,
+        MergeStreams,
+        MintPositionToken,
+        SetWithdrawDelegate,
+        ProposeSenderTransfer,
+        AcceptSenderTransfer,
+        CreateSenderProfile,
+        TopupStream,
+        CreateLien,
+        RepayLien,
+        ReleaseLien,
+        RequestCancel,
+        FinalizeCancel,
+        ProposeAmendment,
+        AcceptAmendment,
+        InitTreasuryAuditLog,
+        SetTreasuryManager,
+        RemoveTreasuryManager,
+        SetSpendingLimits,
+        DepositToTreasury,
+        WithdrawFromTreasury,
+        CreateTreasuryStream,
+        CreateComplianceRegistry,
+        UpdateComplianceEntries,
+        SetComplianceMerkleRoot,
+        SetTreasuryComplianceRegistry,
+        FreezeStreamWithdrawals,
+        ApproveYieldVault,
+        RevokeYieldVault,
+        AcceptEscrowYield,
+        RebalanceEscrowYield,
+        HarvestEscrowYield,
+        GetWithdrawable,
+        GetStreamed,
+        GetStreamSummary,
+    );
+}
+
+#[test]
+fn instruction_data_matches() {
+    let recipient = Pubkey::new_unique();
+    let segment = state::VestingSegment {
+        duration: 1_000,
+        amount: 5_000,
+        exponent: 2_000_000,
+        ..Default::default()
+    };
+    let leaver_policy = state::LeaverPolicy {
+        bad_leaver_clawback_window: 30,
+        good_leaver_acceleration_bps: 2_500,
+        enabled: true.into(),
+        ..Default::default()
+    };
+    let acceleration = state::AccelerationClause {
+        trigger_authority: Pubkey::new_unique(),
+        percentage_bps: 5_000,
+        double_trigger: true.into(),
+        ..Default::default()
+    };
+    let interface = instruction::CreateVestingStream {
+        stream_id: [3; 32],
+        recipient,
+        start_time: 100,
+        cliff_time: 200,
+        segments: vec![segment],
+        cancelable_by_sender: true,
+        cancelable_by_recipient: false,
+        cancel_notice_period: 60,
+        leaver_policy,
+        acceleration,
+        stream_name: "grant".to_string(),
+        yield_share_bps: Some(5_000),
+    };
+    let program = streamflow::instruction::CreateVestingStream {
+        stream_id: [3; 32],
+        recipient,
+        start_time: 100,
+        cliff_time: 200,
+        segments: vec![streamflow::state::vesting::VestingSegment {
+            duration: 1_000,
+            amount: 5_000,
+            exponent: 2_000_000,
+            ..Default::default()
+        }],
+        cancelable_by_sender: true,
+        cancelable_by_recipient: false,
+        cancel_notice_period: 60,
+        leaver_policy: streamflow::state::vesting::LeaverPolicy {
+            bad_leaver_clawback_window: 30,
+            good_leaver_acceleration_bps: 2_500,
+            enabled: true.into(),
+            ..Default::default()
+        },
+        acceleration: streamflow::state::vesting::AccelerationClause {
+            trigger_authority: acceleration.trigger_authority,
+            percentage_bps: 5_000,
+            double_trigger: true.into(),
+            ..Default::default()
+        },
+        stream_name: "grant".to_string(),
+        yield_share_bps: Some(5_000),
+    };
+    assert_eq!(interface.data(), program.data());
+
+    let interface = instruction::SplitStream {
+        new_recipient: recipient,
+        split_amount: state::SplitAmount::Amount(700),
+        compliance_proof: vec![[5; 32]],
+    };
+    let program = streamflow::instruction::SplitStream {
+        new_recipient: recipient,
+        split_amount: streamflow::state::SplitAmount::Amount(700),
+        compliance_proof: vec![[5; 32]],
+    };
+    assert_eq!(interface.data(), program.data());
+
+    let interface = instruction::ProposeAmendment {
+        terms: state::StreamTerms {
+            end_time: 500,
+            rate_amount: 7,
+            transferable_by_recipient: true.into(),
+            ..Default::default()
+        },
+    };
+    let program = streamflow::instruction::ProposeAmendment {
+        terms: streamflow::state::StreamTerms {
+            end_time: 500,
+            rate_amount: 7,
+            transferable_by_recipient: true.into(),
+            ..Default::default()
+        },
+    };
+    assert_eq!(interface.data(), program.data());
+
+    let interface = instruction::SetTreasuryManager {
+        manager: recipient,
+        access_level: state::AccessLevel::Manager,
+    };
+    let program = streamflow::instruction::SetTreasuryManager {
+        manager: recipient,
+        access_level: streamflow::state::AccessLevel::Manager,
+    };
+    assert_eq!(interface.data(), program.data());
+}
+
+#[test]
+fn account_metas_match() {
+    let keys: Vec<Pubkey> = (0..12).map(|_| Pubkey::new_unique()).collect();
+
+    let interface = cpi::accounts::CreateVestingStream {
+        stream: account_info(keys[0]),
+        escrow_token_account: account_info(keys[1]),
+        sender_token_account: account_info(keys[2]),
+        sender: account_info(keys[3]),
+        sender_profile: Some(account_info(keys[4])),
+        mint: account_info(keys[5]),
+        approved_yield_vault: None,
+        yield_vault: Some(account_info(keys[7])),
+        yield_vault_token_account: None,
+        token_program: account_info(keys[9]),
+        system_program: account_info(keys[10]),
+        rent: account_info(keys[11]),
+    };
+    let program = streamflow::accounts::CreateVestingStream {
+        stream: keys[0],
+        escrow_token_account: keys[1],
+        sender_token_account: keys[2],
+        sender: keys[3],
+        sender_profile: Some(keys[4]),
+        mint: keys[5],
+        approved_yield_vault: None,
+        yield_vault: Some(keys[7]),
+        yield_vault_token_account: None,
+        token_program: keys[9],
+        system_program: keys[10],
+        rent: keys[11],
+    };
+    assert_eq!(interface.to_account_metas(None), program.to_account_metas(None));
+
+    let interface = cpi::accounts::Withdraw {
+        stream: account_info(keys[0]),
+        recipient: account_info(keys[1]),
+        escrow_token_account: account_info(keys[2]),
+        recipient_token_account: account_info(keys[3]),
+        position_token_account: None,
+        lender_token_account: Some(account_info(keys[5])),
+        yield_vault_program: Some(account_info(keys[6])),
+        yield_vault: Some(account_info(keys[7])),
+        yield_vault_token_account: Some(account_info(keys[8])),
+        token_program: account_info(keys[9]),
+    };
+    let program = streamflow::accounts::Withdraw {
+        stream: keys[0],
+        recipient: keys[1],
+        escrow_token_account: keys[2],
+        recipient_token_account: keys[3],
+        position_token_account: None,
+        lender_token_account: Some(keys[5]),
+        yield_vault_program: Some(keys[6]),
+        yield_vault: Some(keys[7]),
+        yield_vault_token_account: Some(keys[8]),
+        token_program: keys[9],
+    };
+    assert_eq!(interface.to_account_metas(None), program.to_account_metas(None));
+
+    let interface = cpi::accounts::ApproveYieldVault {
+        approved_yield_vault: account_info(keys[0]),
+        vault_program: account_info(keys[1]),
+        authority: account_info(keys[2]),
+        program: account_info(keys[3]),
+        program_data: account_info(keys[4]),
+        system_program: account_info(keys[5]),
+    };
+    let program = streamflow::accounts::ApproveYieldVault {
+        approved_yield_vault: keys[0],
+        vault_program: keys[1],
+        authority: keys[2],
+        program: keys[3],
+        program_data: keys[4],
+        system_program: keys[5],
+    };
+    assert_eq!(interface.to_account_metas(None), program.to_account_metas(None));
+}
+
+#[test]
+fn zero_copy_layouts_match() {
+    assert_same_discriminators!(state: Stream);
+    assert_eq!(state::Stream::LEN, streamflow::state::Stream::LEN);
+    assert_same_layout!(Stream:
+        sender,
+        recipient,
+        mint,
+        escrow_tokens,
+        cancelled_by,
+        position_mint,
+        withdraw_authority,
+        withdraw_destination,
+        creator,
+        pending_sender,
+        cancel_requested_by,
+        compliance_registry,
+        stream_id,
+        name,
+        metadata,
+        deposited_amount,
+        withdrawn_amount,
+        start_time,
+        end_time,
+        cliff_time,
+        rate_amount,
+        rate_interval_in_seconds,
+        cancelled_at,
+        closed_at,
+        paused_at,
+        total_paused_time,
+        last_withdrawn_at,
+        cancel_notice_period,
+        cancel_effective_at,
+        amendment_proposed_at,
+        split_count,
+        vesting_segments,
+        leaver_policy,
+        acceleration,
+        lien,
+        pending_amendment,
+        milestones,
+        escrow_yield,
+        vesting_months,
+        cancelable_by_sender,
+        cancelable_by_recipient,
+        transferable_by_sender,
+        transferable_by_recipient,
+        automatic_withdrawal,
+        cancelled,
+        closed,
+        paused,
+        withdrawals_frozen,
+        vesting_type,
+        vesting_segment_count,
+        bump,
+        _reserved,
+    );
+    assert_same_layout!(VestingSegment: duration, amount, exponent);
+    assert_same_layout!(LeaverPolicy:
+        bad_leaver_clawback_window,
+        good_leaver_acceleration_bps,
+        enabled,
+    );
+    assert_same_layout!(AccelerationClause:
+        trigger_authority,
+        accelerated_at,
+        percentage_bps,
+        double_trigger,
+        triggers_fired,
+    );
+    assert_same_layout!(Lien: lender, lender_token_account, amount, repaid_amount, created_at);
+    assert_same_layout!(StreamTerms:
+        end_time,
+        cliff_time,
+        rate_amount,
+        rate_interval_in_seconds,
+        cancelable_by_sender,
+        cancelable_by_recipient,
+        transferable_by_sender,
+        transferable_by_recipient,
+    );
+    assert_same_layout!(MilestoneSchedule: attestor, amounts, approved_at, count);
+    assert_same_layout!(EscrowYield:
+        vault_program,
+        vault,
+        vault_token_account,
+        deposited_principal,
+        harvested_yield,
+        recipient_share_bps,
+        adapter,
+        recipient_consented,
+    );
+}
+
+#[test]
+fn borsh_accounts_match() {
+    assert_same_discriminators!(state:
+        Treasury,
+        TreasuryAuditLog,
+        ComplianceRegistry,
+        SenderProfile,
+        ApprovedYieldVault,
+    );
+
+    let mut treasury = state::Treasury {
+        authority: Pubkey::new_unique(),
+        name: "payroll".to_string(),
+        description: "monthly payroll".to_string(),
+        auto_close: true,
+        total_streams: 3,
+        created_at: 42,
+        manager_count: 1,
+        bump: 254,
+        ..Default::default()
+    };
+    treasury.managers[0] = state::TreasuryManager {
+        key: Pubkey::new_unique(),
+        access_level: state::AccessLevel::Admin,
+    };
+    treasury.spending_limits.daily_limit = 1_000;
+    treasury.spending_limits.hourly_committed[23] = 9;
+    assert_same_account::<_, streamflow::state::Treasury>(&treasury);
+
+    let mut audit_log = state::TreasuryAuditLog {
+        treasury: Pubkey::new_unique(),
+        next_sequence: 1,
+        entries: [state::AuditEntry {
+            sequence: 0,
+            operation: state::TreasuryOperation::Deposit,
+            actor: Pubkey::default(),
+            amount: 0,
+            stream: Pubkey::default(),
+            timestamp: 0,
+        }; state::MAX_AUDIT_LOG_ENTRIES],
+        bump: 255,
+    };
+    audit_log.entries[0].operation = state::TreasuryOperation::SetComplianceRegistry;
+    audit_log.entries[0].amount = 77;
+    assert_same_account::<_, streamflow::state::TreasuryAuditLog>(&audit_log);
+
+    let mut registry = state::ComplianceRegistry {
+        authority: Pubkey::new_unique(),
+        mode: state::ComplianceMode::MerkleAllowlist,
+        entry_count: 1,
+        entries: [Pubkey::default(); state::MAX_COMPLIANCE_ENTRIES],
+        merkle_root: [6; 32],
+        bump: 253,
+    };
+    registry.entries[0] = Pubkey::new_unique();
+    assert_same_account::<_, streamflow::state::ComplianceRegistry>(&registry);
+
+    let profile = state::SenderProfile {
+        authority: Pubkey::new_unique(),
+        next_nonce: 12,
+        bump: 252,
+    };
+    assert_same_account::<_, streamflow::state::SenderProfile>(&profile);
+
+    let approval = state::ApprovedYieldVault {
+        vault_program: Pubkey::new_unique(),
+        adapter: state::YieldAdapter::LendingVault as u8,
+        approved_at: 1_700_000_000,
+        bump: 251,
+    };
+    assert_same_account::<_, streamflow::state::ApprovedYieldVault>(&approval);
+}
+
+#[test]
+fn helpers_match() {
+    let account = Pubkey::new_unique();
+    let sibling = [9; 32];
+    let leaf = state::compliance_leaf(&account);
+    assert_eq!(leaf, streamflow::state::compliance_leaf(&account));
+    let root = anchor_lang::solana_program::keccak::hashv(&[&leaf.min(sibling), &leaf.max(sibling)]).0;
+    assert!(state::verify_merkle_proof(&root, &leaf, &[sibling]));
+    assert_eq!(
+        state::milestone_approval_message(&account, 2, 500),
+        streamflow::state::milestone_approval_message(&account, 2, 500),
+    );
+    assert_eq!(
+        streamflow_interface::pda::nonce_stream_id(7),
+        streamflow::state::nonce_stream_id(7),
+    );
+    assert_eq!(
+        streamflow_interface::pda::split_stream_id(&account, 3),
+        streamflow::state::split_stream_id(&account, 3),
+    );
+}
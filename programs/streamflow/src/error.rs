@@ -544,4 +544,73 @@ pub enum StreamFlowError {
     InvalidPairing,
     
     #[msg("Bilinear map failed")]
-    BilinearMapFailed,
\ No newline at end of file
+    BilinearMapFailed,
+    
+    #[msg("Stream has no leaver policy")]
+    LeaverPolicyNotSet,
+    
+    #[msg("Acceleration trigger has already been applied")]
+    AccelerationAlreadyApplied,
+    
+    #[msg("Stream recipient is represented by a position token")]
+    PositionTokenized,
+    
+    #[msg("Stream is pledged to a lender")]
+    StreamHasLien,
+    
+    #[msg("Stream has no outstanding lien")]
+    LienNotFound,
+    
+    #[msg("Stream can only be cancelled with notice")]
+    CancelNoticeRequired,
+    
+    #[msg("Cancellation must take effect after the notice period")]
+    CancelNoticeTooShort,
+    
+    #[msg("Cancellation has already been requested")]
+    CancelAlreadyRequested,
+    
+    #[msg("No cancellation has been requested")]
+    CancelNotRequested,
+    
+    #[msg("Requested cancellation has not taken effect yet")]
+    CancelNotDue,
+    
+    #[msg("Amendment does not change the stream terms")]
+    AmendmentUnchanged,
+    
+    #[msg("No amendment has been proposed")]
+    AmendmentNotFound,
+    
+    #[msg("Accepted terms do not match the proposed amendment")]
+    AmendmentMismatch,
+    
+    #[msg("Treasury has an audit log that must be passed")]
+    AuditLogRequired,
+    
+    #[msg("Audit log does not belong to the treasury")]
+    AuditLogMismatch,
+    
+    #[msg("Milestone not found")]
+    MilestoneNotFound,
+    
+    #[msg("Milestone has already been approved")]
+    MilestoneAlreadyApproved,
+    
+    #[msg("Yield vault accounts are missing or do not match the stream")]
+    YieldVaultMismatch,
+    
+    #[msg("Yield vault moved a different amount than requested")]
+    YieldVaultShortfall,
+    
+    #[msg("Stream principal is deposited in a yield vault")]
+    YieldVaultNotEmpty,
+    
+    #[msg("An amendment is awaiting the recipient's acceptance")]
+    AmendmentPending,
+    
+    #[msg("A sender transfer is awaiting the new sender's acceptance")]
+    SenderTransferPending,
+    
+    #[msg("The stream sender must co-sign this action")]
+    SenderSignatureRequired,
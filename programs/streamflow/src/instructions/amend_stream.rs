@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ProposeAmendment<'info> {
+    #[account(
+        mut,
+        has_one = sender @ StreamFlowError::UnauthorizedAccess,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = !stream.load()?.has_pending_cancel() @ StreamFlowError::CancelAlreadyRequested,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub sender: Signer<'info>,
+}
+
+/// Countersign a pending amendment.
+///
+/// Tokenized and pledged streams are excluded, the position holder or lender would
+/// not have consented to the new terms.
+#[derive(Accounts)]
+pub struct AcceptAmendment<'info> {
+    #[account(
+        mut,
+        has_one = recipient @ StreamFlowError::UnauthorizedAccess,
+        constraint = stream.load()?.has_pending_amendment() @ StreamFlowError::AmendmentNotFound,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = !stream.load()?.has_pending_cancel() @ StreamFlowError::CancelAlreadyRequested,
+        constraint = !stream.load()?.has_position_token() @ StreamFlowError::PositionTokenized,
+        constraint = !stream.load()?.has_lien() @ StreamFlowError::StreamHasLien,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub recipient: Signer<'info>,
+}
+
+/// Propose new terms for the stream. Terms that strictly favor the recipient are
+/// applied immediately, anything else waits for `accept_amendment`. Proposing the
+/// current terms withdraws a pending proposal.
+pub fn propose_handler(ctx: Context<ProposeAmendment>, terms: StreamTerms) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    let current = StreamTerms::of(stream);
+
+    if terms == current {
+        require!(
+            stream.has_pending_amendment(),
+            StreamFlowError::AmendmentUnchanged
+        );
+        stream.pending_amendment = StreamTerms::default();
+        stream.amendment_proposed_at = 0;
+
+        emit!(AmendmentWithdrawn {
+            stream: ctx.accounts.stream.key(),
+            sender: stream.sender,
+            withdrawn_at: current_time,
+        });
+
+        return Ok(());
+    }
+
+    stream.validate_terms(&terms, current_time)?;
+
+    if terms.favors_recipient(&current) {
+        stream.apply_terms(&terms);
+        stream.pending_amendment = StreamTerms::default();
+        stream.amendment_proposed_at = 0;
+
+        emit!(StreamAmended {
+            stream: ctx.accounts.stream.key(),
+            sender: stream.sender,
+            recipient: stream.recipient,
+            previous_terms: current,
+            terms,
+            countersigned: false,
+            amended_at: current_time,
+        });
+
+        return Ok(());
+    }
+
+    stream.pending_amendment = terms;
+    stream.amendment_proposed_at = current_time;
+
+    emit!(AmendmentProposed {
+        stream: ctx.accounts.stream.key(),
+        sender: stream.sender,
+        recipient: stream.recipient,
+        terms,
+        proposed_at: current_time,
+    });
+
+    Ok(())
+}
+
+/// Apply the pending amendment. `terms` must repeat the proposal so the recipient
+/// cannot be front-run by a different one.
+pub fn accept_handler(ctx: Context<AcceptAmendment>, terms: StreamTerms) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+
+    require!(
+        terms == stream.pending_amendment,
+        StreamFlowError::AmendmentMismatch
+    );
+
+    // Tokens may have unlocked or been withdrawn since the proposal
+    stream.validate_terms(&terms, current_time)?;
+
+    let previous_terms = StreamTerms::of(stream);
+    stream.apply_terms(&terms);
+    stream.pending_amendment = StreamTerms::default();
+    stream.amendment_proposed_at = 0;
+
+    emit!(StreamAmended {
+        stream: ctx.accounts.stream.key(),
+        sender: stream.sender,
+        recipient: stream.recipient,
+        previous_terms,
+        terms,
+        countersigned: true,
+        amended_at: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AmendmentProposed {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub terms: StreamTerms,
+    pub proposed_at: i64,
+}
+
+#[event]
+pub struct AmendmentWithdrawn {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub withdrawn_at: i64,
+}
+
+#[event]
+pub struct StreamAmended {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub previous_terms: StreamTerms,
+    pub terms: StreamTerms,
+    /// Whether the recipient countersigned, false for sender-only changes
+    pub countersigned: bool,
+    pub amended_at: i64,
+}
@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+/// Offset of the first signature entry in ed25519 program instruction data
+const ED25519_OFFSETS_START: usize = 2;
+
+/// Size of one signature entry in ed25519 program instruction data
+const ED25519_OFFSETS_SIZE: usize = 14;
+
+/// Instruction index the ed25519 program uses for "this instruction"
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Record an attestor approval of a milestone.
+///
+/// The attestor signs `milestone_approval_message` off-chain; anyone can submit it
+/// by placing an ed25519 program instruction carrying the signature earlier in the
+/// same transaction.
+#[derive(Accounts)]
+pub struct ApproveMilestone<'info> {
+    #[account(
+        mut,
+        constraint = stream.load()?.vesting_type()? == VestingType::Milestone @ StreamFlowError::InvalidVestingSchedule,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    /// CHECK: the instructions sysvar, checked by address
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+pub fn handler(ctx: Context<ApproveMilestone>, milestone_index: u8) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let stream_key = ctx.accounts.stream.key();
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    let milestones = &stream.milestones;
+
+    require!(
+        (milestone_index as usize) < milestones.count as usize,
+        StreamFlowError::MilestoneNotFound
+    );
+    let amount = milestones.amounts[milestone_index as usize];
+    let message = milestone_approval_message(&stream_key, milestone_index, amount);
+
+    require!(
+        is_signed_in_transaction(
+            &ctx.accounts.instructions.to_account_info(),
+            &milestones.attestor,
+            &message,
+        )?,
+        StreamFlowError::EscrowReleaseConditionsNotMet
+    );
+
+    stream
+        .milestones
+        .approve(milestone_index as usize, current_time)?;
+
+    emit!(MilestoneApproved {
+        stream: stream_key,
+        attestor: stream.milestones.attestor,
+        milestone_index,
+        amount,
+        approved_at: current_time,
+    });
+
+    Ok(())
+}
+
+/// Whether an ed25519 program instruction before the current one verifies a
+/// signature of `message` by `signer`
+fn is_signed_in_transaction(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<bool> {
+    let current_index = load_current_index_checked(instructions)?;
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions)?;
+        if instruction.program_id == ed25519_program::ID
+            && ed25519_data_verifies(&instruction.data, signer, message)
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Check ed25519 program instruction data for an entry over `message` by `signer`.
+///
+/// Only entries whose key, signature and message all live in the ed25519
+/// instruction itself are considered, so they are the data the program verified.
+fn ed25519_data_verifies(data: &[u8], signer: &Pubkey, message: &[u8]) -> bool {
+    let Some(count) = data.first() else {
+        return false;
+    };
+
+    (0..*count as usize).any(|entry| {
+        let start = ED25519_OFFSETS_START + entry * ED25519_OFFSETS_SIZE;
+        let Some(offsets) = data.get(start..start + ED25519_OFFSETS_SIZE) else {
+            return false;
+        };
+        let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+        let signature_instruction = read(2);
+        let public_key_offset = read(4) as usize;
+        let public_key_instruction = read(6);
+        let message_offset = read(8) as usize;
+        let message_size = read(10) as usize;
+        let message_instruction = read(12);
+
+        if signature_instruction != ED25519_CURRENT_INSTRUCTION
+            || public_key_instruction != ED25519_CURRENT_INSTRUCTION
+            || message_instruction != ED25519_CURRENT_INSTRUCTION
+        {
+            return false;
+        }
+
+        data.get(public_key_offset..public_key_offset + 32) == Some(signer.as_ref())
+            && data.get(message_offset..message_offset + message_size) == Some(message)
+    })
+}
+
+#[event]
+pub struct MilestoneApproved {
+    pub stream: Pubkey,
+    pub attestor: Pubkey,
+    pub milestone_index: u8,
+    pub amount: u64,
+    pub approved_at: i64,
+}
@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateComplianceRegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ComplianceRegistry::LEN,
+        seeds = [
+            b"compliance_registry",
+            authority.key().as_ref(),
+        ],
+        bump
+    )]
+    pub registry: Box<Account<'info, ComplianceRegistry>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateComplianceRegistry<'info> {
+    #[account(
+        mut,
+        has_one = authority @ StreamFlowError::UnauthorizedAccess,
+    )]
+    pub registry: Box<Account<'info, ComplianceRegistry>>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Bind a treasury to a registry, or unbind it when `registry` is omitted. Streams
+/// keep the registry they were stamped with.
+#[derive(Accounts)]
+pub struct SetTreasuryComplianceRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury", treasury.authority.as_ref()],
+        bump = treasury.bump,
+        has_one = authority @ StreamFlowError::UnauthorizedAccess,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    pub authority: Signer<'info>,
+
+    pub registry: Option<Box<Account<'info, ComplianceRegistry>>>,
+
+    /// Required once the treasury has an audit log
+    #[account(mut)]
+    pub audit_log: Option<Box<Account<'info, TreasuryAuditLog>>>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeStreamWithdrawals<'info> {
+    #[account(
+        mut,
+        constraint = stream.load()?.compliance_registry == registry.key() @ StreamFlowError::ComplianceCheckFailed,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        has_one = authority @ StreamFlowError::UnauthorizedAccess,
+    )]
+    pub registry: Box<Account<'info, ComplianceRegistry>>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn create_registry_handler(
+    ctx: Context<CreateComplianceRegistry>,
+    mode: ComplianceMode,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    registry.authority = ctx.accounts.authority.key();
+    registry.mode = mode;
+    registry.entry_count = 0;
+    registry.merkle_root = [0u8; 32];
+    registry.bump = ctx.bumps.registry;
+
+    emit!(ComplianceRegistryCreated {
+        registry: registry.key(),
+        authority: registry.authority,
+        mode,
+    });
+
+    Ok(())
+}
+
+/// Add and remove listed accounts. Removals are applied after additions.
+pub fn update_entries_handler(
+    ctx: Context<UpdateComplianceRegistry>,
+    add: Vec<Pubkey>,
+    remove: Vec<Pubkey>,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    for account in add.iter() {
+        registry.add_entry(*account)?;
+    }
+    for account in remove.iter() {
+        registry.remove_entry(account);
+    }
+
+    emit!(ComplianceRegistryUpdated {
+        registry: registry.key(),
+        added: add,
+        removed: remove,
+        merkle_root: registry.merkle_root,
+    });
+
+    Ok(())
+}
+
+pub fn set_merkle_root_handler(
+    ctx: Context<UpdateComplianceRegistry>,
+    merkle_root: [u8; 32],
+) -> Result<()> {
+    let registry = &mut ctx.accounts.registry;
+    require!(
+        registry.mode == ComplianceMode::MerkleAllowlist,
+        StreamFlowError::InvalidTreasuryConfiguration
+    );
+    registry.merkle_root = merkle_root;
+
+    emit!(ComplianceRegistryUpdated {
+        registry: registry.key(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        merkle_root,
+    });
+
+    Ok(())
+}
+
+pub fn set_treasury_registry_handler(ctx: Context<SetTreasuryComplianceRegistry>) -> Result<()> {
+    let registry = ctx
+        .accounts
+        .registry
+        .as_ref()
+        .map_or(Pubkey::default(), |registry| registry.key());
+
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.compliance_registry = registry;
+
+    record_treasury_operation(
+        treasury,
+        ctx.accounts.audit_log.as_deref_mut(),
+        TreasuryOperation::SetComplianceRegistry,
+        ctx.accounts.authority.key(),
+        0,
+        Pubkey::default(),
+    )?;
+
+    emit!(TreasuryComplianceRegistrySet {
+        treasury: treasury.key(),
+        registry,
+    });
+
+    Ok(())
+}
+
+/// Freeze or unfreeze withdrawals of a stream bound to the registry. A frozen
+/// stream cannot be withdrawn from, cancelled or transferred.
+pub fn freeze_handler(ctx: Context<FreezeStreamWithdrawals>, frozen: bool) -> Result<()> {
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    stream.withdrawals_frozen = frozen.into();
+
+    emit!(StreamWithdrawalsFrozen {
+        stream: ctx.accounts.stream.key(),
+        registry: ctx.accounts.registry.key(),
+        frozen,
+        updated_at: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ComplianceRegistryCreated {
+    pub registry: Pubkey,
+    pub authority: Pubkey,
+    pub mode: ComplianceMode,
+}
+
+#[event]
+pub struct ComplianceRegistryUpdated {
+    pub registry: Pubkey,
+    pub added: Vec<Pubkey>,
+    pub removed: Vec<Pubkey>,
+    pub merkle_root: [u8; 32],
+}
+
+#[event]
+pub struct TreasuryComplianceRegistrySet {
+    pub treasury: Pubkey,
+    /// Default when the registry was detached
+    pub registry: Pubkey,
+}
+
+#[event]
+pub struct StreamWithdrawalsFrozen {
+    pub stream: Pubkey,
+    pub registry: Pubkey,
+    pub frozen: bool,
+    pub updated_at: i64,
+}
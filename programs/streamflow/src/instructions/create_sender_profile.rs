@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CreateSenderProfile<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = SenderProfile::LEN,
+        seeds = [
+            b"sender_profile",
+            sender.key().as_ref(),
+        ],
+        bump
+    )]
+    pub sender_profile: Account<'info, SenderProfile>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the profile that hands out sequential stream ids to `sender`
+pub fn handler(ctx: Context<CreateSenderProfile>) -> Result<()> {
+    let profile = &mut ctx.accounts.sender_profile;
+    profile.authority = ctx.accounts.sender.key();
+    profile.next_nonce = 0;
+    profile.bump = ctx.bumps.sender_profile;
+
+    emit!(SenderProfileCreated {
+        sender_profile: profile.key(),
+        authority: profile.authority,
+        created_at: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct SenderProfileCreated {
+    pub sender_profile: Pubkey,
+    pub authority: Pubkey,
+    pub created_at: i64,
+}
@@ -0,0 +1,394 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::error::StreamFlowError;
+use crate::instructions::EscrowYieldEnabled;
+use crate::state::constants::{self, MAX_VESTING_SEGMENTS};
+use crate::state::vesting::{self, AccelerationClause, LeaverPolicy, VestingSegment};
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(stream_id: [u8; 32])]
+pub struct CreateVestingStream<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = Stream::LEN,
+        seeds = [
+            b"stream",
+            sender.key().as_ref(),
+            stream_id.as_ref(),
+        ],
+        bump
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        init,
+        payer = sender,
+        token::mint = mint,
+        token::authority = stream,
+        seeds = [
+            b"escrow",
+            stream.key().as_ref(),
+        ],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == mint.key(),
+        constraint = sender_token_account.owner == sender.key()
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// Supplies the stream id when the sender uses sequential ids
+    #[account(
+        mut,
+        seeds = [
+            b"sender_profile",
+            sender.key().as_ref(),
+        ],
+        bump = sender_profile.bump,
+    )]
+    pub sender_profile: Option<Account<'info, SenderProfile>>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// Approval of the yield vault program, required with `yield_share_bps`
+    #[account(
+        seeds = [
+            b"yield_vault",
+            approved_yield_vault.vault_program.as_ref(),
+        ],
+        bump = approved_yield_vault.bump,
+    )]
+    pub approved_yield_vault: Option<Box<Account<'info, ApprovedYieldVault>>>,
+
+    /// CHECK: vault state, must be owned by the approved vault program
+    pub yield_vault: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        constraint = yield_vault_token_account.mint == mint.key() @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub yield_vault_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(
+    ctx: Context<CreateVestingStream>,
+    stream_id: [u8; 32],
+    recipient: Pubkey,
+    start_time: i64,
+    cliff_time: i64,
+    segments: Vec<VestingSegment>,
+    cancelable_by_sender: bool,
+    cancelable_by_recipient: bool,
+    cancel_notice_period: u64,
+    leaver_policy: LeaverPolicy,
+    acceleration: AccelerationClause,
+    stream_name: String,
+    yield_share_bps: Option<u16>,
+) -> Result<()> {
+    // The schedule defines both the duration and the deposit
+    let (duration, deposited_amount) = vesting::validate_segments(&segments)?;
+    let end_time = start_time
+        .checked_add(duration as i64)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+
+    let mut vesting_segments = [VestingSegment::default(); MAX_VESTING_SEGMENTS];
+    vesting_segments[..segments.len()].copy_from_slice(&segments);
+
+    let mut stream = ctx.accounts.stream.load_init()?;
+    stream.vesting_type = VestingType::Custom as u8;
+    stream.vesting_segment_count = segments.len() as u8;
+    stream.vesting_segments = vesting_segments;
+    drop(stream);
+
+    initialize_stream(
+        ctx,
+        stream_id,
+        recipient,
+        deposited_amount,
+        start_time,
+        end_time,
+        cliff_time,
+        cancelable_by_sender,
+        cancelable_by_recipient,
+        cancel_notice_period,
+        leaver_policy,
+        acceleration,
+        stream_name,
+        yield_share_bps,
+    )
+}
+
+pub fn monthly_handler(
+    ctx: Context<CreateVestingStream>,
+    stream_id: [u8; 32],
+    recipient: Pubkey,
+    start_time: i64,
+    cliff_time: i64,
+    deposited_amount: u64,
+    months: u16,
+    cancelable_by_sender: bool,
+    cancelable_by_recipient: bool,
+    cancel_notice_period: u64,
+    leaver_policy: LeaverPolicy,
+    acceleration: AccelerationClause,
+    stream_name: String,
+    yield_share_bps: Option<u16>,
+) -> Result<()> {
+    require!(
+        months > 0 && months <= constants::MAX_VESTING_MONTHS,
+        StreamFlowError::InvalidVestingSchedule
+    );
+
+    let end_time = calendar::add_months(start_time, months as u32)?;
+
+    let mut stream = ctx.accounts.stream.load_init()?;
+    stream.vesting_type = VestingType::Monthly as u8;
+    stream.vesting_months = months;
+    drop(stream);
+
+    initialize_stream(
+        ctx,
+        stream_id,
+        recipient,
+        deposited_amount,
+        start_time,
+        end_time,
+        cliff_time,
+        cancelable_by_sender,
+        cancelable_by_recipient,
+        cancel_notice_period,
+        leaver_policy,
+        acceleration,
+        stream_name,
+        yield_share_bps,
+    )
+}
+
+/// Create a stream whose tranches unlock as `attestor` approves each milestone,
+/// see `approve_milestone`. `end_time` only bounds the stream duration; pending
+/// milestones stay locked until approved or the stream is cancelled.
+pub fn milestone_handler(
+    ctx: Context<CreateVestingStream>,
+    stream_id: [u8; 32],
+    recipient: Pubkey,
+    start_time: i64,
+    end_time: i64,
+    attestor: Pubkey,
+    milestone_amounts: Vec<u64>,
+    cancelable_by_sender: bool,
+    cancelable_by_recipient: bool,
+    cancel_notice_period: u64,
+    leaver_policy: LeaverPolicy,
+    acceleration: AccelerationClause,
+    stream_name: String,
+    yield_share_bps: Option<u16>,
+) -> Result<()> {
+    let (milestones, deposited_amount) = MilestoneSchedule::new(attestor, &milestone_amounts)?;
+
+    let mut stream = ctx.accounts.stream.load_init()?;
+    stream.vesting_type = VestingType::Milestone as u8;
+    stream.milestones = milestones;
+    drop(stream);
+
+    initialize_stream(
+        ctx,
+        stream_id,
+        recipient,
+        deposited_amount,
+        start_time,
+        end_time,
+        start_time,
+        cancelable_by_sender,
+        cancelable_by_recipient,
+        cancel_notice_period,
+        leaver_policy,
+        acceleration,
+        stream_name,
+        yield_share_bps,
+    )
+}
+
+fn initialize_stream(
+    ctx: Context<CreateVestingStream>,
+    stream_id: [u8; 32],
+    recipient: Pubkey,
+    deposited_amount: u64,
+    start_time: i64,
+    end_time: i64,
+    cliff_time: i64,
+    cancelable_by_sender: bool,
+    cancelable_by_recipient: bool,
+    cancel_notice_period: u64,
+    leaver_policy: LeaverPolicy,
+    mut acceleration: AccelerationClause,
+    stream_name: String,
+    yield_share_bps: Option<u16>,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+
+    sender_profile::claim_stream_id(ctx.accounts.sender_profile.as_deref_mut(), &stream_id)?;
+    require!(start_time >= current_time, StreamFlowError::InvalidStartTime);
+    require!(stream_name.len() <= 64, StreamFlowError::StreamMetadataTooLarge);
+    require!(
+        recipient != ctx.accounts.sender.key(),
+        StreamFlowError::InvalidRecipient
+    );
+
+    let duration = (end_time - start_time) as u64;
+    utils::validate_duration(duration)?;
+    require!(
+        cancel_notice_period <= constants::MAX_STREAM_DURATION,
+        StreamFlowError::InvalidDuration
+    );
+    leaver_policy.validate()?;
+    acceleration.initialize()?;
+
+    // Rate fields describe the average rate over the whole schedule
+    Stream::validate_params(
+        start_time,
+        end_time,
+        cliff_time,
+        deposited_amount,
+        deposited_amount,
+        duration,
+    )?;
+
+    let mut name = [0u8; 64];
+    name[..stream_name.len()].copy_from_slice(stream_name.as_bytes());
+
+    let stream = &mut *ctx.accounts.stream.load_init()?;
+    stream.sender = ctx.accounts.sender.key();
+    stream.creator = ctx.accounts.sender.key();
+    stream.stream_id = stream_id;
+    stream.recipient = recipient;
+    stream.mint = ctx.accounts.mint.key();
+    stream.escrow_tokens = ctx.accounts.escrow_token_account.key();
+    stream.deposited_amount = deposited_amount;
+    stream.withdrawn_amount = 0;
+    stream.start_time = start_time;
+    stream.end_time = end_time;
+    stream.cliff_time = cliff_time;
+    stream.cancelable_by_sender = cancelable_by_sender.into();
+    stream.cancelable_by_recipient = cancelable_by_recipient.into();
+    stream.cancel_notice_period = cancel_notice_period;
+    stream.transferable_by_sender = false.into();
+    stream.transferable_by_recipient = false.into();
+    stream.automatic_withdrawal = false.into();
+    stream.rate_amount = deposited_amount;
+    stream.rate_interval_in_seconds = duration;
+    stream.cancelled = false.into();
+    stream.closed = false.into();
+    stream.paused = false.into();
+    stream.total_paused_time = 0;
+    stream.last_withdrawn_at = start_time;
+    stream.name = name;
+    stream.leaver_policy = leaver_policy;
+    stream.acceleration = acceleration;
+    stream.bump = ctx.bumps.stream;
+
+    if let Some(recipient_share_bps) = yield_share_bps {
+        stream.escrow_yield = escrow_yield_config(ctx.accounts, recipient_share_bps)?;
+        emit!(EscrowYieldEnabled {
+            stream: ctx.accounts.stream.key(),
+            vault_program: stream.escrow_yield.vault_program,
+            vault: stream.escrow_yield.vault,
+            recipient_share_bps,
+        });
+    }
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.sender_token_account.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.sender.to_account_info(),
+            },
+        ),
+        deposited_amount,
+    )?;
+
+    emit!(VestingStreamCreated {
+        stream: ctx.accounts.stream.key(),
+        stream_id,
+        sender: stream.sender,
+        recipient,
+        mint: stream.mint,
+        vesting_type: stream.vesting_type()?,
+        deposited_amount,
+        start_time,
+        end_time,
+        cliff_time,
+        created_at: current_time,
+    });
+
+    msg!(
+        "Vesting stream created: {} tokens over {} seconds",
+        deposited_amount,
+        duration
+    );
+
+    Ok(())
+}
+
+/// Yield vault terms of a new stream, from the approved vault accounts passed in
+fn escrow_yield_config(
+    accounts: &CreateVestingStream,
+    recipient_share_bps: u16,
+) -> Result<EscrowYield> {
+    let (Some(approval), Some(vault), Some(vault_token_account)) = (
+        accounts.approved_yield_vault.as_deref(),
+        accounts.yield_vault.as_ref(),
+        accounts.yield_vault_token_account.as_deref(),
+    ) else {
+        return err!(StreamFlowError::YieldVaultMismatch);
+    };
+    require_keys_eq!(
+        *vault.owner,
+        approval.vault_program,
+        StreamFlowError::YieldVaultMismatch
+    );
+    require!(
+        recipient_share_bps <= YIELD_SHARE_DENOMINATOR,
+        StreamFlowError::InvalidAmount
+    );
+
+    Ok(EscrowYield {
+        vault_program: approval.vault_program,
+        vault: vault.key(),
+        vault_token_account: vault_token_account.key(),
+        deposited_principal: 0,
+        harvested_yield: 0,
+        recipient_share_bps,
+        adapter: approval.adapter,
+        recipient_consented: false.into(),
+        _padding: [0; 4],
+    })
+}
+
+#[event]
+pub struct VestingStreamCreated {
+    pub stream: Pubkey,
+    pub stream_id: [u8; 32],
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Pubkey,
+    pub vesting_type: VestingType,
+    pub deposited_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub cliff_time: i64,
+    pub created_at: i64,
+}
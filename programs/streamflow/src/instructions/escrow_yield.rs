@@ -0,0 +1,432 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::error::StreamFlowError;
+use crate::instructions::LienRepaid;
+use crate::program::Streamflow;
+use crate::state::*;
+
+/// Approve a vault program escrows may be deposited in. Only the program upgrade
+/// authority can approve vaults.
+#[derive(Accounts)]
+pub struct ApproveYieldVault<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ApprovedYieldVault::LEN,
+        seeds = [
+            b"yield_vault",
+            vault_program.key().as_ref(),
+        ],
+        bump
+    )]
+    pub approved_yield_vault: Account<'info, ApprovedYieldVault>,
+
+    /// CHECK: yield vault program being approved
+    #[account(executable)]
+    pub vault_program: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ StreamFlowError::InvalidProgramData,
+    )]
+    pub program: Program<'info, Streamflow>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ StreamFlowError::InvalidUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Revoke a vault approval. Streams already using the vault stop depositing and
+/// unwind on their next rebalance.
+#[derive(Accounts)]
+pub struct RevokeYieldVault<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            b"yield_vault",
+            approved_yield_vault.vault_program.as_ref(),
+        ],
+        bump = approved_yield_vault.bump,
+    )]
+    pub approved_yield_vault: Account<'info, ApprovedYieldVault>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ StreamFlowError::InvalidProgramData,
+    )]
+    pub program: Program<'info, Streamflow>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ StreamFlowError::InvalidUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+/// Accept the yield vault and split the sender chose at creation. Nothing is
+/// deposited before the recipient accepts.
+#[derive(Accounts)]
+pub struct AcceptEscrowYield<'info> {
+    #[account(
+        mut,
+        has_one = recipient @ StreamFlowError::UnauthorizedAccess,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = stream.load()?.escrow_yield.is_enabled() @ StreamFlowError::YieldVaultMismatch,
+        constraint = !stream.load()?.escrow_yield.recipient_consented.get() @ StreamFlowError::StreamModificationNotAllowed,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub recipient: Signer<'info>,
+}
+
+/// Move the escrow towards holding only unlocked tokens, depositing the unvested
+/// balance in the vault or redeeming principal that has unlocked since. Anyone can
+/// call it.
+#[derive(Accounts)]
+pub struct RebalanceEscrowYield<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"stream",
+            stream.load()?.creator.as_ref(),
+            stream.load()?.stream_id.as_ref(),
+        ],
+        bump = stream.load()?.bump,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow",
+            stream.key().as_ref(),
+        ],
+        bump,
+        constraint = escrow_token_account.key() == stream.load()?.escrow_tokens @ StreamFlowError::EscrowAccountNotFound,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: approval of the vault program, may have been revoked
+    #[account(
+        seeds = [
+            b"yield_vault",
+            stream.load()?.escrow_yield.vault_program.as_ref(),
+        ],
+        bump,
+    )]
+    pub approved_yield_vault: UncheckedAccount<'info>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    pub vault_program: UncheckedAccount<'info>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub vault_token_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Pay out the yield earned above the deposited principal. Anyone can call it, the
+/// accounts paid are fixed by the stream. The recipient share is paid like a
+/// withdrawal, repaying an outstanding lien first.
+#[derive(Accounts)]
+pub struct HarvestEscrowYield<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"stream",
+            stream.load()?.creator.as_ref(),
+            stream.load()?.stream_id.as_ref(),
+        ],
+        bump = stream.load()?.bump,
+        constraint = !stream.load()?.withdrawals_frozen.get() @ StreamFlowError::AccountFrozen,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow",
+            stream.key().as_ref(),
+        ],
+        bump,
+        constraint = escrow_token_account.key() == stream.load()?.escrow_tokens @ StreamFlowError::EscrowAccountNotFound,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = sender_token_account.owner == stream.load()?.sender @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = stream.load()?.is_withdraw_destination(
+            &recipient_token_account.key(),
+            &recipient_token_account.owner,
+            position_token_account.as_deref(),
+        ) @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    /// Position token account of the holder when the stream is tokenized
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Lender repayment account, required while the stream has a lien
+    #[account(
+        mut,
+        constraint = lender_token_account.key() == stream.load()?.lien.lender_token_account @ StreamFlowError::TokenAccountNotFound
+    )]
+    pub lender_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    pub vault_program: UncheckedAccount<'info>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub vault_token_account: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn approve_vault_handler(ctx: Context<ApproveYieldVault>, adapter: YieldAdapter) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let approval = &mut ctx.accounts.approved_yield_vault;
+    approval.vault_program = ctx.accounts.vault_program.key();
+    approval.adapter = adapter as u8;
+    approval.approved_at = current_time;
+    approval.bump = ctx.bumps.approved_yield_vault;
+
+    emit!(YieldVaultApproved {
+        vault_program: approval.vault_program,
+        adapter,
+        approved_at: current_time,
+    });
+
+    Ok(())
+}
+
+pub fn revoke_vault_handler(ctx: Context<RevokeYieldVault>) -> Result<()> {
+    emit!(YieldVaultRevoked {
+        vault_program: ctx.accounts.approved_yield_vault.vault_program,
+        revoked_at: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn accept_handler(ctx: Context<AcceptEscrowYield>) -> Result<()> {
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    stream.escrow_yield.recipient_consented = true.into();
+
+    emit!(EscrowYieldAccepted {
+        stream: ctx.accounts.stream.key(),
+        recipient: stream.recipient,
+        accepted_at: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn rebalance_handler(ctx: Context<RebalanceEscrowYield>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let vault = load_yield_vault(
+        &ctx.accounts.stream,
+        ctx.accounts.escrow_token_account.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        Some(&ctx.accounts.vault_program),
+        Some(&ctx.accounts.vault),
+        Some(&ctx.accounts.vault_token_account),
+    )?;
+    let escrow = ctx.accounts.escrow_token_account.to_account_info();
+    let stream = &ctx.accounts.stream;
+
+    // Deposits need the recipient's consent and a standing vault approval. A frozen
+    // or pending-cancel stream only unwinds towards the escrow.
+    let approved = ApprovedYieldVault::is_approved(&ctx.accounts.approved_yield_vault);
+    let (target, principal) = {
+        let stream = stream.load()?;
+        let target = if !approved
+            || !stream.escrow_yield.recipient_consented.get()
+            || stream.withdrawals_frozen.get()
+            || stream.has_pending_cancel()
+        {
+            0
+        } else {
+            stream.unvested_balance(current_time)?
+        };
+        (target, stream.escrow_yield.deposited_principal)
+    };
+
+    let (deposited, redeemed) = if target > principal {
+        deposit_escrow_principal(stream, vault.as_ref(), &escrow, target - principal)?;
+        (target - principal, 0)
+    } else if principal > target {
+        redeem_escrow_principal(stream, vault.as_ref(), &escrow, principal - target)?;
+        (0, principal - target)
+    } else {
+        (0, 0)
+    };
+
+    emit!(EscrowYieldRebalanced {
+        stream: stream.key(),
+        deposited,
+        redeemed,
+        deposited_principal: stream.load()?.escrow_yield.deposited_principal,
+    });
+
+    Ok(())
+}
+
+pub fn harvest_handler(ctx: Context<HarvestEscrowYield>) -> Result<()> {
+    let vault = load_yield_vault(
+        &ctx.accounts.stream,
+        ctx.accounts.escrow_token_account.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        Some(&ctx.accounts.vault_program),
+        Some(&ctx.accounts.vault),
+        Some(&ctx.accounts.vault_token_account),
+    )?;
+    let escrow = ctx.accounts.escrow_token_account.to_account_info();
+
+    let earned = redeem_escrow_yield(&ctx.accounts.stream, vault.as_ref(), &escrow)?;
+    require!(earned > 0, StreamFlowError::InvalidAmount);
+
+    // The recipient share repays an outstanding lien first, like a withdrawal
+    let mut stream = ctx.accounts.stream.load_mut()?;
+    let (to_recipient, to_sender) = stream.escrow_yield.split_yield(earned);
+    let lender = stream.lien.lender;
+    let (to_lender, to_recipient) = stream.lien.split_withdrawal(to_recipient);
+    let lien_cleared = stream.lien.repay(to_lender)?;
+    let seeds = StreamSeeds::of(&stream);
+    drop(stream);
+    let signer_seeds = &[&seeds.signer()[..]];
+
+    let lender_token_account = if to_lender > 0 {
+        Some(
+            ctx.accounts
+                .lender_token_account
+                .as_ref()
+                .ok_or(StreamFlowError::TokenAccountNotFound)?,
+        )
+    } else {
+        None
+    };
+
+    for (destination, amount) in [
+        (lender_token_account, to_lender),
+        (Some(&ctx.accounts.recipient_token_account), to_recipient),
+        (Some(&ctx.accounts.sender_token_account), to_sender),
+    ] {
+        let Some(destination) = destination else {
+            continue;
+        };
+        if amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token_account.to_account_info(),
+                        to: destination.to_account_info(),
+                        authority: ctx.accounts.stream.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+            )?;
+        }
+    }
+
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    stream.escrow_yield.harvested_yield = stream
+        .escrow_yield
+        .harvested_yield
+        .checked_add(earned)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+
+    if to_lender > 0 {
+        emit!(LienRepaid {
+            stream: ctx.accounts.stream.key(),
+            lender,
+            amount: to_lender,
+            outstanding: stream.lien.outstanding(),
+            cleared: lien_cleared,
+        });
+    }
+    emit!(EscrowYieldHarvested {
+        stream: ctx.accounts.stream.key(),
+        lender_amount: to_lender,
+        recipient_amount: to_recipient,
+        sender_amount: to_sender,
+        harvested_yield: stream.escrow_yield.harvested_yield,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct EscrowYieldEnabled {
+    pub stream: Pubkey,
+    pub vault_program: Pubkey,
+    pub vault: Pubkey,
+    pub recipient_share_bps: u16,
+}
+
+#[event]
+pub struct EscrowYieldAccepted {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub accepted_at: i64,
+}
+
+#[event]
+pub struct YieldVaultApproved {
+    pub vault_program: Pubkey,
+    pub adapter: YieldAdapter,
+    pub approved_at: i64,
+}
+
+#[event]
+pub struct YieldVaultRevoked {
+    pub vault_program: Pubkey,
+    pub revoked_at: i64,
+}
+
+#[event]
+pub struct EscrowYieldRebalanced {
+    pub stream: Pubkey,
+    pub deposited: u64,
+    pub redeemed: u64,
+    pub deposited_principal: u64,
+}
+
+#[event]
+pub struct EscrowYieldHarvested {
+    pub stream: Pubkey,
+    /// Part of the recipient share repaying a lien
+    pub lender_amount: u64,
+    pub recipient_amount: u64,
+    pub sender_amount: u64,
+    /// Total yield paid out by the stream so far
+    pub harvested_yield: u64,
+}
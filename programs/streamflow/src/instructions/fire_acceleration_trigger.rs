@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::vesting::AccelerationTrigger;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct FireAccelerationTrigger<'info> {
+    #[account(
+        mut,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = stream.load()?.acceleration.can_trigger(&authority.key(), &stream.load()?.sender) @ StreamFlowError::UnauthorizedAccess,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<FireAccelerationTrigger>, trigger: AccelerationTrigger) -> Result<()> {
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let streamed_before = stream.streamed_amount(current_time)?;
+    let accelerated = stream.acceleration.fire(trigger, current_time)?;
+
+    emit!(AccelerationTriggerFired {
+        stream: ctx.accounts.stream.key(),
+        trigger,
+        fired_by: ctx.accounts.authority.key(),
+        triggers_fired: stream.acceleration.triggers_fired,
+        fired_at: current_time,
+    });
+
+    if accelerated {
+        let streamed_after = stream.streamed_amount(current_time)?;
+
+        emit!(VestingAccelerated {
+            stream: ctx.accounts.stream.key(),
+            recipient: stream.recipient,
+            percentage_bps: stream.acceleration.percentage_bps,
+            accelerated_amount: streamed_after - streamed_before,
+            accelerated_at: current_time,
+        });
+
+        msg!(
+            "Vesting accelerated: {} tokens now withdrawable",
+            streamed_after - streamed_before
+        );
+    }
+
+    Ok(())
+}
+
+#[event]
+pub struct AccelerationTriggerFired {
+    pub stream: Pubkey,
+    pub trigger: AccelerationTrigger,
+    pub fired_by: Pubkey,
+    pub triggers_fired: u8,
+    pub fired_at: i64,
+}
+
+#[event]
+pub struct VestingAccelerated {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub percentage_bps: u16,
+    pub accelerated_amount: u64,
+    pub accelerated_at: i64,
+}
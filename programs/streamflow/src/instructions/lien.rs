@@ -0,0 +1,201 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+/// Pledge the future withdrawals of a stream to a lender.
+///
+/// Tokenized streams are excluded because the position token could be sold out from
+/// under the lien. On streams bound to a compliance registry the lender must be
+/// approved, since repayments are paid out of the escrow. A lien blocks cancellation
+/// and termination, so the sender co-signs it while it holds either right.
+#[derive(Accounts)]
+pub struct CreateLien<'info> {
+    #[account(
+        mut,
+        has_one = recipient @ StreamFlowError::UnauthorizedAccess,
+        constraint = !stream.load()?.has_lien() @ StreamFlowError::StreamHasLien,
+        constraint = !stream.load()?.has_position_token() @ StreamFlowError::PositionTokenized,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = !stream.load()?.has_pending_cancel() @ StreamFlowError::CancelAlreadyRequested,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub recipient: Signer<'info>,
+
+    /// Required when the sender can cancel or terminate the stream
+    pub sender: Option<Signer<'info>>,
+
+    /// Account receiving repayments, owned by the lender
+    #[account(
+        constraint = lender_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub lender_token_account: Account<'info, TokenAccount>,
+
+    /// Required when the stream is bound to a compliance registry
+    pub compliance_registry: Option<Box<Account<'info, ComplianceRegistry>>>,
+}
+
+#[derive(Accounts)]
+pub struct RepayLien<'info> {
+    #[account(
+        mut,
+        constraint = stream.load()?.has_lien() @ StreamFlowError::LienNotFound,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = payer_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = payer_token_account.owner == payer.key() @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub payer_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lender_token_account.key() == stream.load()?.lien.lender_token_account @ StreamFlowError::TokenAccountNotFound,
+    )]
+    pub lender_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseLien<'info> {
+    #[account(
+        mut,
+        constraint = stream.load()?.has_lien() @ StreamFlowError::LienNotFound,
+        constraint = stream.load()?.lien.lender == lender.key() @ StreamFlowError::UnauthorizedAccess,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub lender: Signer<'info>,
+}
+
+/// Pledge up to `amount` of the unwithdrawn balance to `lender`. `compliance_proof`
+/// proves the lender against a Merkle allowlist registry.
+pub fn create_handler(
+    ctx: Context<CreateLien>,
+    lender: Pubkey,
+    amount: u64,
+    compliance_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.lender_token_account.owner == lender,
+        StreamFlowError::InvalidTokenAccountOwner
+    );
+    require!(
+        lender != Pubkey::default() && lender != ctx.accounts.stream.load()?.recipient,
+        StreamFlowError::InvalidPublicKey
+    );
+    require_compliant(
+        &ctx.accounts.stream.load()?.compliance_registry,
+        ctx.accounts.compliance_registry.as_deref(),
+        &lender,
+        &compliance_proof,
+    )?;
+
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    if stream.lien_requires_sender() {
+        require!(
+            ctx.accounts.sender.as_ref().map(|sender| sender.key()) == Some(stream.sender),
+            StreamFlowError::SenderSignatureRequired
+        );
+    }
+    require!(
+        amount > 0 && amount <= stream.remaining_balance(),
+        StreamFlowError::InvalidAmount
+    );
+
+    let current_time = Clock::get()?.unix_timestamp;
+    stream.lien = Lien {
+        lender,
+        lender_token_account: ctx.accounts.lender_token_account.key(),
+        amount,
+        repaid_amount: 0,
+        created_at: current_time,
+    };
+
+    emit!(LienCreated {
+        stream: ctx.accounts.stream.key(),
+        recipient: stream.recipient,
+        lender,
+        amount,
+        created_at: current_time,
+    });
+
+    Ok(())
+}
+
+/// Repay the lender directly, up to the outstanding amount
+pub fn repay_handler(ctx: Context<RepayLien>, amount: u64) -> Result<()> {
+    let amount = std::cmp::min(amount, ctx.accounts.stream.load()?.lien.outstanding());
+    require!(amount > 0, StreamFlowError::InvalidAmount);
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.payer_token_account.to_account_info(),
+        to: ctx.accounts.lender_token_account.to_account_info(),
+        authority: ctx.accounts.payer.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    let lender = stream.lien.lender;
+    let cleared = stream.lien.repay(amount)?;
+
+    emit!(LienRepaid {
+        stream: ctx.accounts.stream.key(),
+        lender,
+        amount,
+        outstanding: stream.lien.outstanding(),
+        cleared,
+    });
+
+    Ok(())
+}
+
+/// Forgive whatever is still owed, lifting the lien
+pub fn release_handler(ctx: Context<ReleaseLien>) -> Result<()> {
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    let forgiven_amount = stream.lien.outstanding();
+    stream.lien = Lien::default();
+
+    emit!(LienReleased {
+        stream: ctx.accounts.stream.key(),
+        lender: ctx.accounts.lender.key(),
+        forgiven_amount,
+        released_at: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct LienCreated {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub lender: Pubkey,
+    pub amount: u64,
+    pub created_at: i64,
+}
+
+#[event]
+pub struct LienRepaid {
+    pub stream: Pubkey,
+    pub lender: Pubkey,
+    pub amount: u64,
+    pub outstanding: u64,
+    pub cleared: bool,
+}
+
+#[event]
+pub struct LienReleased {
+    pub stream: Pubkey,
+    pub lender: Pubkey,
+    pub forgiven_amount: u64,
+    pub released_at: i64,
+}
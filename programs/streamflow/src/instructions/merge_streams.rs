@@ -0,0 +1,309 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Transfer};
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+/// Merges the streams passed in `remaining_accounts` into `stream`.
+///
+/// Remaining accounts are `[stream, escrow]` pairs for every stream being merged
+/// away. Vested amounts of all streams are paid to the recipient first, then the
+/// unvested balances are moved into the target escrow and streamed linearly from
+/// now until the chosen end time.
+#[derive(Accounts)]
+pub struct MergeStreams<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"stream",
+            stream.load()?.creator.as_ref(),
+            stream.load()?.stream_id.as_ref(),
+        ],
+        bump = stream.load()?.bump,
+        has_one = sender @ StreamFlowError::UnauthorizedAccess,
+        has_one = recipient @ StreamFlowError::UnauthorizedAccess,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() && !stream.load()?.paused.get() @ StreamFlowError::StreamNotActive,
+        constraint = !stream.load()?.withdrawals_frozen.get() @ StreamFlowError::AccountFrozen,
+        constraint = !stream.load()?.has_lien() @ StreamFlowError::StreamHasLien,
+        constraint = !stream.load()?.has_position_token() @ StreamFlowError::PositionTokenized,
+        constraint = stream.load()?.vesting_type()? != VestingType::Milestone @ StreamFlowError::StreamModificationNotAllowed,
+        constraint = !stream.load()?.has_vault_principal() @ StreamFlowError::YieldVaultNotEmpty,
+        // Pending requests were made against the schedule the merge replaces
+        constraint = !stream.load()?.has_pending_cancel() @ StreamFlowError::CancelAlreadyRequested,
+        constraint = !stream.load()?.has_pending_amendment() @ StreamFlowError::AmendmentPending,
+        constraint = !stream.load()?.has_pending_sender_transfer() @ StreamFlowError::SenderTransferPending,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow",
+            stream.key().as_ref(),
+        ],
+        bump,
+        constraint = escrow_token_account.key() == stream.load()?.escrow_tokens @ StreamFlowError::EscrowAccountNotFound,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = stream.load()?.is_withdraw_destination(
+            &recipient_token_account.key(),
+            &recipient_token_account.owner,
+            None,
+        ) @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub recipient: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MergeStreams<'info>>,
+    end_time: i64,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let remaining_accounts = ctx.remaining_accounts;
+
+    require!(
+        !remaining_accounts.is_empty()
+            && remaining_accounts.len() % 2 == 0
+            && remaining_accounts.len() / 2 <= constants::MAX_MERGE_STREAMS,
+        StreamFlowError::InvalidBatchSize
+    );
+
+    require!(
+        !ctx.accounts.stream.load()?.acceleration.is_accelerated(),
+        StreamFlowError::StreamModificationNotAllowed
+    );
+
+    let target_key = ctx.accounts.stream.key();
+    let mut merged_streams: Vec<Pubkey> = Vec::with_capacity(remaining_accounts.len() / 2);
+    let mut paid_amount: u64 = 0;
+    let mut unvested_amount: u64 = 0;
+
+    for pair in remaining_accounts.chunks(2) {
+        let source_account = AccountLoader::<Stream>::try_from(&pair[0])?;
+        let source_escrow = Account::<TokenAccount>::try_from(&pair[1])?;
+        let source_key = source_account.key();
+        let source = source_account.load()?;
+
+        require!(
+            source_key != target_key && !merged_streams.contains(&source_key),
+            StreamFlowError::InvalidStreamConfig
+        );
+        require!(
+            ctx.accounts.stream.load()?.is_mergeable_with(&source),
+            StreamFlowError::StreamModificationNotAllowed
+        );
+        require!(
+            source_escrow.key() == source.escrow_tokens,
+            StreamFlowError::EscrowAccountNotFound
+        );
+        require!(
+            !source.cancelled.get() && !source.closed.get() && !source.paused.get(),
+            StreamFlowError::StreamNotActive
+        );
+        require!(!source.withdrawals_frozen.get(), StreamFlowError::AccountFrozen);
+        require!(
+            !source.acceleration.is_accelerated(),
+            StreamFlowError::StreamModificationNotAllowed
+        );
+        require!(!source.has_lien(), StreamFlowError::StreamHasLien);
+        // Closing the source would silently drop its pending requests
+        require!(
+            !source.has_pending_cancel(),
+            StreamFlowError::CancelAlreadyRequested
+        );
+        require!(
+            !source.has_pending_amendment(),
+            StreamFlowError::AmendmentPending
+        );
+        require!(
+            !source.has_pending_sender_transfer(),
+            StreamFlowError::SenderTransferPending
+        );
+        require!(
+            !source.has_position_token(),
+            StreamFlowError::PositionTokenized
+        );
+        // Vested amounts of the source are paid out under its own destination rules
+        require!(
+            source.is_withdraw_destination(
+                &ctx.accounts.recipient_token_account.key(),
+                &ctx.accounts.recipient_token_account.owner,
+                None,
+            ),
+            StreamFlowError::InvalidTokenAccountOwner
+        );
+        require!(
+            !source.has_vault_principal(),
+            StreamFlowError::YieldVaultNotEmpty
+        );
+        // Merging would restart unapproved tranches on a time schedule
+        require!(
+            source.vesting_type()? != VestingType::Milestone,
+            StreamFlowError::StreamModificationNotAllowed
+        );
+
+        let vested = source.withdrawable_amount(current_time)?;
+        let unvested = source.remaining_balance() - vested;
+
+        // The source signs the transfers, so it must not stay borrowed
+        let seeds = StreamSeeds::of(&source);
+        drop(source);
+        let signer_seeds = &[&seeds.signer()[..]];
+
+        if vested > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: source_escrow.to_account_info(),
+                        to: ctx.accounts.recipient_token_account.to_account_info(),
+                        authority: source_account.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                vested,
+            )?;
+        }
+
+        if unvested > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: source_escrow.to_account_info(),
+                        to: ctx.accounts.escrow_token_account.to_account_info(),
+                        authority: source_account.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                unvested,
+            )?;
+        }
+
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: source_escrow.to_account_info(),
+                destination: ctx.accounts.sender.to_account_info(),
+                authority: source_account.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        source_account.close(ctx.accounts.sender.to_account_info())?;
+
+        paid_amount = paid_amount
+            .checked_add(vested)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?;
+        unvested_amount = unvested_amount
+            .checked_add(unvested)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?;
+        merged_streams.push(source_key);
+    }
+
+    // Pay out what the target itself has vested so far
+    let target = ctx.accounts.stream.load()?;
+    let vested = target.withdrawable_amount(current_time)?;
+    let unvested = target.remaining_balance() - vested;
+    let seeds = StreamSeeds::of(&target);
+    drop(target);
+
+    if vested > 0 {
+        let signer_seeds = &[&seeds.signer()[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: ctx.accounts.stream.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            vested,
+        )?;
+    }
+
+    paid_amount = paid_amount
+        .checked_add(vested)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    unvested_amount = unvested_amount
+        .checked_add(unvested)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+
+    // Restart the target as a linear stream over the unvested total
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    let restart_time = std::cmp::max(current_time, stream.start_time);
+    require!(end_time > restart_time, StreamFlowError::InvalidEndTime);
+    require!(unvested_amount > 0, StreamFlowError::NoFundsAvailable);
+
+    let duration = (end_time - restart_time) as u64;
+    utils::validate_duration(duration)?;
+    let rate_amount = (unvested_amount + duration - 1) / duration;
+
+    Stream::validate_params(
+        restart_time,
+        end_time,
+        restart_time,
+        unvested_amount,
+        rate_amount,
+        1,
+    )?;
+
+    stream.deposited_amount = unvested_amount;
+    stream.withdrawn_amount = 0;
+    stream.start_time = restart_time;
+    stream.end_time = end_time;
+    stream.cliff_time = restart_time;
+    stream.rate_amount = rate_amount;
+    stream.rate_interval_in_seconds = 1;
+    stream.total_paused_time = 0;
+    stream.last_withdrawn_at = current_time;
+    stream.vesting_type = VestingType::Linear as u8;
+    stream.vesting_segment_count = 0;
+    stream.vesting_months = 0;
+
+    emit!(StreamsMerged {
+        stream: ctx.accounts.stream.key(),
+        sender: stream.sender,
+        recipient: stream.recipient,
+        merged_streams,
+        paid_amount,
+        deposited_amount: unvested_amount,
+        end_time,
+        merged_at: current_time,
+    });
+
+    msg!(
+        "Streams merged: {} tokens paid out, {} tokens streaming until {}",
+        paid_amount,
+        unvested_amount,
+        end_time
+    );
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamsMerged {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub merged_streams: Vec<Pubkey>,
+    pub paid_amount: u64,
+    pub deposited_amount: u64,
+    pub end_time: i64,
+    pub merged_at: i64,
+}
@@ -0,0 +1,189 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{create_metadata_accounts_v3, CreateMetadataAccountsV3, Metadata};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::token::{self, Mint, MintTo, SetAuthority, Token, TokenAccount};
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+/// Symbol used for every stream position token
+pub const POSITION_TOKEN_SYMBOL: &str = "STRM";
+
+/// Maximum name length accepted by token metadata
+const MAX_METADATA_NAME_LENGTH: usize = 32;
+
+/// Tokenizes the recipient position of a freshly created stream.
+///
+/// Sent by the sender alongside stream creation. Minting is its own instruction
+/// rather than an option of the create instructions, so creating a stream does not
+/// take the mint, metadata and holder accounts; it is rejected once anything has
+/// been withdrawn, so the token always covers the whole position. Whoever holds the
+/// minted token is allowed to withdraw and exercises the recipient's cancel rights,
+/// so the position moves with ordinary token transfers and `transfer_stream` is
+/// disabled for the stream.
+#[derive(Accounts)]
+pub struct MintPositionToken<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"stream",
+            stream.load()?.creator.as_ref(),
+            stream.load()?.stream_id.as_ref(),
+        ],
+        bump = stream.load()?.bump,
+        has_one = sender @ StreamFlowError::UnauthorizedAccess,
+        has_one = recipient @ StreamFlowError::InvalidRecipient,
+        constraint = !stream.load()?.has_position_token() @ StreamFlowError::PositionTokenized,
+        constraint = stream.load()?.withdrawn_amount == 0 @ StreamFlowError::StreamModificationNotAllowed,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = !stream.load()?.has_lien() @ StreamFlowError::StreamHasLien,
+        // Payouts follow the token once minted, not a delegate of the original recipient
+        constraint = stream.load()?.withdraw_authority == Pubkey::default()
+            && stream.load()?.withdraw_destination == Pubkey::default() @ StreamFlowError::StreamModificationNotAllowed,
+        // A position token could be sold to a recipient the registry never approved
+        constraint = !stream.load()?.has_compliance_registry() @ StreamFlowError::ComplianceCheckFailed,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        init,
+        payer = sender,
+        mint::decimals = 0,
+        mint::authority = stream,
+        seeds = [
+            b"position",
+            stream.key().as_ref(),
+        ],
+        bump
+    )]
+    pub position_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = sender,
+        associated_token::mint = position_mint,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_position_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Initialized by the token metadata program
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            position_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// CHECK: Validated against the stream recipient
+    pub recipient: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<MintPositionToken>) -> Result<()> {
+    let stream = ctx.accounts.stream.load()?;
+    let mut name = String::from_utf8_lossy(&stream.name)
+        .trim_end_matches('\0')
+        .to_string();
+    if name.is_empty() {
+        name = format!("Stream {}", ctx.accounts.stream.key());
+    }
+    while name.len() > MAX_METADATA_NAME_LENGTH {
+        name.pop();
+    }
+
+    // The stream signs the CPIs below, so it must not stay borrowed
+    let seeds = StreamSeeds::of(&stream);
+    drop(stream);
+    let signer_seeds = &[&seeds.signer()[..]];
+
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.position_mint.to_account_info(),
+                to: ctx.accounts.recipient_position_account.to_account_info(),
+                authority: ctx.accounts.stream.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )?;
+
+    create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_metadata_program.to_account_info(),
+            CreateMetadataAccountsV3 {
+                metadata: ctx.accounts.metadata.to_account_info(),
+                mint: ctx.accounts.position_mint.to_account_info(),
+                mint_authority: ctx.accounts.stream.to_account_info(),
+                payer: ctx.accounts.sender.to_account_info(),
+                update_authority: ctx.accounts.stream.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        DataV2 {
+            name: name.clone(),
+            symbol: POSITION_TOKEN_SYMBOL.to_string(),
+            uri: String::new(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        },
+        false,
+        true,
+        None,
+    )?;
+
+    // Fix the supply at one so the token uniquely identifies the position
+    token::set_authority(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: ctx.accounts.stream.to_account_info(),
+                account_or_mint: ctx.accounts.position_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+
+    let position_mint = ctx.accounts.position_mint.key();
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    stream.position_mint = position_mint;
+
+    emit!(PositionTokenMinted {
+        stream: ctx.accounts.stream.key(),
+        position_mint,
+        recipient: stream.recipient,
+        name,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PositionTokenMinted {
+    pub stream: Pubkey,
+    pub position_mint: Pubkey,
+    pub recipient: Pubkey,
+    pub name: String,
+}
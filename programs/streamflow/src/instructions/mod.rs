@@ -0,0 +1,41 @@
+pub mod amend_stream;
+pub mod approve_milestone;
+pub mod compliance;
+pub mod create_sender_profile;
+pub mod create_vesting_stream;
+pub mod escrow_yield;
+pub mod fire_acceleration_trigger;
+pub mod lien;
+pub mod merge_streams;
+pub mod mint_position_token;
+pub mod request_cancel;
+pub mod sender_transfer;
+pub mod set_withdraw_delegate;
+pub mod split_stream;
+pub mod terminate_vesting;
+pub mod topup_stream;
+pub mod treasury_audit_log;
+pub mod treasury_funds;
+pub mod treasury_management;
+pub mod view_stream;
+
+pub use amend_stream::*;
+pub use approve_milestone::*;
+pub use compliance::*;
+pub use create_sender_profile::*;
+pub use create_vesting_stream::*;
+pub use escrow_yield::*;
+pub use fire_acceleration_trigger::*;
+pub use lien::*;
+pub use merge_streams::*;
+pub use mint_position_token::*;
+pub use request_cancel::*;
+pub use sender_transfer::*;
+pub use set_withdraw_delegate::*;
+pub use split_stream::*;
+pub use terminate_vesting::*;
+pub use topup_stream::*;
+pub use treasury_audit_log::*;
+pub use treasury_funds::*;
+pub use treasury_management::*;
+pub use view_stream::*;
@@ -0,0 +1,230 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+use crate::StreamCanceled;
+
+#[derive(Accounts)]
+pub struct RequestCancel<'info> {
+    #[account(
+        mut,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = !stream.load()?.has_lien() @ StreamFlowError::StreamHasLien,
+        constraint = !stream.load()?.has_pending_cancel() @ StreamFlowError::CancelAlreadyRequested,
+        constraint = stream.load()?.can_cancel(
+            &authority.key(),
+            position_token_account.as_deref(),
+        ) @ StreamFlowError::UnauthorizedAccess,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub authority: Signer<'info>,
+
+    /// Position token account of the holder when the stream is tokenized
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+}
+
+/// Settles a stream once its requested cancellation has taken effect. Anyone can
+/// call it, the accounts paid are fixed by the stream.
+#[derive(Accounts)]
+pub struct FinalizeCancel<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"stream",
+            stream.load()?.creator.as_ref(),
+            stream.load()?.stream_id.as_ref(),
+        ],
+        bump = stream.load()?.bump,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = !stream.load()?.has_lien() @ StreamFlowError::StreamHasLien,
+        constraint = stream.load()?.has_pending_cancel() @ StreamFlowError::CancelNotRequested,
+        constraint = !stream.load()?.withdrawals_frozen.get() @ StreamFlowError::AccountFrozen,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = sender_token_account.owner == stream.load()?.sender @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = stream.load()?.is_withdraw_destination(
+            &recipient_token_account.key(),
+            &recipient_token_account.owner,
+            position_token_account.as_deref(),
+        ) @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    /// Position token account of the holder when the stream is tokenized
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow",
+            stream.key().as_ref(),
+        ],
+        bump,
+        constraint = escrow_token_account.key() == stream.load()?.escrow_tokens @ StreamFlowError::EscrowAccountNotFound,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    /// Yield vault program, required once escrow yield is enabled
+    /// CHECK: checked against the stream when loading the adapter
+    pub yield_vault_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub yield_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub yield_vault_token_account: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FinalizeCancel<'info> {
+    pub fn transfer_to_recipient_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.escrow_token_account.to_account_info(),
+            to: self.recipient_token_account.to_account_info(),
+            authority: self.stream.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+
+    pub fn transfer_to_sender_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.escrow_token_account.to_account_info(),
+            to: self.sender_token_account.to_account_info(),
+            authority: self.stream.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
+
+/// Schedule cancellation at `effective_at`, which must respect the notice period
+/// fixed at creation. The stream keeps unlocking and paying out until then.
+pub fn request_handler(ctx: Context<RequestCancel>, effective_at: i64) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+
+    let earliest = current_time
+        .checked_add(stream.cancel_notice_period as i64)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    require!(
+        effective_at >= earliest && effective_at > 0,
+        StreamFlowError::CancelNoticeTooShort
+    );
+
+    stream.cancel_effective_at = effective_at;
+    stream.cancel_requested_by = ctx.accounts.authority.key();
+
+    emit!(CancelRequested {
+        stream: ctx.accounts.stream.key(),
+        requested_by: stream.cancel_requested_by,
+        requested_at: current_time,
+        effective_at,
+    });
+
+    Ok(())
+}
+
+/// Pay the recipient what unlocked up to the effective time and return the rest
+/// to the sender
+pub fn finalize_handler(ctx: Context<FinalizeCancel>) -> Result<()> {
+    let stream = ctx.accounts.stream.load()?;
+    let current_time = Clock::get()?.unix_timestamp;
+    let effective_at = stream.cancel_effective_at;
+
+    require!(current_time >= effective_at, StreamFlowError::CancelNotDue);
+
+    // `streamed_amount` already stops at the effective time
+    let unlocked = stream.withdrawable_amount(current_time)?;
+    let mut to_recipient = unlocked;
+    let mut to_sender = stream
+        .remaining_balance()
+        .checked_sub(to_recipient)
+        .ok_or(StreamFlowError::ArithmeticUnderflow)?;
+
+    let yield_enabled = stream.escrow_yield.is_enabled();
+    let seeds = StreamSeeds::of(&stream);
+    drop(stream);
+
+    // Bring the whole yield position back before settling
+    if yield_enabled {
+        let vault = load_yield_vault(
+            &ctx.accounts.stream,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.yield_vault_program.as_ref(),
+            ctx.accounts.yield_vault.as_ref(),
+            ctx.accounts.yield_vault_token_account.as_ref(),
+        )?;
+        let escrow = ctx.accounts.escrow_token_account.to_account_info();
+        (to_recipient, to_sender) = settle_escrow_yield(
+            &ctx.accounts.stream,
+            vault.as_ref(),
+            &escrow,
+            to_recipient,
+            to_sender,
+        )?;
+    }
+
+    let signer_seeds = &[&seeds.signer()[..]];
+
+    if to_recipient > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_to_recipient_ctx()
+                .with_signer(signer_seeds),
+            to_recipient,
+        )?;
+    }
+
+    if to_sender > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_to_sender_ctx()
+                .with_signer(signer_seeds),
+            to_sender,
+        )?;
+    }
+
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    stream.withdrawn_amount = stream
+        .withdrawn_amount
+        .checked_add(unlocked)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    stream.last_withdrawn_at = current_time;
+    stream.cancelled = true.into();
+    stream.cancelled_at = effective_at;
+    stream.cancelled_by = stream.cancel_requested_by;
+
+    emit!(StreamCanceled {
+        stream: ctx.accounts.stream.key(),
+        sender: stream.sender,
+        recipient: stream.recipient,
+        canceled_at: effective_at,
+        withdrawn_amount: stream.withdrawn_amount,
+        returned_amount: to_sender,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct CancelRequested {
+    pub stream: Pubkey,
+    pub requested_by: Pubkey,
+    pub requested_at: i64,
+    pub effective_at: i64,
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ProposeSenderTransfer<'info> {
+    #[account(
+        mut,
+        has_one = sender @ StreamFlowError::UnauthorizedAccess,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub sender: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptSenderTransfer<'info> {
+    #[account(
+        mut,
+        constraint = stream.load()?.has_pending_sender_transfer() @ StreamFlowError::ProposalNotFound,
+        constraint = stream.load()?.pending_sender == new_sender.key() @ StreamFlowError::UnauthorizedAccess,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub new_sender: Signer<'info>,
+}
+
+/// Nominate `new_sender` to take over the sender role. Proposing the default
+/// pubkey withdraws a pending proposal.
+pub fn propose_handler(ctx: Context<ProposeSenderTransfer>, new_sender: Pubkey) -> Result<()> {
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+
+    require!(
+        new_sender != stream.sender && new_sender != stream.recipient,
+        StreamFlowError::InvalidPublicKey
+    );
+
+    stream.pending_sender = new_sender;
+
+    emit!(SenderTransferProposed {
+        stream: ctx.accounts.stream.key(),
+        sender: stream.sender,
+        pending_sender: new_sender,
+        proposed_at: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Complete a pending transfer, moving cancel rights and refunds to the new sender
+pub fn accept_handler(ctx: Context<AcceptSenderTransfer>) -> Result<()> {
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    let previous_sender = stream.sender;
+
+    stream.sender = stream.pending_sender;
+    stream.pending_sender = Pubkey::default();
+
+    emit!(SenderTransferred {
+        stream: ctx.accounts.stream.key(),
+        previous_sender,
+        new_sender: stream.sender,
+        transferred_at: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Stream sender transferred from {} to {}",
+        previous_sender,
+        stream.sender
+    );
+
+    Ok(())
+}
+
+#[event]
+pub struct SenderTransferProposed {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub pending_sender: Pubkey,
+    pub proposed_at: i64,
+}
+
+#[event]
+pub struct SenderTransferred {
+    pub stream: Pubkey,
+    pub previous_sender: Pubkey,
+    pub new_sender: Pubkey,
+    pub transferred_at: i64,
+}
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+/// Lets the recipient name a withdraw authority and a fixed destination account.
+///
+/// Tokenized streams are excluded because the delegation would silently survive a
+/// transfer of the position token. On streams bound to a compliance registry both
+/// the authority and the owner of the destination must be approved.
+#[derive(Accounts)]
+pub struct SetWithdrawDelegate<'info> {
+    #[account(
+        mut,
+        has_one = recipient @ StreamFlowError::OnlyRecipientCanWithdraw,
+        constraint = !stream.load()?.has_position_token() @ StreamFlowError::PositionTokenized,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    pub recipient: Signer<'info>,
+
+    /// Required when setting a destination
+    #[account(
+        constraint = destination_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub destination_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Required when the stream is bound to a compliance registry
+    pub compliance_registry: Option<Box<Account<'info, ComplianceRegistry>>>,
+}
+
+pub fn handler(
+    ctx: Context<SetWithdrawDelegate>,
+    withdraw_authority: Option<Pubkey>,
+    destination: Option<Pubkey>,
+    authority_proof: Vec<[u8; 32]>,
+    destination_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let compliance_registry = ctx.accounts.stream.load()?.compliance_registry;
+    if let Some(withdraw_authority) = withdraw_authority {
+        require_compliant(
+            &compliance_registry,
+            ctx.accounts.compliance_registry.as_deref(),
+            &withdraw_authority,
+            &authority_proof,
+        )?;
+    }
+
+    if let Some(destination) = destination {
+        let destination_token_account = ctx
+            .accounts
+            .destination_token_account
+            .as_ref()
+            .ok_or(StreamFlowError::TokenAccountNotFound)?;
+        require!(
+            destination_token_account.key() == destination,
+            StreamFlowError::TokenAccountNotFound
+        );
+        require_compliant(
+            &compliance_registry,
+            ctx.accounts.compliance_registry.as_deref(),
+            &destination_token_account.owner,
+            &destination_proof,
+        )?;
+    }
+
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    stream.withdraw_authority = withdraw_authority.unwrap_or_default();
+    stream.withdraw_destination = destination.unwrap_or_default();
+
+    emit!(WithdrawDelegateSet {
+        stream: ctx.accounts.stream.key(),
+        recipient: stream.recipient,
+        withdraw_authority: stream.withdraw_authority,
+        destination: stream.withdraw_destination,
+        updated_at: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct WithdrawDelegateSet {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub withdraw_authority: Pubkey,
+    pub destination: Pubkey,
+    pub updated_at: i64,
+}
+
+#[event]
+pub struct DelegatedWithdrawal {
+    pub stream: Pubkey,
+    pub withdraw_authority: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
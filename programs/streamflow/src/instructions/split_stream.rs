@@ -0,0 +1,194 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SplitStream<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"stream",
+            stream.load()?.creator.as_ref(),
+            stream.load()?.stream_id.as_ref(),
+        ],
+        bump = stream.load()?.bump,
+        has_one = recipient @ StreamFlowError::UnauthorizedAccess,
+        has_one = mint @ StreamFlowError::InvalidTokenMint,
+        // Splitting hands part of the position to a new recipient
+        constraint = stream.load()?.transferable_by_recipient.get() @ StreamError::UnauthorizedTransfer,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = !stream.load()?.has_lien() @ StreamFlowError::StreamHasLien,
+        // The position token can only stand for one of the two streams
+        constraint = !stream.load()?.has_position_token() @ StreamFlowError::PositionTokenized,
+        constraint = !stream.load()?.withdrawals_frozen.get() @ StreamFlowError::AccountFrozen,
+        constraint = stream.load()?.vesting_type()? != VestingType::Milestone @ StreamFlowError::StreamModificationNotAllowed,
+        constraint = !stream.load()?.has_vault_principal() @ StreamFlowError::YieldVaultNotEmpty,
+        // Pending requests are addressed to the current recipient and schedule
+        constraint = !stream.load()?.has_pending_cancel() @ StreamFlowError::CancelAlreadyRequested,
+        constraint = !stream.load()?.has_pending_amendment() @ StreamFlowError::AmendmentPending,
+        constraint = !stream.load()?.has_pending_sender_transfer() @ StreamFlowError::SenderTransferPending,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        init,
+        payer = recipient,
+        space = Stream::LEN,
+        seeds = [
+            b"stream",
+            stream.load()?.creator.as_ref(),
+            split_stream_id(&stream.key(), stream.load()?.split_count).as_ref(),
+        ],
+        bump
+    )]
+    pub new_stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow",
+            stream.key().as_ref(),
+        ],
+        bump,
+        constraint = escrow_token_account.key() == stream.load()?.escrow_tokens @ StreamFlowError::EscrowAccountNotFound,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = recipient,
+        token::mint = mint,
+        token::authority = new_stream,
+        seeds = [
+            b"escrow",
+            new_stream.key().as_ref(),
+        ],
+        bump
+    )]
+    pub new_escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// Required when the stream is bound to a compliance registry
+    pub compliance_registry: Option<Box<Account<'info, ComplianceRegistry>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SplitStream<'info> {
+    pub fn transfer_to_new_escrow_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.escrow_token_account.to_account_info(),
+            to: self.new_escrow_token_account.to_account_info(),
+            authority: self.stream.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
+
+pub fn handler(
+    ctx: Context<SplitStream>,
+    new_recipient: Pubkey,
+    split_amount: SplitAmount,
+    compliance_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        new_recipient != ctx.accounts.stream.load()?.sender,
+        StreamFlowError::InvalidRecipient
+    );
+    require_compliant(
+        &ctx.accounts.stream.load()?.compliance_registry,
+        ctx.accounts.compliance_registry.as_deref(),
+        &new_recipient,
+        &compliance_proof,
+    )?;
+    let new_stream_id = split_stream_id(
+        &ctx.accounts.stream.key(),
+        ctx.accounts.stream.load()?.split_count,
+    );
+    // Nonce ids are reserved for streams the creator makes through its profile
+    require!(
+        is_custom_stream_id(&new_stream_id),
+        StreamFlowError::InvalidNonce
+    );
+
+    let (numerator, denominator) = match split_amount {
+        SplitAmount::Percentage(bps) => (bps as u64, 10_000),
+        SplitAmount::Amount(amount) => (amount, ctx.accounts.stream.load()?.remaining_balance()),
+    };
+
+    let mut stream = ctx.accounts.stream.load_mut()?;
+    let mut new_stream = ctx.accounts.new_stream.load_init()?;
+    let moved_amount = stream.split_into(&mut new_stream, new_recipient, numerator, denominator)?;
+    stream.split_count = stream
+        .split_count
+        .checked_add(1)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    new_stream.stream_id = new_stream_id;
+    new_stream.escrow_tokens = ctx.accounts.new_escrow_token_account.key();
+    new_stream.bump = ctx.bumps.new_stream;
+
+    let remaining_balance = stream.remaining_balance();
+    let seeds = StreamSeeds::of(&stream);
+    drop(stream);
+    let signer_seeds = &[&seeds.signer()[..]];
+
+    token::transfer(
+        ctx.accounts
+            .transfer_to_new_escrow_ctx()
+            .with_signer(signer_seeds),
+        moved_amount,
+    )?;
+
+    // The new escrow must hold exactly what the new stream owes, and the old escrow
+    // must still cover the old stream
+    ctx.accounts.escrow_token_account.reload()?;
+    ctx.accounts.new_escrow_token_account.reload()?;
+    require!(
+        ctx.accounts.new_escrow_token_account.amount == new_stream.remaining_balance()
+            && ctx.accounts.escrow_token_account.amount >= remaining_balance,
+        StreamFlowError::InvalidProgramState
+    );
+
+    emit!(StreamSplit {
+        stream: ctx.accounts.stream.key(),
+        new_stream: ctx.accounts.new_stream.key(),
+        sender: new_stream.sender,
+        recipient: ctx.accounts.recipient.key(),
+        new_recipient,
+        moved_amount,
+        new_deposited_amount: new_stream.deposited_amount,
+        new_withdrawn_amount: new_stream.withdrawn_amount,
+        split_at: current_time,
+    });
+
+    msg!(
+        "Stream split: {} tokens moved to new stream for {}",
+        moved_amount,
+        new_recipient
+    );
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamSplit {
+    pub stream: Pubkey,
+    pub new_stream: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub new_recipient: Pubkey,
+    pub moved_amount: u64,
+    pub new_deposited_amount: u64,
+    pub new_withdrawn_amount: u64,
+    pub split_at: i64,
+}
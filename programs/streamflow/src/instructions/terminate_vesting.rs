@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::error::StreamFlowError;
+use crate::state::vesting::TerminationKind;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct TerminateVesting<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"stream",
+            stream.load()?.creator.as_ref(),
+            stream.load()?.stream_id.as_ref(),
+        ],
+        bump = stream.load()?.bump,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+        constraint = !stream.load()?.withdrawals_frozen.get() @ StreamFlowError::AccountFrozen,
+        constraint = !stream.load()?.has_lien() @ StreamFlowError::StreamHasLien,
+        constraint = !stream.load()?.has_vault_principal() @ StreamFlowError::YieldVaultNotEmpty,
+        constraint = stream.load()?.sender == sender.key() @ StreamFlowError::OnlyCreatorCanCancel,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = sender_token_account.owner == sender.key() @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = stream.load()?.is_withdraw_destination(
+            &recipient_token_account.key(),
+            &recipient_token_account.owner,
+            position_token_account.as_deref(),
+        ) @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+
+    /// Position token account of the holder when the stream is tokenized
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow",
+            stream.key().as_ref(),
+        ],
+        bump,
+        constraint = escrow_token_account.key() == stream.load()?.escrow_tokens @ StreamFlowError::EscrowAccountNotFound,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> TerminateVesting<'info> {
+    pub fn transfer_to_recipient_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.escrow_token_account.to_account_info(),
+            to: self.recipient_token_account.to_account_info(),
+            authority: self.stream.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+
+    pub fn transfer_to_sender_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.escrow_token_account.to_account_info(),
+            to: self.sender_token_account.to_account_info(),
+            authority: self.stream.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
+
+pub fn handler(ctx: Context<TerminateVesting>, termination: TerminationKind) -> Result<()> {
+    let stream = ctx.accounts.stream.load()?;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        stream.leaver_policy.enabled.get(),
+        StreamFlowError::LeaverPolicyNotSet
+    );
+
+    // Split the remaining escrow between the recipient and the sender
+    let (adjustment, to_recipient, to_sender) =
+        stream.termination_payout(termination, current_time)?;
+
+    // The stream signs the transfers, so it must not stay borrowed
+    let seeds = StreamSeeds::of(&stream);
+    drop(stream);
+    let signer_seeds = &[&seeds.signer()[..]];
+
+    if to_recipient > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_to_recipient_ctx()
+                .with_signer(signer_seeds),
+            to_recipient,
+        )?;
+    }
+
+    if to_sender > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_to_sender_ctx()
+                .with_signer(signer_seeds),
+            to_sender,
+        )?;
+    }
+
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+    stream.withdrawn_amount = stream
+        .withdrawn_amount
+        .checked_add(to_recipient)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    stream.last_withdrawn_at = current_time;
+    stream.cancelled = true.into();
+    stream.cancelled_at = current_time;
+    stream.cancelled_by = ctx.accounts.sender.key();
+
+    match termination {
+        TerminationKind::GoodLeaver => emit!(GoodLeaverTerminated {
+            stream: ctx.accounts.stream.key(),
+            sender: stream.sender,
+            recipient: stream.recipient,
+            accelerated_amount: adjustment,
+            paid_to_recipient: to_recipient,
+            returned_to_sender: to_sender,
+            terminated_at: current_time,
+        }),
+        TerminationKind::BadLeaver => emit!(BadLeaverTerminated {
+            stream: ctx.accounts.stream.key(),
+            sender: stream.sender,
+            recipient: stream.recipient,
+            clawed_back_amount: adjustment,
+            paid_to_recipient: to_recipient,
+            returned_to_sender: to_sender,
+            terminated_at: current_time,
+        }),
+    }
+
+    msg!(
+        "Vesting terminated - Paid: {}, Returned: {}",
+        to_recipient,
+        to_sender
+    );
+
+    Ok(())
+}
+
+#[event]
+pub struct GoodLeaverTerminated {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub accelerated_amount: u64,
+    pub paid_to_recipient: u64,
+    pub returned_to_sender: u64,
+    pub terminated_at: i64,
+}
+
+#[event]
+pub struct BadLeaverTerminated {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub clawed_back_amount: u64,
+    pub paid_to_recipient: u64,
+    pub returned_to_sender: u64,
+    pub terminated_at: i64,
+}
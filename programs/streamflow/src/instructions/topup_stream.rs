@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+/// Adds funds to a linear stream. The rate is kept and the end time moves out
+/// until the new deposit is fully streamed.
+#[derive(Accounts)]
+pub struct TopupStream<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"stream",
+            stream.load()?.creator.as_ref(),
+            stream.load()?.stream_id.as_ref(),
+        ],
+        bump = stream.load()?.bump,
+        has_one = sender @ StreamFlowError::UnauthorizedAccess,
+        constraint = !stream.load()?.cancelled.get() && !stream.load()?.closed.get() @ StreamFlowError::StreamNotActive,
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"escrow",
+            stream.key().as_ref(),
+        ],
+        bump,
+        constraint = escrow_token_account.key() == stream.load()?.escrow_tokens @ StreamFlowError::EscrowAccountNotFound,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == stream.load()?.mint @ StreamFlowError::InvalidTokenMint,
+        constraint = sender_token_account.owner == sender.key() @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub sender_token_account: Account<'info, TokenAccount>,
+
+    pub sender: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> TopupStream<'info> {
+    pub fn transfer_to_escrow_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.sender_token_account.to_account_info(),
+            to: self.escrow_token_account.to_account_info(),
+            authority: self.sender.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
+
+pub fn handler(ctx: Context<TopupStream>, amount: u64) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let stream = &mut *ctx.accounts.stream.load_mut()?;
+
+    require!(amount > 0, StreamFlowError::InvalidAmount);
+    // Curved and calendar schedules fix the deposit up front
+    require!(
+        matches!(stream.vesting_type()?, VestingType::Linear | VestingType::Cliff),
+        StreamFlowError::StreamModificationNotAllowed
+    );
+    require!(
+        !stream.acceleration.is_accelerated(),
+        StreamFlowError::StreamModificationNotAllowed
+    );
+    require!(stream.end_time > current_time, StreamFlowError::StreamNotActive);
+
+    let deposited_amount = stream
+        .deposited_amount
+        .checked_add(amount)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+
+    // Stretch the schedule so the whole deposit streams at the current rate
+    require!(
+        stream.rate_amount > 0,
+        StreamFlowError::StreamRateCalculationFailed
+    );
+    let intervals = deposited_amount
+        .checked_add(stream.rate_amount - 1)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?
+        / stream.rate_amount;
+    let streaming_time = intervals
+        .checked_mul(stream.rate_interval_in_seconds)
+        .and_then(|time| i64::try_from(time).ok())
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    let effective_start_time = std::cmp::max(stream.start_time, stream.cliff_time);
+    let end_time = std::cmp::max(
+        stream.end_time,
+        effective_start_time
+            .checked_add(streaming_time)
+            .and_then(|time| time.checked_add(stream.total_paused_time))
+            .ok_or(StreamFlowError::ArithmeticOverflow)?,
+    );
+    let duration = end_time
+        .checked_sub(stream.start_time)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    utils::validate_duration(duration as u64)?;
+
+    stream.deposited_amount = deposited_amount;
+    stream.end_time = end_time;
+
+    token::transfer(ctx.accounts.transfer_to_escrow_ctx(), amount)?;
+
+    emit!(StreamToppedUp {
+        stream: ctx.accounts.stream.key(),
+        sender: stream.sender,
+        amount,
+        deposited_amount,
+        end_time,
+        topped_up_at: current_time,
+    });
+
+    msg!(
+        "Stream topped up with {} tokens, now ending at {}",
+        amount,
+        end_time
+    );
+
+    Ok(())
+}
+
+#[event]
+pub struct StreamToppedUp {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub amount: u64,
+    pub deposited_amount: u64,
+    pub end_time: i64,
+    pub topped_up_at: i64,
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct InitTreasuryAuditLog<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury", treasury.authority.as_ref()],
+        bump = treasury.bump,
+        has_one = authority @ StreamFlowError::UnauthorizedAccess,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = TreasuryAuditLog::LEN,
+        seeds = [
+            b"treasury_audit_log",
+            treasury.key().as_ref(),
+        ],
+        bump
+    )]
+    pub audit_log: Box<Account<'info, TreasuryAuditLog>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Start recording operations on the treasury. The log cannot be detached
+/// afterwards, every treasury-scoped instruction must pass it.
+pub fn handler(ctx: Context<InitTreasuryAuditLog>) -> Result<()> {
+    let audit_log = &mut ctx.accounts.audit_log;
+    audit_log.treasury = ctx.accounts.treasury.key();
+    audit_log.next_sequence = 0;
+    audit_log.bump = ctx.bumps.audit_log;
+
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.audit_log = audit_log.key();
+
+    emit!(TreasuryAuditLogCreated {
+        treasury: treasury.key(),
+        audit_log: audit_log.key(),
+        created_at: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TreasuryAuditLogCreated {
+    pub treasury: Pubkey,
+    pub audit_log: Pubkey,
+    pub created_at: i64,
+}
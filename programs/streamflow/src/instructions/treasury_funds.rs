@@ -0,0 +1,380 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+use crate::StreamCreated;
+
+/// Fund the treasury. Its vault is the associated token account of the treasury
+/// for the mint, created beforehand by the depositor.
+#[derive(Accounts)]
+pub struct DepositToTreasury<'info> {
+    #[account(
+        seeds = [b"treasury", treasury.authority.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_vault: Account<'info, TokenAccount>,
+
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = depositor_token_account.mint == mint.key() @ StreamFlowError::InvalidTokenMint,
+        constraint = depositor_token_account.owner == depositor.key() @ StreamFlowError::InvalidTokenAccountOwner,
+    )]
+    pub depositor_token_account: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// Required once the treasury has an audit log
+    #[account(mut)]
+    pub audit_log: Option<Box<Account<'info, TreasuryAuditLog>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromTreasury<'info> {
+    #[account(
+        seeds = [b"treasury", treasury.authority.as_ref()],
+        bump = treasury.bump,
+        has_one = authority @ StreamFlowError::UnauthorizedAccess,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_vault: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == mint.key() @ StreamFlowError::InvalidTokenMint,
+    )]
+    pub destination_token_account: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// Required once the treasury has an audit log
+    #[account(mut)]
+    pub audit_log: Option<Box<Account<'info, TreasuryAuditLog>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Create a linear stream funded from the treasury vault.
+///
+/// The treasury authority becomes the sender, so cancellation refunds go to the
+/// authority rather than back into the vault.
+#[derive(Accounts)]
+#[instruction(stream_id: [u8; 32])]
+pub struct CreateTreasuryStream<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury", treasury.authority.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+    )]
+    pub treasury_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = manager,
+        space = Stream::LEN,
+        seeds = [
+            b"stream",
+            treasury.authority.as_ref(),
+            stream_id.as_ref(),
+        ],
+        bump
+    )]
+    pub stream: AccountLoader<'info, Stream>,
+
+    #[account(
+        init,
+        payer = manager,
+        token::mint = mint,
+        token::authority = stream,
+        seeds = [
+            b"escrow",
+            stream.key().as_ref(),
+        ],
+        bump
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// The treasury authority or one of its managers
+    #[account(mut)]
+    pub manager: Signer<'info>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    /// Required once the treasury has an audit log
+    #[account(mut)]
+    pub audit_log: Option<Box<Account<'info, TreasuryAuditLog>>>,
+
+    /// Required when the treasury is bound to a compliance registry
+    pub compliance_registry: Option<Box<Account<'info, ComplianceRegistry>>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> CreateTreasuryStream<'info> {
+    pub fn fund_escrow_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.treasury_vault.to_account_info(),
+            to: self.escrow_token_account.to_account_info(),
+            authority: self.treasury.to_account_info(),
+        };
+        CpiContext::new(self.token_program.to_account_info(), cpi_accounts)
+    }
+}
+
+pub fn deposit_handler(ctx: Context<DepositToTreasury>, amount: u64) -> Result<()> {
+    require!(amount > 0, StreamFlowError::InvalidAmount);
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.depositor_token_account.to_account_info(),
+        to: ctx.accounts.treasury_vault.to_account_info(),
+        authority: ctx.accounts.depositor.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    record_treasury_operation(
+        &ctx.accounts.treasury,
+        ctx.accounts.audit_log.as_deref_mut(),
+        TreasuryOperation::Deposit,
+        ctx.accounts.depositor.key(),
+        amount,
+        Pubkey::default(),
+    )?;
+
+    emit!(TreasuryDeposited {
+        treasury: ctx.accounts.treasury.key(),
+        depositor: ctx.accounts.depositor.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Move idle funds out of the vault. Only the authority can withdraw, managers
+/// can only commit funds to streams.
+pub fn withdraw_handler(ctx: Context<WithdrawFromTreasury>, amount: u64) -> Result<()> {
+    require!(amount > 0, StreamFlowError::InvalidAmount);
+    require!(
+        amount <= ctx.accounts.treasury_vault.amount,
+        StreamFlowError::InsufficientFunds
+    );
+
+    let treasury = &ctx.accounts.treasury;
+    let seeds = &[b"treasury", treasury.authority.as_ref(), &[treasury.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.treasury_vault.to_account_info(),
+        to: ctx.accounts.destination_token_account.to_account_info(),
+        authority: ctx.accounts.treasury.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts)
+        .with_signer(signer_seeds);
+    token::transfer(cpi_ctx, amount)?;
+
+    record_treasury_operation(
+        &ctx.accounts.treasury,
+        ctx.accounts.audit_log.as_deref_mut(),
+        TreasuryOperation::Withdraw,
+        ctx.accounts.authority.key(),
+        amount,
+        Pubkey::default(),
+    )?;
+
+    emit!(TreasuryWithdrawn {
+        treasury: ctx.accounts.treasury.key(),
+        destination: ctx.accounts.destination_token_account.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Create a stream of `deposited_amount` unlocking every `period` seconds between
+/// `start_time` and `end_time`, subject to the spending limits of the treasury.
+/// `compliance_proof` proves the recipient against a Merkle allowlist registry.
+pub fn create_stream_handler(
+    ctx: Context<CreateTreasuryStream>,
+    stream_id: [u8; 32],
+    recipient: Pubkey,
+    deposited_amount: u64,
+    start_time: i64,
+    end_time: i64,
+    cliff_time: i64,
+    period: u64,
+    cancelable_by_sender: bool,
+    cancelable_by_recipient: bool,
+    stream_name: String,
+    compliance_proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let manager = ctx.accounts.manager.key();
+    let authority = ctx.accounts.treasury.authority;
+
+    let access_level = ctx
+        .accounts
+        .treasury
+        .access_level(&manager)
+        .ok_or(StreamFlowError::UnauthorizedAccess)?;
+
+    // Treasury streams always use caller supplied ids
+    claim_stream_id(None, &stream_id)?;
+    require!(start_time >= current_time, StreamFlowError::InvalidStartTime);
+    require!(stream_name.len() <= 64, StreamFlowError::StreamMetadataTooLarge);
+    require!(
+        recipient != authority && recipient != Pubkey::default(),
+        StreamFlowError::InvalidRecipient
+    );
+    require_compliant(
+        &ctx.accounts.treasury.compliance_registry,
+        ctx.accounts.compliance_registry.as_deref(),
+        &recipient,
+        &compliance_proof,
+    )?;
+    require!(
+        deposited_amount <= ctx.accounts.treasury_vault.amount,
+        StreamFlowError::InsufficientFunds
+    );
+
+    require!(end_time > start_time, StreamFlowError::InvalidEndTime);
+    let duration = (end_time - start_time) as u64;
+    utils::validate_duration(duration)?;
+    require!(
+        period > 0 && period <= duration,
+        StreamFlowError::InvalidDuration
+    );
+    let periods = duration / period;
+    let rate_amount = deposited_amount
+        .checked_add(periods - 1)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?
+        / periods;
+    Stream::validate_params(
+        start_time,
+        end_time,
+        cliff_time,
+        deposited_amount,
+        rate_amount,
+        period,
+    )?;
+
+    let compliance_registry = ctx.accounts.treasury.compliance_registry;
+    let treasury = &mut ctx.accounts.treasury;
+    treasury
+        .spending_limits
+        .commit(deposited_amount, access_level, current_time)?;
+    treasury.total_streams += 1;
+    treasury.active_streams += 1;
+    treasury.total_deposited = treasury
+        .total_deposited
+        .checked_add(deposited_amount)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+
+    let mut name = [0u8; 64];
+    name[..stream_name.len()].copy_from_slice(stream_name.as_bytes());
+
+    let stream = &mut *ctx.accounts.stream.load_init()?;
+    stream.sender = authority;
+    stream.creator = authority;
+    stream.stream_id = stream_id;
+    stream.recipient = recipient;
+    stream.mint = ctx.accounts.mint.key();
+    stream.escrow_tokens = ctx.accounts.escrow_token_account.key();
+    stream.deposited_amount = deposited_amount;
+    stream.withdrawn_amount = 0;
+    stream.start_time = start_time;
+    stream.end_time = end_time;
+    stream.cliff_time = cliff_time;
+    stream.cancelable_by_sender = cancelable_by_sender.into();
+    stream.cancelable_by_recipient = cancelable_by_recipient.into();
+    stream.transferable_by_sender = false.into();
+    stream.transferable_by_recipient = false.into();
+    stream.automatic_withdrawal = false.into();
+    stream.rate_amount = rate_amount;
+    stream.rate_interval_in_seconds = period;
+    stream.last_withdrawn_at = start_time;
+    stream.name = name;
+    stream.vesting_type = VestingType::Linear as u8;
+    stream.compliance_registry = compliance_registry;
+    stream.bump = ctx.bumps.stream;
+
+    let treasury = &ctx.accounts.treasury;
+    let seeds = &[b"treasury", treasury.authority.as_ref(), &[treasury.bump]];
+    let signer_seeds = &[&seeds[..]];
+    token::transfer(
+        ctx.accounts.fund_escrow_ctx().with_signer(signer_seeds),
+        deposited_amount,
+    )?;
+
+    record_treasury_operation(
+        &ctx.accounts.treasury,
+        ctx.accounts.audit_log.as_deref_mut(),
+        TreasuryOperation::CreateStream,
+        manager,
+        deposited_amount,
+        ctx.accounts.stream.key(),
+    )?;
+
+    emit!(StreamCreated {
+        stream: ctx.accounts.stream.key(),
+        sender: authority,
+        recipient,
+        deposit_amount: deposited_amount,
+        start_time,
+        end_time,
+    });
+
+    msg!(
+        "Treasury stream created by {}: {} tokens over {} seconds",
+        manager,
+        deposited_amount,
+        duration
+    );
+
+    Ok(())
+}
+
+#[event]
+pub struct TreasuryDeposited {
+    pub treasury: Pubkey,
+    pub depositor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub treasury: Pubkey,
+    pub destination: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ManageTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury", treasury.authority.as_ref()],
+        bump = treasury.bump,
+        has_one = authority @ StreamFlowError::UnauthorizedAccess,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    pub authority: Signer<'info>,
+
+    /// Required once the treasury has an audit log
+    #[account(mut)]
+    pub audit_log: Option<Box<Account<'info, TreasuryAuditLog>>>,
+}
+
+/// Add `manager` with `access_level`, or change its level if already a manager
+pub fn set_manager_handler(
+    ctx: Context<ManageTreasury>,
+    manager: Pubkey,
+    access_level: AccessLevel,
+) -> Result<()> {
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.set_manager(manager, access_level)?;
+
+    record_treasury_operation(
+        treasury,
+        ctx.accounts.audit_log.as_deref_mut(),
+        TreasuryOperation::SetManager,
+        ctx.accounts.authority.key(),
+        0,
+        Pubkey::default(),
+    )?;
+
+    emit!(TreasuryManagerSet {
+        treasury: treasury.key(),
+        manager,
+        access_level: Some(access_level),
+    });
+
+    Ok(())
+}
+
+pub fn remove_manager_handler(ctx: Context<ManageTreasury>, manager: Pubkey) -> Result<()> {
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.remove_manager(&manager)?;
+
+    record_treasury_operation(
+        treasury,
+        ctx.accounts.audit_log.as_deref_mut(),
+        TreasuryOperation::SetManager,
+        ctx.accounts.authority.key(),
+        0,
+        Pubkey::default(),
+    )?;
+
+    emit!(TreasuryManagerSet {
+        treasury: treasury.key(),
+        manager,
+        access_level: None,
+    });
+
+    Ok(())
+}
+
+/// Replace the spending caps. Amounts already committed keep counting against the
+/// new caps until they leave their window.
+pub fn set_spending_limits_handler(
+    ctx: Context<ManageTreasury>,
+    daily_limit: u64,
+    monthly_limit: u64,
+    admin_max_per_stream: u64,
+    manager_max_per_stream: u64,
+) -> Result<()> {
+    let treasury = &mut ctx.accounts.treasury;
+    let limits = &mut treasury.spending_limits;
+    limits.daily_limit = daily_limit;
+    limits.monthly_limit = monthly_limit;
+    limits.admin_max_per_stream = admin_max_per_stream;
+    limits.manager_max_per_stream = manager_max_per_stream;
+
+    record_treasury_operation(
+        treasury,
+        ctx.accounts.audit_log.as_deref_mut(),
+        TreasuryOperation::SetSpendingLimits,
+        ctx.accounts.authority.key(),
+        0,
+        Pubkey::default(),
+    )?;
+
+    emit!(SpendingLimitsSet {
+        treasury: treasury.key(),
+        daily_limit,
+        monthly_limit,
+        admin_max_per_stream,
+        manager_max_per_stream,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TreasuryManagerSet {
+    pub treasury: Pubkey,
+    pub manager: Pubkey,
+    /// New access level, `None` when the manager was removed
+    pub access_level: Option<AccessLevel>,
+}
+
+#[event]
+pub struct SpendingLimitsSet {
+    pub treasury: Pubkey,
+    pub daily_limit: u64,
+    pub monthly_limit: u64,
+    pub admin_max_per_stream: u64,
+    pub manager_max_per_stream: u64,
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+/// Read-only access to a stream for the quote instructions.
+///
+/// Nothing is signed or written, so these instructions can be simulated by anyone
+/// and called through CPI by other programs. Anchor serializes the returned value
+/// with Borsh and publishes it with `set_return_data`.
+#[derive(Accounts)]
+pub struct ViewStream<'info> {
+    pub stream: AccountLoader<'info, Stream>,
+}
+
+/// Stream state together with a single computed amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct StreamQuote {
+    pub stream: StreamData,
+    pub amount: u64,
+    /// Cluster time the amount was computed at
+    pub quoted_at: i64,
+}
+
+/// Stream state together with every computed amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct StreamSummary {
+    pub stream: StreamData,
+    pub streamed_amount: u64,
+    pub withdrawable_amount: u64,
+    pub remaining_balance: u64,
+    pub progress_percentage: u8,
+    pub is_completed: bool,
+    /// Cluster time the amounts were computed at
+    pub quoted_at: i64,
+}
+
+pub fn withdrawable_handler(ctx: Context<ViewStream>) -> Result<StreamQuote> {
+    let stream = &*ctx.accounts.stream.load()?;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    Ok(StreamQuote {
+        stream: StreamData::from(stream),
+        amount: stream.withdrawable_amount(current_time)?,
+        quoted_at: current_time,
+    })
+}
+
+pub fn streamed_handler(ctx: Context<ViewStream>) -> Result<StreamQuote> {
+    let stream = &*ctx.accounts.stream.load()?;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    Ok(StreamQuote {
+        stream: StreamData::from(stream),
+        amount: stream.streamed_amount(current_time)?,
+        quoted_at: current_time,
+    })
+}
+
+pub fn summary_handler(ctx: Context<ViewStream>) -> Result<StreamSummary> {
+    let stream = &*ctx.accounts.stream.load()?;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    Ok(StreamSummary {
+        stream: StreamData::from(stream),
+        streamed_amount: stream.streamed_amount(current_time)?,
+        withdrawable_amount: stream.withdrawable_amount(current_time)?,
+        remaining_balance: stream.remaining_balance(),
+        progress_percentage: stream.progress_percentage(current_time)?,
+        is_completed: stream.is_completed(current_time),
+        quoted_at: current_time,
+    })
+}
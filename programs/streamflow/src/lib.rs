@@ -1,7 +1,22 @@
 ```rust
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use std::mem::size_of;
+
+pub mod error;
+pub mod instructions;
+pub mod state;
+
+use error::StreamFlowError;
+pub use instructions::*;
+use state::{
+    load_yield_vault, recall_escrow_principal, settle_escrow_yield, AccessLevel, ComplianceMode,
+    ComplianceRegistry, SenderProfile, SplitAmount, Stream, StreamError, StreamSeeds, StreamTerms,
+    Treasury, TreasuryAuditLog, TreasuryOperation, VestingType, YieldAdapter, MAX_TREASURY_DESCRIPTION_LENGTH,
+    MAX_TREASURY_NAME_LENGTH,
+};
+use state::vesting::{
+    AccelerationClause, AccelerationTrigger, LeaverPolicy, TerminationKind, VestingSegment,
+};
 
 declare_id!("11111111111111111111111111111112");
 
@@ -11,47 +26,71 @@ pub mod streamflow {
 
     pub fn create_stream(
         ctx: Context<CreateStream>,
+        stream_id: [u8; 32],
         recipient: Pubkey,
-        deposit_amount: u64,
+        deposited_amount: u64,
         start_time: i64,
         end_time: i64,
         cliff_time: Option<i64>,
         cancelable_by_sender: bool,
         cancelable_by_recipient: bool,
+        cancel_notice_period: u64,
         transferable_by_sender: bool,
         transferable_by_recipient: bool,
         automatic_withdrawal: bool,
-        withdrawal_frequency: u64,
+        period: u64,
     ) -> Result<()> {
         require!(start_time < end_time, StreamError::InvalidTimeRange);
-        require!(deposit_amount > 0, StreamError::InvalidAmount);
-        
-        if let Some(cliff) = cliff_time {
-            require!(cliff >= start_time && cliff <= end_time, StreamError::InvalidCliffTime);
-        }
+        require!(deposited_amount > 0, StreamError::InvalidAmount);
 
-        let stream = &mut ctx.accounts.stream;
-        let clock = Clock::get()?;
+        let duration = (end_time - start_time) as u64;
+        require!(
+            period > 0 && period <= duration,
+            StreamError::InvalidRateInterval
+        );
+
+        // Round the rate up so the whole deposit is unlocked by the end time
+        let periods = duration / period;
+        let rate_amount = deposited_amount
+            .checked_add(periods - 1)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?
+            / periods;
+        let cliff_time = cliff_time.unwrap_or(start_time);
+        Stream::validate_params(
+            start_time,
+            end_time,
+            cliff_time,
+            deposited_amount,
+            rate_amount,
+            period,
+        )?;
+
+        state::claim_stream_id(ctx.accounts.sender_profile.as_deref_mut(), &stream_id)?;
+
+        let stream = &mut *ctx.accounts.stream.load_init()?;
 
         stream.sender = ctx.accounts.sender.key();
+        stream.creator = ctx.accounts.sender.key();
+        stream.stream_id = stream_id;
         stream.recipient = recipient;
         stream.mint = ctx.accounts.mint.key();
-        stream.escrow_token_account = ctx.accounts.escrow_token_account.key();
-        stream.deposit_amount = deposit_amount;
+        stream.escrow_tokens = ctx.accounts.escrow_token_account.key();
+        stream.deposited_amount = deposited_amount;
         stream.withdrawn_amount = 0;
         stream.start_time = start_time;
         stream.end_time = end_time;
         stream.cliff_time = cliff_time;
-        stream.cancelable_by_sender = cancelable_by_sender;
-        stream.cancelable_by_recipient = cancelable_by_recipient;
-        stream.transferable_by_sender = transferable_by_sender;
-        stream.transferable_by_recipient = transferable_by_recipient;
-        stream.automatic_withdrawal = automatic_withdrawal;
-        stream.withdrawal_frequency = withdrawal_frequency;
-        stream.last_withdrawal_time = start_time;
-        stream.canceled_at = None;
-        stream.created_at = clock.unix_timestamp;
-        stream.bump = *ctx.bumps.get("stream").unwrap();
+        stream.rate_amount = rate_amount;
+        stream.rate_interval_in_seconds = period;
+        stream.cancelable_by_sender = cancelable_by_sender.into();
+        stream.cancelable_by_recipient = cancelable_by_recipient.into();
+        stream.cancel_notice_period = cancel_notice_period;
+        stream.transferable_by_sender = transferable_by_sender.into();
+        stream.transferable_by_recipient = transferable_by_recipient.into();
+        stream.automatic_withdrawal = automatic_withdrawal.into();
+        stream.last_withdrawn_at = start_time;
+        stream.vesting_type = VestingType::Linear as u8;
+        stream.bump = ctx.bumps.stream;
 
         // Transfer tokens to escrow
         let cpi_accounts = Transfer {
@@ -61,13 +100,13 @@ pub mod streamflow {
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, deposit_amount)?;
+        token::transfer(cpi_ctx, deposited_amount)?;
 
         emit!(StreamCreated {
-            stream: stream.key(),
+            stream: ctx.accounts.stream.key(),
             sender: stream.sender,
             recipient: stream.recipient,
-            deposit_amount,
+            deposit_amount: deposited_amount,
             start_time,
             end_time,
         });
@@ -76,80 +115,186 @@ pub mod streamflow {
     }
 
     pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
-        let stream = &mut ctx.accounts.stream;
+        let stream = ctx.accounts.stream.load()?;
         let clock = Clock::get()?;
         let current_time = clock.unix_timestamp;
 
-        require!(!stream.is_canceled(), StreamError::StreamCanceled);
+        require!(!stream.cancelled.get(), StreamError::StreamCanceled);
+        require!(!stream.withdrawals_frozen.get(), StreamFlowError::AccountFrozen);
+        require!(
+            stream.is_withdraw_authority(
+                &ctx.accounts.recipient.key(),
+                ctx.accounts.position_token_account.as_deref(),
+            ),
+            StreamError::UnauthorizedWithdrawal
+        );
         require!(
-            ctx.accounts.recipient.key() == stream.recipient,
+            stream.is_withdraw_destination(
+                &ctx.accounts.recipient_token_account.key(),
+                &ctx.accounts.recipient_token_account.owner,
+                ctx.accounts.position_token_account.as_deref(),
+            ),
             StreamError::UnauthorizedWithdrawal
         );
 
         let withdrawable_amount = stream.withdrawable_amount(current_time)?;
         require!(amount <= withdrawable_amount, StreamError::InsufficientFunds);
 
+        let has_vault_principal = stream.has_vault_principal();
+        drop(stream);
+
+        // Unvested funds parked in a yield vault are pulled back only as needed
+        if has_vault_principal {
+            let vault = load_yield_vault(
+                &ctx.accounts.stream,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.yield_vault_program.as_ref(),
+                ctx.accounts.yield_vault.as_ref(),
+                ctx.accounts.yield_vault_token_account.as_ref(),
+            )?;
+            recall_escrow_principal(
+                &ctx.accounts.stream,
+                vault.as_ref(),
+                &ctx.accounts.escrow_token_account.to_account_info(),
+                amount,
+            )?;
+        }
+
+        // An outstanding lien is repaid before the recipient receives anything
+        let mut stream = ctx.accounts.stream.load_mut()?;
+        let lender = stream.lien.lender;
+        let (lender_amount, recipient_amount) = stream.lien.split_withdrawal(amount);
+        let lien_cleared = stream.lien.repay(lender_amount)?;
+
         stream.withdrawn_amount += amount;
-        stream.last_withdrawal_time = current_time;
+        stream.last_withdrawn_at = current_time;
 
         // Transfer tokens from escrow to recipient
-        let seeds = &[
-            b"stream",
-            stream.sender.as_ref(),
-            stream.recipient.as_ref(),
-            stream.mint.as_ref(),
-            &[stream.bump],
-        ];
-        let signer = &[&seeds[..]];
+        let seeds = StreamSeeds::of(&stream);
+        drop(stream);
+        let signer = &[&seeds.signer()[..]];
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.escrow_token_account.to_account_info(),
-            to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: ctx.accounts.stream.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, amount)?;
+        if lender_amount > 0 {
+            let lender_token_account = ctx
+                .accounts
+                .lender_token_account
+                .as_ref()
+                .ok_or(StreamFlowError::TokenAccountNotFound)?;
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: lender_token_account.to_account_info(),
+                authority: ctx.accounts.stream.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, lender_amount)?;
+        }
+
+        if recipient_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.stream.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, recipient_amount)?;
+        }
+
+        let stream = ctx.accounts.stream.load()?;
+        if lender_amount > 0 {
+            emit!(LienRepaid {
+                stream: ctx.accounts.stream.key(),
+                lender,
+                amount: lender_amount,
+                outstanding: stream.lien.outstanding(),
+                cleared: lien_cleared,
+            });
+        }
 
         emit!(Withdrawal {
-            stream: stream.key(),
+            stream: ctx.accounts.stream.key(),
             recipient: stream.recipient,
             amount,
             withdrawn_amount: stream.withdrawn_amount,
         });
 
+        if ctx.accounts.recipient.key() == stream.withdraw_authority {
+            emit!(DelegatedWithdrawal {
+                stream: ctx.accounts.stream.key(),
+                withdraw_authority: stream.withdraw_authority,
+                destination: ctx.accounts.recipient_token_account.key(),
+                amount,
+            });
+        }
+
         Ok(())
     }
 
     pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
-        let stream = &mut ctx.accounts.stream;
+        let stream = ctx.accounts.stream.load()?;
         let clock = Clock::get()?;
         let current_time = clock.unix_timestamp;
 
-        require!(!stream.is_canceled(), StreamError::StreamAlreadyCanceled);
+        require!(!stream.cancelled.get(), StreamError::StreamAlreadyCanceled);
+        require!(!stream.has_lien(), StreamFlowError::StreamHasLien);
+        require!(!stream.withdrawals_frozen.get(), StreamFlowError::AccountFrozen);
+        require!(
+            stream.cancel_notice_period == 0,
+            StreamFlowError::CancelNoticeRequired
+        );
 
         let authority = ctx.accounts.authority.key();
-        let can_cancel = (authority == stream.sender && stream.cancelable_by_sender) ||
-                        (authority == stream.recipient && stream.cancelable_by_recipient);
-        
-        require!(can_cancel, StreamError::UnauthorizedCancellation);
+        require!(
+            stream.can_cancel(
+                &authority,
+                ctx.accounts.position_token_account.as_deref(),
+            ),
+            StreamError::UnauthorizedCancellation
+        );
+        require!(
+            stream.is_withdraw_destination(
+                &ctx.accounts.recipient_token_account.key(),
+                &ctx.accounts.recipient_token_account.owner,
+                ctx.accounts.position_token_account.as_deref(),
+            ),
+            StreamFlowError::InvalidTokenAccountOwner
+        );
 
         let withdrawable_amount = stream.withdrawable_amount(current_time)?;
-        let remaining_amount = stream.deposit_amount - stream.withdrawn_amount;
+        let remaining_amount = stream.remaining_balance();
+        let mut recipient_amount = withdrawable_amount;
+        let mut return_amount = remaining_amount - withdrawable_amount;
+
+        let yield_enabled = stream.escrow_yield.is_enabled();
+        let seeds = StreamSeeds::of(&stream);
+        drop(stream);
 
-        stream.canceled_at = Some(current_time);
+        // Bring the whole yield position back before settling
+        if yield_enabled {
+            let vault = load_yield_vault(
+                &ctx.accounts.stream,
+                ctx.accounts.escrow_token_account.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.yield_vault_program.as_ref(),
+                ctx.accounts.yield_vault.as_ref(),
+                ctx.accounts.yield_vault_token_account.as_ref(),
+            )?;
+            (recipient_amount, return_amount) = settle_escrow_yield(
+                &ctx.accounts.stream,
+                vault.as_ref(),
+                &ctx.accounts.escrow_token_account.to_account_info(),
+                recipient_amount,
+                return_amount,
+            )?;
+        }
 
-        let seeds = &[
-            b"stream",
-            stream.sender.as_ref(),
-            stream.recipient.as_ref(),
-            stream.mint.as_ref(),
-            &[stream.bump],
-        ];
-        let signer = &[&seeds[..]];
+        let signer = &[&seeds.signer()[..]];
 
         // Transfer withdrawable amount to recipient if any
-        if withdrawable_amount > 0 {
+        if recipient_amount > 0 {
             let cpi_accounts = Transfer {
                 from: ctx.accounts.escrow_token_account.to_account_info(),
                 to: ctx.accounts.recipient_token_account.to_account_info(),
@@ -157,13 +302,10 @@ pub mod streamflow {
             };
             let cpi_program = ctx.accounts.token_program.to_account_info();
             let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-            token::transfer(cpi_ctx, withdrawable_amount)?;
-
-            stream.withdrawn_amount += withdrawable_amount;
+            token::transfer(cpi_ctx, recipient_amount)?;
         }
 
         // Return remaining amount to sender
-        let return_amount = remaining_amount - withdrawable_amount;
         if return_amount > 0 {
             let cpi_accounts = Transfer {
                 from: ctx.accounts.escrow_token_account.to_account_info(),
@@ -175,8 +317,14 @@ pub mod streamflow {
             token::transfer(cpi_ctx, return_amount)?;
         }
 
+        let stream = &mut *ctx.accounts.stream.load_mut()?;
+        stream.cancelled = true.into();
+        stream.cancelled_at = current_time;
+        stream.cancelled_by = authority;
+        stream.withdrawn_amount += withdrawable_amount;
+
         emit!(StreamCanceled {
-            stream: stream.key(),
+            stream: ctx.accounts.stream.key(),
             sender: stream.sender,
             recipient: stream.recipient,
             canceled_at: current_time,
@@ -187,22 +335,41 @@ pub mod streamflow {
         Ok(())
     }
 
-    pub fn transfer_stream(ctx: Context<TransferStream>, new_recipient: Pubkey) -> Result<()> {
-        let stream = &mut ctx.accounts.stream;
+    pub fn transfer_stream(
+        ctx: Context<TransferStream>,
+        new_recipient: Pubkey,
+        compliance_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let stream = &mut *ctx.accounts.stream.load_mut()?;
         
-        require!(!stream.is_canceled(), StreamError::StreamCanceled);
+        require!(!stream.cancelled.get(), StreamError::StreamCanceled);
+        require!(
+            !stream.has_position_token(),
+            StreamFlowError::PositionTokenized
+        );
+        require!(!stream.has_lien(), StreamFlowError::StreamHasLien);
+        require!(!stream.withdrawals_frozen.get(), StreamFlowError::AccountFrozen);
+        state::require_compliant(
+            &stream.compliance_registry,
+            ctx.accounts.compliance_registry.as_deref(),
+            &new_recipient,
+            &compliance_proof,
+        )?;
         
         let authority = ctx.accounts.authority.key();
-        let can_transfer = (authority == stream.sender && stream.transferable_by_sender) ||
-                          (authority == stream.recipient && stream.transferable_by_recipient);
+        let can_transfer = (authority == stream.sender && stream.transferable_by_sender.get()) ||
+                          (authority == stream.recipient && stream.transferable_by_recipient.get());
         
         require!(can_transfer, StreamError::UnauthorizedTransfer);
 
         let old_recipient = stream.recipient;
         stream.recipient = new_recipient;
+        // A delegate of the previous recipient must not keep withdrawing
+        stream.withdraw_authority = Pubkey::default();
+        stream.withdraw_destination = Pubkey::default();
 
         emit!(StreamTransferred {
-            stream: stream.key(),
+            stream: ctx.accounts.stream.key(),
             old_recipient,
             new_recipient,
             transferred_by: authority,
@@ -216,31 +383,37 @@ pub mod streamflow {
         cancelable_by_sender: Option<bool>,
         cancelable_by_recipient: Option<bool>,
         automatic_withdrawal: Option<bool>,
-        withdrawal_frequency: Option<u64>,
     ) -> Result<()> {
-        let stream = &mut ctx.accounts.stream;
+        let stream = &mut *ctx.accounts.stream.load_mut()?;
         
-        require!(!stream.is_canceled(), StreamError::StreamCanceled);
+        require!(!stream.cancelled.get(), StreamError::StreamCanceled);
         require!(
             ctx.accounts.sender.key() == stream.sender,
             StreamError::UnauthorizedUpdate
         );
 
+        // Permissions can only move in the recipient's favour here, anything else
+        // goes through `propose_amendment`
         if let Some(cancelable_sender) = cancelable_by_sender {
-            stream.cancelable_by_sender = cancelable_sender;
+            require!(
+                !cancelable_sender || stream.cancelable_by_sender.get(),
+                StreamFlowError::StreamModificationNotAllowed
+            );
+            stream.cancelable_by_sender = cancelable_sender.into();
         }
         if let Some(cancelable_recipient) = cancelable_by_recipient {
-            stream.cancelable_by_recipient = cancelable_recipient;
+            require!(
+                cancelable_recipient || !stream.cancelable_by_recipient.get(),
+                StreamFlowError::StreamModificationNotAllowed
+            );
+            stream.cancelable_by_recipient = cancelable_recipient.into();
         }
         if let Some(auto_withdrawal) = automatic_withdrawal {
-            stream.automatic_withdrawal = auto_withdrawal;
-        }
-        if let Some(frequency) = withdrawal_frequency {
-            stream.withdrawal_frequency = frequency;
+            stream.automatic_withdrawal = auto_withdrawal.into();
         }
 
         emit!(StreamUpdated {
-            stream: stream.key(),
+            stream: ctx.accounts.stream.key(),
             sender: stream.sender,
         });
 
@@ -253,8 +426,11 @@ pub mod streamflow {
         description: String,
         auto_close: bool,
     ) -> Result<()> {
-        require!(name.len() <= 50, StreamError::NameTooLong);
-        require!(description.len() <= 200, StreamError::DescriptionTooLong);
+        require!(name.len() <= MAX_TREASURY_NAME_LENGTH, StreamError::NameTooLong);
+        require!(
+            description.len() <= MAX_TREASURY_DESCRIPTION_LENGTH,
+            StreamError::DescriptionTooLong
+        );
 
         let treasury = &mut ctx.accounts.treasury;
         let clock = Clock::get()?;
@@ -268,7 +444,7 @@ pub mod streamflow {
         treasury.total_deposited = 0;
         treasury.total_withdrawn = 0;
         treasury.created_at = clock.unix_timestamp;
-        treasury.bump = *ctx.bumps.get("treasury").unwrap();
+        treasury.bump = ctx.bumps.treasury;
 
         emit!(TreasuryCreated {
             treasury: treasury.key(),
@@ -279,9 +455,12 @@ pub mod streamflow {
         Ok(())
     }
 
-    pub fn add_stream_to_treasury(ctx: Context<AddStreamToTreasury>) -> Result<()> {
+    pub fn add_stream_to_treasury(
+        ctx: Context<AddStreamToTreasury>,
+        compliance_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
         let treasury = &mut ctx.accounts.treasury;
-        let stream = &ctx.accounts.stream;
+        let stream = &mut *ctx.accounts.stream.load_mut()?;
 
         require!(
             ctx.accounts.authority.key() == treasury.authority,
@@ -292,36 +471,409 @@ pub mod streamflow {
             StreamError::StreamNotOwnedByTreasury
         );
 
+        // Adopted streams answer to the registry of the treasury from now on
+        state::require_compliant(
+            &treasury.compliance_registry,
+            ctx.accounts.compliance_registry.as_deref(),
+            &stream.recipient,
+            &compliance_proof,
+        )?;
+        stream.compliance_registry = treasury.compliance_registry;
+
         treasury.total_streams += 1;
-        if !stream.is_canceled() {
+        if !stream.cancelled.get() {
             treasury.active_streams += 1;
         }
-        treasury.total_deposited += stream.deposit_amount;
+        treasury.total_deposited += stream.deposited_amount;
         treasury.total_withdrawn += stream.withdrawn_amount;
 
+        state::record_treasury_operation(
+            treasury,
+            ctx.accounts.audit_log.as_deref_mut(),
+            TreasuryOperation::AddStream,
+            ctx.accounts.authority.key(),
+            stream.deposited_amount,
+            ctx.accounts.stream.key(),
+        )?;
+
         emit!(StreamAddedToTreasury {
             treasury: treasury.key(),
-            stream: stream.key(),
+            stream: ctx.accounts.stream.key(),
         });
 
         Ok(())
     }
+
+    pub fn create_vesting_stream(
+        ctx: Context<CreateVestingStream>,
+        stream_id: [u8; 32],
+        recipient: Pubkey,
+        start_time: i64,
+        cliff_time: i64,
+        segments: Vec<VestingSegment>,
+        cancelable_by_sender: bool,
+        cancelable_by_recipient: bool,
+        cancel_notice_period: u64,
+        leaver_policy: LeaverPolicy,
+        acceleration: AccelerationClause,
+        stream_name: String,
+        yield_share_bps: Option<u16>,
+    ) -> Result<()> {
+        instructions::create_vesting_stream::handler(
+            ctx,
+            stream_id,
+            recipient,
+            start_time,
+            cliff_time,
+            segments,
+            cancelable_by_sender,
+            cancelable_by_recipient,
+            cancel_notice_period,
+            leaver_policy,
+            acceleration,
+            stream_name,
+            yield_share_bps,
+        )
+    }
+
+    pub fn create_monthly_vesting_stream(
+        ctx: Context<CreateVestingStream>,
+        stream_id: [u8; 32],
+        recipient: Pubkey,
+        start_time: i64,
+        cliff_time: i64,
+        deposited_amount: u64,
+        months: u16,
+        cancelable_by_sender: bool,
+        cancelable_by_recipient: bool,
+        cancel_notice_period: u64,
+        leaver_policy: LeaverPolicy,
+        acceleration: AccelerationClause,
+        stream_name: String,
+        yield_share_bps: Option<u16>,
+    ) -> Result<()> {
+        instructions::create_vesting_stream::monthly_handler(
+            ctx,
+            stream_id,
+            recipient,
+            start_time,
+            cliff_time,
+            deposited_amount,
+            months,
+            cancelable_by_sender,
+            cancelable_by_recipient,
+            cancel_notice_period,
+            leaver_policy,
+            acceleration,
+            stream_name,
+            yield_share_bps,
+        )
+    }
+
+    pub fn create_milestone_stream(
+        ctx: Context<CreateVestingStream>,
+        stream_id: [u8; 32],
+        recipient: Pubkey,
+        start_time: i64,
+        end_time: i64,
+        attestor: Pubkey,
+        milestone_amounts: Vec<u64>,
+        cancelable_by_sender: bool,
+        cancelable_by_recipient: bool,
+        cancel_notice_period: u64,
+        leaver_policy: LeaverPolicy,
+        acceleration: AccelerationClause,
+        stream_name: String,
+        yield_share_bps: Option<u16>,
+    ) -> Result<()> {
+        instructions::create_vesting_stream::milestone_handler(
+            ctx,
+            stream_id,
+            recipient,
+            start_time,
+            end_time,
+            attestor,
+            milestone_amounts,
+            cancelable_by_sender,
+            cancelable_by_recipient,
+            cancel_notice_period,
+            leaver_policy,
+            acceleration,
+            stream_name,
+            yield_share_bps,
+        )
+    }
+
+    pub fn approve_milestone(ctx: Context<ApproveMilestone>, milestone_index: u8) -> Result<()> {
+        instructions::approve_milestone::handler(ctx, milestone_index)
+    }
+
+    pub fn terminate_vesting(
+        ctx: Context<TerminateVesting>,
+        termination: TerminationKind,
+    ) -> Result<()> {
+        instructions::terminate_vesting::handler(ctx, termination)
+    }
+
+    pub fn fire_acceleration_trigger(
+        ctx: Context<FireAccelerationTrigger>,
+        trigger: AccelerationTrigger,
+    ) -> Result<()> {
+        instructions::fire_acceleration_trigger::handler(ctx, trigger)
+    }
+
+    pub fn split_stream(
+        ctx: Context<SplitStream>,
+        new_recipient: Pubkey,
+        split_amount: SplitAmount,
+        compliance_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::split_stream::handler(
+            ctx,
+            new_recipient,
+            split_amount,
+            compliance_proof,
+        )
+    }
+
+    pub fn merge_streams<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MergeStreams<'info>>,
+        end_time: i64,
+    ) -> Result<()> {
+        instructions::merge_streams::handler(ctx, end_time)
+    }
+
+    pub fn mint_position_token(ctx: Context<MintPositionToken>) -> Result<()> {
+        instructions::mint_position_token::handler(ctx)
+    }
+
+    pub fn set_withdraw_delegate(
+        ctx: Context<SetWithdrawDelegate>,
+        withdraw_authority: Option<Pubkey>,
+        destination: Option<Pubkey>,
+        authority_proof: Vec<[u8; 32]>,
+        destination_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::set_withdraw_delegate::handler(
+            ctx,
+            withdraw_authority,
+            destination,
+            authority_proof,
+            destination_proof,
+        )
+    }
+
+    pub fn propose_sender_transfer(
+        ctx: Context<ProposeSenderTransfer>,
+        new_sender: Pubkey,
+    ) -> Result<()> {
+        instructions::sender_transfer::propose_handler(ctx, new_sender)
+    }
+
+    pub fn accept_sender_transfer(ctx: Context<AcceptSenderTransfer>) -> Result<()> {
+        instructions::sender_transfer::accept_handler(ctx)
+    }
+
+    pub fn create_sender_profile(ctx: Context<CreateSenderProfile>) -> Result<()> {
+        instructions::create_sender_profile::handler(ctx)
+    }
+
+    pub fn topup_stream(ctx: Context<TopupStream>, amount: u64) -> Result<()> {
+        instructions::topup_stream::handler(ctx, amount)
+    }
+
+    pub fn create_lien(
+        ctx: Context<CreateLien>,
+        lender: Pubkey,
+        amount: u64,
+        compliance_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::lien::create_handler(ctx, lender, amount, compliance_proof)
+    }
+
+    pub fn repay_lien(ctx: Context<RepayLien>, amount: u64) -> Result<()> {
+        instructions::lien::repay_handler(ctx, amount)
+    }
+
+    pub fn release_lien(ctx: Context<ReleaseLien>) -> Result<()> {
+        instructions::lien::release_handler(ctx)
+    }
+
+    pub fn request_cancel(ctx: Context<RequestCancel>, effective_at: i64) -> Result<()> {
+        instructions::request_cancel::request_handler(ctx, effective_at)
+    }
+
+    pub fn finalize_cancel(ctx: Context<FinalizeCancel>) -> Result<()> {
+        instructions::request_cancel::finalize_handler(ctx)
+    }
+
+    pub fn propose_amendment(ctx: Context<ProposeAmendment>, terms: StreamTerms) -> Result<()> {
+        instructions::amend_stream::propose_handler(ctx, terms)
+    }
+
+    pub fn accept_amendment(ctx: Context<AcceptAmendment>, terms: StreamTerms) -> Result<()> {
+        instructions::amend_stream::accept_handler(ctx, terms)
+    }
+
+    pub fn init_treasury_audit_log(ctx: Context<InitTreasuryAuditLog>) -> Result<()> {
+        instructions::treasury_audit_log::handler(ctx)
+    }
+
+    pub fn set_treasury_manager(
+        ctx: Context<ManageTreasury>,
+        manager: Pubkey,
+        access_level: AccessLevel,
+    ) -> Result<()> {
+        instructions::treasury_management::set_manager_handler(ctx, manager, access_level)
+    }
+
+    pub fn remove_treasury_manager(ctx: Context<ManageTreasury>, manager: Pubkey) -> Result<()> {
+        instructions::treasury_management::remove_manager_handler(ctx, manager)
+    }
+
+    pub fn set_spending_limits(
+        ctx: Context<ManageTreasury>,
+        daily_limit: u64,
+        monthly_limit: u64,
+        admin_max_per_stream: u64,
+        manager_max_per_stream: u64,
+    ) -> Result<()> {
+        instructions::treasury_management::set_spending_limits_handler(
+            ctx,
+            daily_limit,
+            monthly_limit,
+            admin_max_per_stream,
+            manager_max_per_stream,
+        )
+    }
+
+    pub fn deposit_to_treasury(ctx: Context<DepositToTreasury>, amount: u64) -> Result<()> {
+        instructions::treasury_funds::deposit_handler(ctx, amount)
+    }
+
+    pub fn withdraw_from_treasury(ctx: Context<WithdrawFromTreasury>, amount: u64) -> Result<()> {
+        instructions::treasury_funds::withdraw_handler(ctx, amount)
+    }
+
+    pub fn create_treasury_stream(
+        ctx: Context<CreateTreasuryStream>,
+        stream_id: [u8; 32],
+        recipient: Pubkey,
+        deposited_amount: u64,
+        start_time: i64,
+        end_time: i64,
+        cliff_time: i64,
+        period: u64,
+        cancelable_by_sender: bool,
+        cancelable_by_recipient: bool,
+        stream_name: String,
+        compliance_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::treasury_funds::create_stream_handler(
+            ctx,
+            stream_id,
+            recipient,
+            deposited_amount,
+            start_time,
+            end_time,
+            cliff_time,
+            period,
+            cancelable_by_sender,
+            cancelable_by_recipient,
+            stream_name,
+            compliance_proof,
+        )
+    }
+
+    pub fn create_compliance_registry(
+        ctx: Context<CreateComplianceRegistry>,
+        mode: ComplianceMode,
+    ) -> Result<()> {
+        instructions::compliance::create_registry_handler(ctx, mode)
+    }
+
+    pub fn update_compliance_entries(
+        ctx: Context<UpdateComplianceRegistry>,
+        add: Vec<Pubkey>,
+        remove: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::compliance::update_entries_handler(ctx, add, remove)
+    }
+
+    pub fn set_compliance_merkle_root(
+        ctx: Context<UpdateComplianceRegistry>,
+        merkle_root: [u8; 32],
+    ) -> Result<()> {
+        instructions::compliance::set_merkle_root_handler(ctx, merkle_root)
+    }
+
+    pub fn set_treasury_compliance_registry(
+        ctx: Context<SetTreasuryComplianceRegistry>,
+    ) -> Result<()> {
+        instructions::compliance::set_treasury_registry_handler(ctx)
+    }
+
+    pub fn freeze_stream_withdrawals(
+        ctx: Context<FreezeStreamWithdrawals>,
+        frozen: bool,
+    ) -> Result<()> {
+        instructions::compliance::freeze_handler(ctx, frozen)
+    }
+
+    pub fn approve_yield_vault(
+        ctx: Context<ApproveYieldVault>,
+        adapter: YieldAdapter,
+    ) -> Result<()> {
+        instructions::escrow_yield::approve_vault_handler(ctx, adapter)
+    }
+
+    pub fn revoke_yield_vault(ctx: Context<RevokeYieldVault>) -> Result<()> {
+        instructions::escrow_yield::revoke_vault_handler(ctx)
+    }
+
+    pub fn accept_escrow_yield(ctx: Context<AcceptEscrowYield>) -> Result<()> {
+        instructions::escrow_yield::accept_handler(ctx)
+    }
+
+    pub fn rebalance_escrow_yield(ctx: Context<RebalanceEscrowYield>) -> Result<()> {
+        instructions::escrow_yield::rebalance_handler(ctx)
+    }
+
+    pub fn harvest_escrow_yield(ctx: Context<HarvestEscrowYield>) -> Result<()> {
+        instructions::escrow_yield::harvest_handler(ctx)
+    }
+
+    pub fn get_withdrawable(ctx: Context<ViewStream>) -> Result<StreamQuote> {
+        instructions::view_stream::withdrawable_handler(ctx)
+    }
+
+    pub fn get_streamed(ctx: Context<ViewStream>) -> Result<StreamQuote> {
+        instructions::view_stream::streamed_handler(ctx)
+    }
+
+    pub fn get_stream_summary(ctx: Context<ViewStream>) -> Result<StreamSummary> {
+        instructions::view_stream::summary_handler(ctx)
+    }
 }
 
 #[derive(Accounts)]
+#[instruction(stream_id: [u8; 32])]
 pub struct CreateStream<'info> {
     #[account(
         init,
         payer = sender,
-        space = 8 + size_of::<Stream>(),
-        seeds = [b"stream", sender.key().as_ref(), recipient.as_ref(), mint.key().as_ref()],
+        space = Stream::LEN,
+        seeds = [b"stream", sender.key().as_ref(), stream_id.as_ref()],
         bump
     )]
-    pub stream: Account<'info, Stream>,
+    pub stream: AccountLoader<'info, Stream>,
     
     #[account(mut)]
     pub sender: Signer<'info>,
     
+    #[account(mut, seeds = [b"sender_profile", sender.key().as_ref()], bump = sender_profile.bump)]
+    pub sender_profile: Option<Account<'info, SenderProfile>>,
+    
     /// CHECK: Recipient address validation handled in instruction
     pub recipient: UncheckedAccount<'info>,
     
@@ -352,8 +904,9 @@ pub struct CreateStream<'info> {
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     #[account(mut)]
-    pub stream: Account<'info, Stream>,
+    pub stream: AccountLoader<'info, Stream>,
     
+    /// Recipient, position holder or delegated withdraw authority
     pub recipient: Signer<'info>,
     
     #[account(
@@ -363,20 +916,42 @@ pub struct Withdraw<'info> {
     )]
     pub escrow_token_account: Account<'info, TokenAccount>,
     
+    /// Destination of the withdrawal, checked against the stream in the instruction
     #[account(
         mut,
-        constraint = recipient_token_account.mint == stream.mint,
-        constraint = recipient_token_account.owner == recipient.key()
+        constraint = recipient_token_account.mint == stream.load()?.mint
     )]
     pub recipient_token_account: Account<'info, TokenAccount>,
     
+    /// Position token held by the recipient when the stream is tokenized
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// Lender repayment account, required while the stream has a lien
+    #[account(
+        mut,
+        constraint = lender_token_account.key() == stream.load()?.lien.lender_token_account @ StreamFlowError::TokenAccountNotFound
+    )]
+    pub lender_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Yield vault program, required while part of the escrow is deposited in it
+    /// CHECK: checked against the stream when loading the adapter
+    pub yield_vault_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub yield_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub yield_vault_token_account: Option<UncheckedAccount<'info>>,
+    
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
 pub struct CancelStream<'info> {
     #[account(mut)]
-    pub stream: Account<'info, Stream>,
+    pub stream: AccountLoader<'info, Stream>,
     
     pub authority: Signer<'info>,
     
@@ -389,15 +964,32 @@ pub struct CancelStream<'info> {
     
     #[account(
         mut,
-        constraint = sender_token_account.mint == stream.mint
+        constraint = sender_token_account.mint == stream.load()?.mint,
+        constraint = sender_token_account.owner == stream.load()?.sender @ StreamFlowError::InvalidTokenAccountOwner,
     )]
     pub sender_token_account: Account<'info, TokenAccount>,
     
     #[account(
         mut,
-        constraint = recipient_token_account.mint == stream.mint
+        constraint = recipient_token_account.mint == stream.load()?.mint
     )]
     pub recipient_token_account: Account<'info, TokenAccount>,
+
+    /// Position token account of the holder when the stream is tokenized
+    pub position_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Yield vault program, required once escrow yield is enabled
+    /// CHECK: checked against the stream when loading the adapter
+    pub yield_vault_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub yield_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: checked against the stream when loading the adapter
+    #[account(mut)]
+    pub yield_vault_token_account: Option<UncheckedAccount<'info>>,
+
     
     pub token_program: Program<'info, Token>,
 }
@@ -405,15 +997,18 @@ pub struct CancelStream<'info> {
 #[derive(Accounts)]
 pub struct TransferStream<'info> {
     #[account(mut)]
-    pub stream: Account<'info, Stream>,
+    pub stream: AccountLoader<'info, Stream>,
     
     pub authority: Signer<'info>,
+
+    /// Required when the stream is bound to a compliance registry
+    pub compliance_registry: Option<Box<Account<'info, ComplianceRegistry>>>,
 }
 
 #[derive(Accounts)]
 pub struct UpdateStream<'info> {
     #[account(mut)]
-    pub stream: Account<'info, Stream>,
+    pub stream: AccountLoader<'info, Stream>,
     
     pub sender: Signer<'info>,
 }
@@ -423,7 +1018,7 @@ pub struct CreateTreasury<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + size_of::<Treasury>(),
+        space = Treasury::LEN,
         seeds = [b"treasury", authority.key().as_ref()],
         bump
     )]
@@ -435,4 +1030,79 @@ pub struct CreateTreasury<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[derive(Accounts)]
\ No newline at end of file
+#[derive(Accounts)]
+pub struct AddStreamToTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"treasury", treasury.authority.as_ref()],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    
+    #[account(mut)]
+    pub stream: AccountLoader<'info, Stream>,
+    
+    pub authority: Signer<'info>,
+
+    /// Required once the treasury has an audit log
+    #[account(mut)]
+    pub audit_log: Option<Box<Account<'info, TreasuryAuditLog>>>,
+
+    /// Required when the treasury is bound to a compliance registry
+    pub compliance_registry: Option<Box<Account<'info, ComplianceRegistry>>>,
+}
+
+#[event]
+pub struct StreamCreated {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub deposit_amount: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct Withdrawal {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub withdrawn_amount: u64,
+}
+
+#[event]
+pub struct StreamCanceled {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub canceled_at: i64,
+    pub withdrawn_amount: u64,
+    pub returned_amount: u64,
+}
+
+#[event]
+pub struct StreamTransferred {
+    pub stream: Pubkey,
+    pub old_recipient: Pubkey,
+    pub new_recipient: Pubkey,
+    pub transferred_by: Pubkey,
+}
+
+#[event]
+pub struct StreamUpdated {
+    pub stream: Pubkey,
+    pub sender: Pubkey,
+}
+
+#[event]
+pub struct TreasuryCreated {
+    pub treasury: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+}
+
+#[event]
+pub struct StreamAddedToTreasury {
+    pub treasury: Pubkey,
+    pub stream: Pubkey,
+}
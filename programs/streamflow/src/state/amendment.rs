@@ -0,0 +1,243 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use super::stream::Stream;
+use super::{utils, PodBool, VestingType};
+use crate::error::StreamFlowError;
+
+/// Complete set of amendable stream terms.
+///
+/// Proposals carry the full target terms rather than a diff, so the recipient
+/// countersigns exactly what will be applied.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct StreamTerms {
+    pub end_time: i64,
+    pub cliff_time: i64,
+    pub rate_amount: u64,
+    pub rate_interval_in_seconds: u64,
+    pub cancelable_by_sender: PodBool,
+    pub cancelable_by_recipient: PodBool,
+    pub transferable_by_sender: PodBool,
+    pub transferable_by_recipient: PodBool,
+    pub _padding: [u8; 4],
+}
+
+impl StreamTerms {
+    pub const LEN: usize = 8 + // end_time
+        8 + // cliff_time
+        8 + // rate_amount
+        8 + // rate_interval_in_seconds
+        1 + // cancelable_by_sender
+        1 + // cancelable_by_recipient
+        1 + // transferable_by_sender
+        1 + // transferable_by_recipient
+        4; // _padding
+
+    /// Current terms of `stream`
+    pub fn of(stream: &Stream) -> Self {
+        Self {
+            end_time: stream.end_time,
+            cliff_time: stream.cliff_time,
+            rate_amount: stream.rate_amount,
+            rate_interval_in_seconds: stream.rate_interval_in_seconds,
+            cancelable_by_sender: stream.cancelable_by_sender,
+            cancelable_by_recipient: stream.cancelable_by_recipient,
+            transferable_by_sender: stream.transferable_by_sender,
+            transferable_by_recipient: stream.transferable_by_recipient,
+            _padding: [0; 4],
+        }
+    }
+
+    /// Whether these terms only differ from `current` in the schedule fields
+    fn changes_schedule(&self, current: &StreamTerms) -> bool {
+        self.end_time != current.end_time
+            || self.cliff_time != current.cliff_time
+            || self.rate_amount != current.rate_amount
+            || self.rate_interval_in_seconds != current.rate_interval_in_seconds
+    }
+
+    /// Whether moving from `current` to these terms can only benefit the
+    /// recipient, which lets the sender apply them alone.
+    ///
+    /// Tokens must unlock no later than before: an earlier or equal cliff and end
+    /// and a higher or equal amount per unchanged interval. Permissions may only be
+    /// taken from the sender or given to the recipient.
+    pub fn favors_recipient(&self, current: &StreamTerms) -> bool {
+        if self == current {
+            return false;
+        }
+
+        self.end_time <= current.end_time
+            && self.cliff_time <= current.cliff_time
+            && self.rate_interval_in_seconds == current.rate_interval_in_seconds
+            && self.rate_amount >= current.rate_amount
+            && (current.cancelable_by_sender.get() || !self.cancelable_by_sender.get())
+            && (self.cancelable_by_recipient.get() || !current.cancelable_by_recipient.get())
+            && (current.transferable_by_sender.get() || !self.transferable_by_sender.get())
+            && (self.transferable_by_recipient.get() || !current.transferable_by_recipient.get())
+    }
+}
+
+impl Stream {
+    /// Check that `terms` describe a valid schedule for this stream at
+    /// `current_time` and that applying them would not take back tokens that have
+    /// already unlocked. Terms that only change permissions need no checks.
+    pub fn validate_terms(&self, terms: &StreamTerms, current_time: i64) -> Result<()> {
+        if !terms.changes_schedule(&StreamTerms::of(self)) {
+            return Ok(());
+        }
+
+        // Custom and monthly schedules are fixed by their segments or tranches
+        require!(
+            matches!(self.vesting_type()?, VestingType::Linear | VestingType::Cliff),
+            StreamFlowError::StreamModificationNotAllowed
+        );
+        require!(
+            terms.end_time > current_time,
+            StreamFlowError::InvalidEndTime
+        );
+
+        Stream::validate_params(
+            self.start_time,
+            terms.end_time,
+            terms.cliff_time,
+            self.deposited_amount,
+            terms.rate_amount,
+            terms.rate_interval_in_seconds,
+        )?;
+        utils::validate_duration((terms.end_time - self.start_time) as u64)?;
+
+        let scheduled_amount = self.linear_amount(self.streaming_time(current_time), terms);
+        require!(
+            self.acceleration
+                .accelerate(scheduled_amount, self.deposited_amount)?
+                >= self.withdrawn_amount,
+            StreamFlowError::StreamModificationNotAllowed
+        );
+
+        Ok(())
+    }
+
+    /// Replace the amendable terms of this stream
+    pub fn apply_terms(&mut self, terms: &StreamTerms) {
+        self.end_time = terms.end_time;
+        self.cliff_time = terms.cliff_time;
+        self.rate_amount = terms.rate_amount;
+        self.rate_interval_in_seconds = terms.rate_interval_in_seconds;
+        self.cancelable_by_sender = terms.cancelable_by_sender;
+        self.cancelable_by_recipient = terms.cancelable_by_recipient;
+        self.transferable_by_sender = terms.transferable_by_sender;
+        self.transferable_by_recipient = terms.transferable_by_recipient;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Terms of a linear stream of 1_000_000 tokens over 1_000 seconds, cancelable
+    /// by the sender and transferable by the recipient
+    fn terms() -> StreamTerms {
+        StreamTerms {
+            end_time: 1_000,
+            rate_amount: 1_000,
+            rate_interval_in_seconds: 1,
+            cancelable_by_sender: true.into(),
+            transferable_by_recipient: true.into(),
+            ..Default::default()
+        }
+    }
+
+    fn stream(withdrawn_amount: u64) -> Stream {
+        let mut stream = Stream::zeroed();
+        stream.deposited_amount = 1_000_000;
+        stream.withdrawn_amount = withdrawn_amount;
+        stream.apply_terms(&terms());
+        stream
+    }
+
+    fn favors_recipient(amend: impl FnOnce(&mut StreamTerms)) -> bool {
+        let mut amended = terms();
+        amend(&mut amended);
+        amended.favors_recipient(&terms())
+    }
+
+    #[test]
+    fn unchanged_terms_are_not_an_amendment() {
+        assert!(!terms().favors_recipient(&terms()));
+    }
+
+    #[test]
+    fn earlier_unlock_favors_recipient() {
+        assert!(favors_recipient(|terms| terms.end_time = 999));
+        assert!(favors_recipient(|terms| terms.rate_amount = 1_001));
+        assert!(favors_recipient(|terms| {
+            terms.end_time = 500;
+            terms.rate_amount = 2_000;
+        }));
+
+        let mut current = terms();
+        current.cliff_time = 100;
+        let mut amended = current;
+        amended.cliff_time = 0;
+        assert!(amended.favors_recipient(&current));
+        assert!(!current.favors_recipient(&amended));
+    }
+
+    #[test]
+    fn later_unlock_needs_countersignature() {
+        assert!(!favors_recipient(|terms| terms.end_time = 1_001));
+        assert!(!favors_recipient(|terms| terms.cliff_time = 1));
+        assert!(!favors_recipient(|terms| terms.rate_amount = 999));
+        // A different interval is not comparable, even at the same rate per second
+        assert!(!favors_recipient(|terms| {
+            terms.rate_interval_in_seconds = 2;
+            terms.rate_amount = 2_000;
+        }));
+        assert!(!favors_recipient(|terms| {
+            terms.end_time = 900;
+            terms.rate_amount = 999;
+        }));
+    }
+
+    #[test]
+    fn permissions_may_only_move_towards_recipient() {
+        assert!(favors_recipient(|terms| terms.cancelable_by_sender = false.into()));
+        assert!(favors_recipient(|terms| terms.cancelable_by_recipient = true.into()));
+        assert!(!favors_recipient(|terms| terms.transferable_by_sender = true.into()));
+        assert!(!favors_recipient(|terms| terms.transferable_by_recipient = false.into()));
+        assert!(!favors_recipient(|terms| {
+            terms.end_time = 900;
+            terms.transferable_by_sender = true.into();
+        }));
+    }
+
+    #[test]
+    fn schedule_changes_keep_unlocked_tokens() {
+        let mut slower = terms();
+        slower.end_time = 2_000;
+        slower.rate_amount = 500;
+
+        // 200_000 have unlocked at 400 under the slower schedule
+        assert!(stream(200_000).validate_terms(&slower, 400).is_ok());
+        assert!(stream(200_001).validate_terms(&slower, 400).is_err());
+
+        let mut shorter = terms();
+        shorter.end_time = 800;
+        shorter.rate_amount = 1_250;
+        assert!(stream(400_000).validate_terms(&shorter, 400).is_ok());
+        assert!(stream(0).validate_terms(&shorter, 800).is_err());
+
+        // Permission changes are allowed even once the schedule has ended
+        let mut permissions = terms();
+        permissions.cancelable_by_sender = false.into();
+        assert!(stream(1_000_000).validate_terms(&permissions, 2_000).is_ok());
+
+        let mut custom = stream(0);
+        custom.vesting_type = VestingType::Custom as u8;
+        assert!(custom.validate_terms(&shorter, 400).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+
+use super::treasury::Treasury;
+use super::TreasuryOperation;
+use crate::error::StreamFlowError;
+
+/// Number of entries kept by a treasury audit log
+pub const MAX_AUDIT_LOG_ENTRIES: usize = 64;
+
+/// One recorded treasury operation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct AuditEntry {
+    /// Position of the entry in the full history of the treasury, starting at 0
+    pub sequence: u64,
+    /// Kind of operation
+    pub operation: TreasuryOperation,
+    /// Signer that performed the operation
+    pub actor: Pubkey,
+    /// Token amount moved or committed (0 when none)
+    pub amount: u64,
+    /// Stream the operation concerns (default when none)
+    pub stream: Pubkey,
+    /// Time of the operation
+    pub timestamp: i64,
+}
+
+impl AuditEntry {
+    pub const LEN: usize = 8 + // sequence
+        1 + // operation
+        32 + // actor
+        8 + // amount
+        32 + // stream
+        8; // timestamp
+}
+
+/// Append-only log of the operations performed on a treasury.
+///
+/// Entries are kept in a ring of `MAX_AUDIT_LOG_ENTRIES`; once full, the oldest is
+/// overwritten. Sequence numbers never repeat, so an auditor reading the log
+/// periodically can tell whether any entries were missed.
+#[account]
+#[derive(Debug)]
+pub struct TreasuryAuditLog {
+    /// Treasury the log belongs to
+    pub treasury: Pubkey,
+    /// Sequence number of the next entry, i.e. the number of entries ever recorded
+    pub next_sequence: u64,
+    /// Ring of entries, the slot of an entry is its sequence modulo the capacity
+    pub entries: [AuditEntry; MAX_AUDIT_LOG_ENTRIES],
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+impl TreasuryAuditLog {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // treasury
+        8 + // next_sequence
+        AuditEntry::LEN * MAX_AUDIT_LOG_ENTRIES + // entries
+        1; // bump
+
+    /// Append an entry, overwriting the oldest one when the log is full
+    pub fn record(
+        &mut self,
+        operation: TreasuryOperation,
+        actor: Pubkey,
+        amount: u64,
+        stream: Pubkey,
+        timestamp: i64,
+    ) -> Result<()> {
+        let sequence = self.next_sequence;
+        self.entries[(sequence % MAX_AUDIT_LOG_ENTRIES as u64) as usize] = AuditEntry {
+            sequence,
+            operation,
+            actor,
+            amount,
+            stream,
+            timestamp,
+        };
+        self.next_sequence = sequence
+            .checked_add(1)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Number of entries currently held
+    pub fn len(&self) -> usize {
+        std::cmp::min(self.next_sequence, MAX_AUDIT_LOG_ENTRIES as u64) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next_sequence == 0
+    }
+
+    /// Entries currently held, oldest first
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        let first = self.next_sequence - self.len() as u64;
+        (first..self.next_sequence)
+            .map(|sequence| self.entries[(sequence % MAX_AUDIT_LOG_ENTRIES as u64) as usize])
+            .collect()
+    }
+}
+
+/// Record an operation on `treasury`. Once a treasury has an audit log, every
+/// treasury-scoped instruction must pass it.
+pub fn record_treasury_operation(
+    treasury: &Treasury,
+    audit_log: Option<&mut Account<TreasuryAuditLog>>,
+    operation: TreasuryOperation,
+    actor: Pubkey,
+    amount: u64,
+    stream: Pubkey,
+) -> Result<()> {
+    match audit_log {
+        Some(audit_log) => {
+            require!(
+                treasury.has_audit_log() && audit_log.key() == treasury.audit_log,
+                StreamFlowError::AuditLogMismatch
+            );
+            let timestamp = Clock::get()?.unix_timestamp;
+            audit_log.record(operation, actor, amount, stream, timestamp)
+        }
+        None => {
+            require!(!treasury.has_audit_log(), StreamFlowError::AuditLogRequired);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Civil calendar helpers for schedules that follow real month lengths.
+//!
+//! All dates are proleptic Gregorian in UTC. The day conversions follow Howard
+//! Hinnant's `days_from_civil`/`civil_from_days` algorithms, which only use integer
+//! arithmetic and are exact for every `i64` timestamp a stream can hold.
+
+use anchor_lang::prelude::*;
+
+use crate::error::StreamFlowError;
+
+/// Number of seconds in a UTC day
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Check whether `year` is a Gregorian leap year
+pub fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Number of days in `month` (1-12) of `year`
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for the given civil date
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let month_index = (if month > 2 { month - 3 } else { month + 9 }) as i64;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Civil date `(year, month, day)` for the given number of days since 1970-01-01
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = (if days >= 0 { days } else { days - 146_096 }) / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = (if month_index < 10 { month_index + 3 } else { month_index - 9 }) as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// Move `timestamp` forward by `months` calendar months, keeping the time of day.
+///
+/// The day of month is clamped to the last day of the target month, so a schedule
+/// anchored on the 31st unlocks on the 30th in April and on the 28th or 29th in
+/// February. Clamping is always applied to the original anchor, never compounded.
+pub fn add_months(timestamp: i64, months: u32) -> Result<i64> {
+    let days = timestamp.div_euclid(SECONDS_PER_DAY);
+    let seconds_of_day = timestamp.rem_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+
+    let month_count = year
+        .checked_mul(12)
+        .and_then(|total| total.checked_add(month as i64 - 1 + months as i64))
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    let target_year = month_count.div_euclid(12);
+    let target_month = month_count.rem_euclid(12) as u32 + 1;
+    let target_day = std::cmp::min(day, days_in_month(target_year, target_month));
+
+    days_from_civil(target_year, target_month, target_day)
+        .checked_mul(SECONDS_PER_DAY)
+        .and_then(|seconds| seconds.checked_add(seconds_of_day))
+        .ok_or_else(|| error!(StreamFlowError::ArithmeticOverflow))
+}
+
+/// Number of monthly anniversaries of `anchor` that have passed by `timestamp`
+pub fn months_elapsed(anchor: i64, timestamp: i64) -> Result<u32> {
+    if timestamp < anchor {
+        return Ok(0);
+    }
+
+    let (anchor_year, anchor_month, _) = civil_from_days(anchor.div_euclid(SECONDS_PER_DAY));
+    let (year, month, _) = civil_from_days(timestamp.div_euclid(SECONDS_PER_DAY));
+
+    let mut months = ((year - anchor_year) * 12 + month as i64 - anchor_month as i64) as u32;
+    if add_months(anchor, months)? > timestamp {
+        months -= 1;
+    }
+
+    Ok(months)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Midnight UTC of the given date
+    fn date(year: i64, month: u32, day: u32) -> i64 {
+        days_from_civil(year, month, day) * SECONDS_PER_DAY
+    }
+
+    #[test]
+    fn civil_days_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(date(2024, 1, 31), 1_706_659_200);
+        assert_eq!(date(2024, 2, 29), 1_709_164_800);
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+
+        for days in (-800_000..800_000).step_by(97) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn leap_years() {
+        assert!(is_leap_year(2024));
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(2023));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2100));
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(1900, 2), 28);
+
+        assert_eq!(
+            add_months(date(2024, 2, 29), 12).unwrap(),
+            date(2025, 2, 28)
+        );
+        assert_eq!(
+            add_months(date(2024, 2, 29), 48).unwrap(),
+            date(2028, 2, 29)
+        );
+    }
+
+    #[test]
+    fn end_of_month_clamps_without_compounding() {
+        let anchor = date(2024, 1, 31);
+        assert_eq!(add_months(anchor, 0).unwrap(), anchor);
+        assert_eq!(add_months(anchor, 1).unwrap(), date(2024, 2, 29));
+        assert_eq!(add_months(anchor, 2).unwrap(), date(2024, 3, 31));
+        assert_eq!(add_months(anchor, 3).unwrap(), date(2024, 4, 30));
+        assert_eq!(add_months(date(2023, 1, 31), 1).unwrap(), date(2023, 2, 28));
+        assert_eq!(add_months(date(2023, 8, 31), 1).unwrap(), date(2023, 9, 30));
+    }
+
+    #[test]
+    fn year_rollover() {
+        assert_eq!(
+            add_months(date(2023, 11, 15), 3).unwrap(),
+            date(2024, 2, 15)
+        );
+        assert_eq!(
+            add_months(date(2023, 12, 31), 1).unwrap(),
+            date(2024, 1, 31)
+        );
+        assert_eq!(
+            add_months(date(2023, 12, 31), 2).unwrap(),
+            date(2024, 2, 29)
+        );
+        assert_eq!(add_months(date(2023, 12, 1), 25).unwrap(), date(2026, 1, 1));
+        assert_eq!(
+            add_months(date(1969, 12, 15), 1).unwrap(),
+            date(1970, 1, 15)
+        );
+    }
+
+    #[test]
+    fn time_of_day_is_kept() {
+        let anchor = date(2024, 1, 31) + 12 * 3_600 + 34 * 60 + 56;
+        assert_eq!(anchor, 1_706_704_496);
+        assert_eq!(add_months(anchor, 1).unwrap(), date(2024, 2, 29) + 45_296);
+    }
+
+    #[test]
+    fn months_elapsed_counts_anniversaries() {
+        let anchor = date(2024, 1, 31) + 3_600;
+        assert_eq!(months_elapsed(anchor, anchor - 1).unwrap(), 0);
+        assert_eq!(months_elapsed(anchor, anchor).unwrap(), 0);
+
+        let first = date(2024, 2, 29) + 3_600;
+        assert_eq!(months_elapsed(anchor, first - 1).unwrap(), 0);
+        assert_eq!(months_elapsed(anchor, first).unwrap(), 1);
+        assert_eq!(months_elapsed(anchor, date(2024, 3, 31)).unwrap(), 1);
+        assert_eq!(
+            months_elapsed(anchor, date(2024, 3, 31) + 3_600).unwrap(),
+            2
+        );
+        assert_eq!(
+            months_elapsed(anchor, date(2025, 1, 31) + 3_600).unwrap(),
+            12
+        );
+        assert_eq!(months_elapsed(anchor, date(2025, 1, 30)).unwrap(), 11);
+
+        for months in 0..240 {
+            let unlock = add_months(anchor, months).unwrap();
+            assert_eq!(months_elapsed(anchor, unlock).unwrap(), months);
+            if months > 0 {
+                assert_eq!(months_elapsed(anchor, unlock - 1).unwrap(), months - 1);
+            }
+        }
+    }
+}
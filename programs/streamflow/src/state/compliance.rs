@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+use crate::error::StreamFlowError;
+
+/// Maximum number of accounts listed in a compliance registry
+pub const MAX_COMPLIANCE_ENTRIES: usize = 64;
+
+/// Maximum depth of a Merkle proof against a compliance registry root
+pub const MAX_COMPLIANCE_PROOF_LENGTH: usize = 24;
+
+/// How a compliance registry decides whether a recipient is approved
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ComplianceMode {
+    /// Only listed accounts are approved
+    Allowlist,
+    /// Every account except the listed ones is approved
+    Denylist,
+    /// Only accounts proven against `merkle_root` are approved
+    MerkleAllowlist,
+}
+
+impl Default for ComplianceMode {
+    fn default() -> Self {
+        ComplianceMode::Allowlist
+    }
+}
+
+/// Registry of approved stream recipients, maintained by a compliance authority.
+///
+/// A treasury that attaches a registry stamps it on every stream it funds or adopts.
+/// Such streams can only be created for or transferred to approved recipients, and
+/// the compliance authority can freeze their withdrawals.
+#[account]
+#[derive(Debug)]
+pub struct ComplianceRegistry {
+    /// Compliance authority maintaining the registry
+    pub authority: Pubkey,
+    /// How `entries` or `merkle_root` are interpreted
+    pub mode: ComplianceMode,
+    /// Number of entries used in `entries`
+    pub entry_count: u16,
+    /// Listed accounts, approved or denied depending on `mode`
+    pub entries: [Pubkey; MAX_COMPLIANCE_ENTRIES],
+    /// Root of the Merkle tree of approved accounts in `MerkleAllowlist` mode
+    pub merkle_root: [u8; 32],
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+impl ComplianceRegistry {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // authority
+        1 + // mode
+        2 + // entry_count
+        32 * MAX_COMPLIANCE_ENTRIES + // entries
+        32 + // merkle_root
+        1; // bump
+
+    fn is_listed(&self, account: &Pubkey) -> bool {
+        self.entries[..self.entry_count as usize].contains(account)
+    }
+
+    /// Whether `account` may receive streams. `proof` is only used in
+    /// `MerkleAllowlist` mode.
+    pub fn is_approved(&self, account: &Pubkey, proof: &[[u8; 32]]) -> bool {
+        match self.mode {
+            ComplianceMode::Allowlist => self.is_listed(account),
+            ComplianceMode::Denylist => !self.is_listed(account),
+            ComplianceMode::MerkleAllowlist => {
+                proof.len() <= MAX_COMPLIANCE_PROOF_LENGTH
+                    && verify_merkle_proof(&self.merkle_root, &compliance_leaf(account), proof)
+            }
+        }
+    }
+
+    /// Add `account` to the list, ignoring accounts already listed
+    pub fn add_entry(&mut self, account: Pubkey) -> Result<()> {
+        if self.is_listed(&account) {
+            return Ok(());
+        }
+
+        let count = self.entry_count as usize;
+        require!(
+            count < MAX_COMPLIANCE_ENTRIES,
+            StreamFlowError::InvalidTreasuryConfiguration
+        );
+        self.entries[count] = account;
+        self.entry_count += 1;
+        Ok(())
+    }
+
+    /// Remove `account` from the list, ignoring accounts not listed
+    pub fn remove_entry(&mut self, account: &Pubkey) {
+        let count = self.entry_count as usize;
+        if let Some(index) = self.entries[..count].iter().position(|entry| entry == account) {
+            self.entries[index] = self.entries[count - 1];
+            self.entries[count - 1] = Pubkey::default();
+            self.entry_count -= 1;
+        }
+    }
+}
+
+/// Merkle leaf of an approved account
+pub fn compliance_leaf(account: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[account.as_ref()]).0
+}
+
+/// Check `leaf` against `root`, hashing each pair of nodes in sorted order
+pub fn verify_merkle_proof(root: &[u8; 32], leaf: &[u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(*leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).0
+        } else {
+            keccak::hashv(&[sibling, &node]).0
+        }
+    });
+    computed == *root
+}
+
+/// Check that `recipient` is approved by the registry a stream or treasury is
+/// bound to. `expected` is the bound registry, default when there is none.
+pub fn require_compliant(
+    expected: &Pubkey,
+    registry: Option<&Account<ComplianceRegistry>>,
+    recipient: &Pubkey,
+    proof: &[[u8; 32]],
+) -> Result<()> {
+    if *expected == Pubkey::default() {
+        return Ok(());
+    }
+
+    let registry = registry.ok_or(StreamFlowError::ComplianceCheckFailed)?;
+    require!(
+        registry.key() == *expected,
+        StreamFlowError::ComplianceCheckFailed
+    );
+    require!(
+        registry.is_approved(recipient, proof),
+        StreamFlowError::ComplianceCheckFailed
+    );
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::error::StreamFlowError;
+
+/// Claim of a lender on the future withdrawals of a stream.
+///
+/// While the lien is outstanding every withdrawal pays the lender first, and the
+/// recipient position cannot be transferred, restructured or cancelled.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct Lien {
+    /// Lender the stream is pledged to (default when there is no lien)
+    pub lender: Pubkey,
+    /// Token account of the lender receiving repayments
+    pub lender_token_account: Pubkey,
+    /// Amount pledged to the lender
+    pub amount: u64,
+    /// Amount repaid so far, from withdrawals or directly
+    pub repaid_amount: u64,
+    /// Time the lien was created
+    pub created_at: i64,
+}
+
+impl Lien {
+    pub const LEN: usize = 32 + // lender
+        32 + // lender_token_account
+        8 + // amount
+        8 + // repaid_amount
+        8; // created_at
+
+    /// Whether the stream is currently pledged
+    pub fn is_active(&self) -> bool {
+        self.lender != Pubkey::default()
+    }
+
+    /// Amount still owed to the lender
+    pub fn outstanding(&self) -> u64 {
+        self.amount.saturating_sub(self.repaid_amount)
+    }
+
+    /// Divide a withdrawal of `amount` into the part owed to the lender and the
+    /// part left for the recipient
+    pub fn split_withdrawal(&self, amount: u64) -> (u64, u64) {
+        let to_lender = std::cmp::min(amount, self.outstanding());
+        (to_lender, amount - to_lender)
+    }
+
+    /// Record a repayment, clearing the lien once it is repaid in full. Returns
+    /// whether the lien was cleared.
+    pub fn repay(&mut self, amount: u64) -> Result<bool> {
+        require!(amount <= self.outstanding(), StreamFlowError::InvalidAmount);
+
+        self.repaid_amount += amount;
+        if self.outstanding() == 0 {
+            *self = Lien::default();
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use super::constants::MAX_MILESTONES;
+use crate::error::StreamFlowError;
+
+/// Prefix of the message an attestor signs to approve a milestone
+pub const MILESTONE_APPROVAL_DOMAIN: &[u8] = b"streamflow:milestone-approval";
+
+/// Tranches of a milestone stream and the attestor approvals unlocking them
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct MilestoneSchedule {
+    /// Key whose ed25519 signature approves a milestone
+    pub attestor: Pubkey,
+    /// Amount unlocked by each milestone
+    pub amounts: [u64; MAX_MILESTONES],
+    /// Time each milestone was approved (0 while pending)
+    pub approved_at: [i64; MAX_MILESTONES],
+    /// Number of milestones used in `amounts` and `approved_at`
+    pub count: u8,
+    pub _padding: [u8; 7],
+}
+
+impl MilestoneSchedule {
+    pub const LEN: usize = 32 + // attestor
+        8 * MAX_MILESTONES + // amounts
+        8 * MAX_MILESTONES + // approved_at
+        1 + // count
+        7; // _padding
+
+    /// Build a schedule from the tranche amounts, returning it with their total
+    pub fn new(attestor: Pubkey, amounts: &[u64]) -> Result<(Self, u64)> {
+        require!(
+            !amounts.is_empty() && amounts.len() <= MAX_MILESTONES,
+            StreamFlowError::InvalidVestingSchedule
+        );
+        require!(
+            attestor != Pubkey::default(),
+            StreamFlowError::InvalidPublicKey
+        );
+
+        let mut schedule = MilestoneSchedule {
+            attestor,
+            count: amounts.len() as u8,
+            ..Default::default()
+        };
+        let mut total: u64 = 0;
+        for (index, amount) in amounts.iter().enumerate() {
+            require!(*amount > 0, StreamFlowError::InvalidVestingSchedule);
+            schedule.amounts[index] = *amount;
+            total = total
+                .checked_add(*amount)
+                .ok_or(StreamFlowError::ArithmeticOverflow)?;
+        }
+
+        Ok((schedule, total))
+    }
+
+    pub fn is_approved(&self, index: usize) -> bool {
+        self.approved_at[index] != 0
+    }
+
+    /// Total of the milestones approved at or before `current_time`
+    pub fn approved_amount(&self, current_time: i64) -> u64 {
+        (0..self.count as usize)
+            .filter(|index| self.is_approved(*index) && self.approved_at[*index] <= current_time)
+            .map(|index| self.amounts[index])
+            .sum()
+    }
+
+    /// Record the approval of milestone `index`
+    pub fn approve(&mut self, index: usize, current_time: i64) -> Result<()> {
+        require!(
+            index < self.count as usize,
+            StreamFlowError::MilestoneNotFound
+        );
+        require!(
+            !self.is_approved(index),
+            StreamFlowError::MilestoneAlreadyApproved
+        );
+        self.approved_at[index] = current_time;
+        Ok(())
+    }
+}
+
+/// Message the attestor signs to approve milestone `index` of `stream` unlocking
+/// `amount`
+pub fn milestone_approval_message(stream: &Pubkey, index: u8, amount: u64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(MILESTONE_APPROVAL_DOMAIN.len() + 32 + 1 + 8);
+    message.extend_from_slice(MILESTONE_APPROVAL_DOMAIN);
+    message.extend_from_slice(stream.as_ref());
+    message.push(index);
+    message.extend_from_slice(&amount.to_le_bytes());
+    message
+}
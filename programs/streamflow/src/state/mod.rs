@@ -4,15 +4,35 @@
 //! This module contains all the account state definitions and related functionality
 //! for the StreamFlow token streaming and vesting platform.
 
+pub mod amendment;
+pub mod audit_log;
+pub mod calendar;
+pub mod compliance;
+pub mod lien;
+pub mod milestone;
+pub mod sender_profile;
 pub mod stream;
 pub mod treasury;
 pub mod vesting;
+pub mod yield_vault;
 
+pub use amendment::*;
+pub use audit_log::*;
+pub use calendar::*;
+pub use compliance::*;
+pub use lien::*;
+pub use milestone::*;
+pub use sender_profile::*;
 pub use stream::*;
 pub use treasury::*;
 pub use vesting::*;
+pub use yield_vault::*;
 
 use anchor_lang::prelude::*;
+use std::fmt;
+use std::io::{Read, Write};
+
+use crate::error::StreamFlowError;
 
 /// Common state validation trait
 pub trait StateValidation {
@@ -47,8 +67,9 @@ impl Default for StreamStatus {
     }
 }
 
-/// Vesting type enumeration
+/// Vesting type enumeration, stored as its `u8` discriminant in `Stream`
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
 pub enum VestingType {
     /// Linear vesting over time
     Linear,
@@ -56,6 +77,10 @@ pub enum VestingType {
     Cliff,
     /// Custom vesting schedule with multiple unlock points
     Custom,
+    /// Equal tranches unlocked on the same day of each calendar month (UTC)
+    Monthly,
+    /// Tranches unlocked by attestor approvals of milestones
+    Milestone,
 }
 
 impl Default for VestingType {
@@ -64,19 +89,90 @@ impl Default for VestingType {
     }
 }
 
-/// Treasury operation type
+impl TryFrom<u8> for VestingType {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(VestingType::Linear),
+            1 => Ok(VestingType::Cliff),
+            2 => Ok(VestingType::Custom),
+            3 => Ok(VestingType::Monthly),
+            4 => Ok(VestingType::Milestone),
+            _ => err!(StreamFlowError::InvalidStreamType),
+        }
+    }
+}
+
+/// A `bool` stored as one byte, so zero-copy accounts holding flags are `Pod`.
+///
+/// Serializes exactly like `bool`, which keeps instruction data and events that
+/// carry these flags unchanged.
+#[derive(Clone, Copy, Default, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(transparent)]
+pub struct PodBool(u8);
+
+impl PodBool {
+    pub const fn new(value: bool) -> Self {
+        Self(value as u8)
+    }
+
+    pub const fn get(self) -> bool {
+        self.0 != 0
+    }
+}
+
+impl From<bool> for PodBool {
+    fn from(value: bool) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<PodBool> for bool {
+    fn from(value: PodBool) -> Self {
+        value.get()
+    }
+}
+
+impl fmt::Debug for PodBool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl AnchorSerialize for PodBool {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        self.get().serialize(writer)
+    }
+}
+
+impl AnchorDeserialize for PodBool {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        bool::deserialize_reader(reader).map(Self::new)
+    }
+}
+
+/// Treasury operation type.
+///
+/// Only instructions taking the treasury are recorded. A stream funded from the
+/// treasury has the treasury authority as its sender, so cancelling or transferring
+/// it later does not touch the treasury and is not an operation of it.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
 pub enum TreasuryOperation {
     /// Deposit tokens into treasury
     Deposit,
     /// Withdraw tokens from treasury
     Withdraw,
-    /// Transfer tokens between accounts
-    Transfer,
     /// Create new stream from treasury
     CreateStream,
-    /// Cancel existing stream
-    CancelStream,
+    /// Attach an existing stream of the treasury authority
+    AddStream,
+    /// Add, change or remove a manager
+    SetManager,
+    /// Change the spending limits
+    SetSpendingLimits,
+    /// Attach or detach a compliance registry
+    SetComplianceRegistry,
 }
 
 /// Payment frequency for recurring streams
@@ -92,7 +188,7 @@ pub enum PaymentFrequency {
     Daily,
     /// Pay weekly
     Weekly,
-    /// Pay monthly
+    /// Pay monthly (fixed 30 day periods, see `VestingType::Monthly` for calendar months)
     Monthly,
 }
 
@@ -116,6 +212,15 @@ impl PaymentFrequency {
     }
 }
 
+/// Portion of a stream moved by `split_stream`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SplitAmount {
+    /// Share of the stream in basis points
+    Percentage(u16),
+    /// Exact amount of the unwithdrawn balance
+    Amount(u64),
+}
+
 /// Stream direction enumeration
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
 pub enum StreamDirection {
@@ -150,6 +255,9 @@ pub mod constants {
     /// Maximum number of vesting schedules per account
     pub const MAX_VESTING_SCHEDULES: usize = 50;
     
+    /// Maximum number of streams folded into a target by one merge
+    pub const MAX_MERGE_STREAMS: usize = 8;
+    
     /// Maximum number of treasury managers
     pub const MAX_TREASURY_MANAGERS: usize = 10;
     
@@ -165,6 +273,18 @@ pub mod constants {
     /// Maximum number of custom vesting points
     pub const MAX_VESTING_POINTS: usize = 365;
     
+    /// Maximum number of segments in a custom vesting curve
+    pub const MAX_VESTING_SEGMENTS: usize = 20;
+    
+    /// Maximum vesting curve exponent (16.0, scaled by RATE_PRECISION)
+    pub const MAX_VESTING_EXPONENT: u32 = 16_000_000;
+    
+    /// Maximum number of calendar months in a monthly vesting schedule (10 years)
+    pub const MAX_VESTING_MONTHS: u16 = 120;
+    
+    /// Maximum number of attested milestones in a milestone stream
+    pub const MAX_MILESTONES: usize = 16;
+    
     /// Precision for rate calculations (6 decimal places)
     pub const RATE_PRECISION: u64 = 1_000_000;
     
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::error::StreamFlowError;
+
+/// Per-sender counter used to derive stream addresses.
+///
+/// Streams are derived from `[b"stream", creator, stream_id]`. A sender with a
+/// profile gets sequential ids from `next_nonce`; without one the caller picks an
+/// arbitrary 32-byte id. Nonce ids keep their first 24 bytes zeroed and caller
+/// supplied ids may not, so the two schemes never collide.
+#[account]
+#[derive(Default)]
+pub struct SenderProfile {
+    /// The sender this profile belongs to
+    pub authority: Pubkey,
+    /// Nonce of the next stream created with this profile
+    pub next_nonce: u64,
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+impl SenderProfile {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // authority
+        8 + // next_nonce
+        1; // bump
+
+    /// Check `stream_id` against the next nonce and advance the counter
+    pub fn claim_nonce(&mut self, stream_id: &[u8; 32]) -> Result<()> {
+        require!(
+            *stream_id == nonce_stream_id(self.next_nonce),
+            StreamFlowError::InvalidNonce
+        );
+        self.next_nonce = self
+            .next_nonce
+            .checked_add(1)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Stream id for the given sender profile nonce
+pub fn nonce_stream_id(nonce: u64) -> [u8; 32] {
+    let mut stream_id = [0u8; 32];
+    stream_id[24..].copy_from_slice(&nonce.to_le_bytes());
+    stream_id
+}
+
+/// Stream id of the `split_index`-th stream split off `parent`.
+///
+/// Split streams live under the creator of the parent, so their ids are derived
+/// rather than chosen by the splitting recipient.
+pub fn split_stream_id(parent: &Pubkey, split_index: u64) -> [u8; 32] {
+    hashv(&[b"split", parent.as_ref(), &split_index.to_le_bytes()]).to_bytes()
+}
+
+/// Check that a caller supplied id cannot collide with nonce ids
+pub fn is_custom_stream_id(stream_id: &[u8; 32]) -> bool {
+    stream_id[..24].iter().any(|byte| *byte != 0)
+}
+
+/// Validate the id of a new stream, consuming a nonce when a profile is used
+pub fn claim_stream_id(profile: Option<&mut SenderProfile>, stream_id: &[u8; 32]) -> Result<()> {
+    match profile {
+        Some(profile) => profile.claim_nonce(stream_id),
+        None => {
+            require!(
+                is_custom_stream_id(stream_id),
+                StreamFlowError::InvalidNonce
+            );
+            Ok(())
+        }
+    }
+}
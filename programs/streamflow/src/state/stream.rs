@@ -1,8 +1,25 @@
 ```rust
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
+use anchor_spl::token::TokenAccount;
 
-#[account]
+use super::constants::MAX_VESTING_SEGMENTS;
+use super::calendar;
+use super::amendment::StreamTerms;
+use super::lien::Lien;
+use super::milestone::MilestoneSchedule;
+use super::vesting::{self, AccelerationClause, LeaverPolicy, TerminationKind, VestingSegment};
+use super::yield_vault::EscrowYield;
+use super::{utils, PodBool, VestingType};
+use crate::error::StreamFlowError;
+
+/// A token stream and its escrow terms.
+///
+/// Streams are zero-copy accounts with a `repr(C)` layout. Fields are grouped by
+/// alignment and the account is `Pod`, so the compiler rejects any implicit padding:
+/// new fields go in the matching group and take their space from `_reserved`.
+///
+/// Flags are stored as `PodBool` and enums as their `u8` discriminant.
+#[account(zero_copy)]
 #[derive(Debug)]
 pub struct Stream {
     /// The authority that can modify the stream
@@ -13,6 +30,28 @@ pub struct Stream {
     pub mint: Pubkey,
     /// The escrow token account holding the funds
     pub escrow_tokens: Pubkey,
+    /// The authority that cancelled the stream
+    pub cancelled_by: Pubkey,
+    /// Mint of the 1-of-1 token representing the recipient position (default when not tokenized)
+    pub position_mint: Pubkey,
+    /// Additional authority the recipient allows to trigger withdrawals (default when unset)
+    pub withdraw_authority: Pubkey,
+    /// Token account every withdrawal must be paid into (default when unset)
+    pub withdraw_destination: Pubkey,
+    /// The original sender, used for PDA derivation since `sender` can change
+    pub creator: Pubkey,
+    /// Sender nominated by `propose_sender_transfer` (default when none is pending)
+    pub pending_sender: Pubkey,
+    /// Party that requested the pending cancellation
+    pub cancel_requested_by: Pubkey,
+    /// Compliance registry recipients must be approved by (default when none)
+    pub compliance_registry: Pubkey,
+    /// Id chosen at creation, used with `creator` for PDA derivation
+    pub stream_id: [u8; 32],
+    /// The name/description of the stream
+    pub name: [u8; 64],
+    /// Additional metadata
+    pub metadata: [u8; 128],
     /// The amount of tokens deposited in the stream
     pub deposited_amount: u64,
     /// The amount of tokens withdrawn from the stream
@@ -23,141 +62,282 @@ pub struct Stream {
     pub end_time: i64,
     /// The cliff time (Unix timestamp) - tokens are locked until this time
     pub cliff_time: i64,
-    /// Whether the stream can be cancelled by the sender
-    pub cancelable_by_sender: bool,
-    /// Whether the stream can be cancelled by the recipient
-    pub cancelable_by_recipient: bool,
-    /// Whether automatic withdrawal is enabled
-    pub automatic_withdrawal: bool,
     /// The rate of tokens per second
     pub rate_amount: u64,
     /// The interval for rate calculation (in seconds)
     pub rate_interval_in_seconds: u64,
-    /// Whether the stream has been cancelled
-    pub cancelled: bool,
     /// The time when the stream was cancelled
     pub cancelled_at: i64,
-    /// The authority that cancelled the stream
-    pub cancelled_by: Pubkey,
-    /// Whether the stream has been closed
-    pub closed: bool,
     /// The time when the stream was closed
     pub closed_at: i64,
-    /// The current pause state of the stream
-    pub paused: bool,
     /// The time when the stream was paused
     pub paused_at: i64,
     /// Total time the stream has been paused
     pub total_paused_time: i64,
     /// The last time the stream was updated
     pub last_withdrawn_at: i64,
-    /// The name/description of the stream
-    pub name: [u8; 64],
-    /// Additional metadata
-    pub metadata: [u8; 128],
+    /// Minimum seconds between `request_cancel` and the cancellation taking effect
+    pub cancel_notice_period: u64,
+    /// Time a requested cancellation takes effect (0 when none is pending)
+    pub cancel_effective_at: i64,
+    /// Time the pending amendment was proposed (0 when none is pending)
+    pub amendment_proposed_at: i64,
+    /// Number of streams split off this one, numbering the id of the next split
+    pub split_count: u64,
+    /// Curve segments used when `vesting_type` is `Custom`
+    pub vesting_segments: [VestingSegment; MAX_VESTING_SEGMENTS],
+    /// Termination terms the sender can enforce with `terminate_vesting`
+    pub leaver_policy: LeaverPolicy,
+    /// Change-of-control acceleration terms and trigger state
+    pub acceleration: AccelerationClause,
+    /// Claim of a lender on withdrawals (inactive when `lien.lender` is default)
+    pub lien: Lien,
+    /// Terms proposed by the sender and awaiting the recipient's countersignature
+    pub pending_amendment: StreamTerms,
+    /// Attested tranches of a milestone stream
+    pub milestones: MilestoneSchedule,
+    /// Yield vault the unvested balance is deposited in (disabled by default)
+    pub escrow_yield: EscrowYield,
+    /// Number of monthly tranches when `vesting_type` is `Monthly`
+    pub vesting_months: u16,
+    /// Whether the stream can be cancelled by the sender
+    pub cancelable_by_sender: PodBool,
+    /// Whether the stream can be cancelled by the recipient
+    pub cancelable_by_recipient: PodBool,
+    /// Whether the sender can transfer the stream to a new recipient
+    pub transferable_by_sender: PodBool,
+    /// Whether the recipient can transfer the stream to a new recipient
+    pub transferable_by_recipient: PodBool,
+    /// Whether automatic withdrawal is enabled
+    pub automatic_withdrawal: PodBool,
+    /// Whether the stream has been cancelled
+    pub cancelled: PodBool,
+    /// Whether the stream has been closed
+    pub closed: PodBool,
+    /// The current pause state of the stream
+    pub paused: PodBool,
+    /// Whether the compliance authority froze withdrawals pending review
+    pub withdrawals_frozen: PodBool,
+    /// `VestingType` deciding how unlocked tokens are calculated
+    pub vesting_type: u8,
+    /// Number of populated entries in `vesting_segments`
+    pub vesting_segment_count: u8,
     /// The bump seed for the PDA
     pub bump: u8,
+    /// Aligns `_reserved` to 8 bytes
+    pub _padding: [u8; 2],
     /// Reserved space for future upgrades
-    pub _reserved: [u8; 256],
+    pub _reserved: [u8; 248],
 }
 
+const _: () = assert!(8 + std::mem::size_of::<Stream>() == Stream::LEN);
+
 impl Stream {
     pub const LEN: usize = 8 + // discriminator
         32 + // sender
         32 + // recipient
         32 + // mint
         32 + // escrow_tokens
+        32 + // cancelled_by
+        32 + // position_mint
+        32 + // withdraw_authority
+        32 + // withdraw_destination
+        32 + // creator
+        32 + // pending_sender
+        32 + // cancel_requested_by
+        32 + // compliance_registry
+        32 + // stream_id
+        64 + // name
+        128 + // metadata
         8 + // deposited_amount
         8 + // withdrawn_amount
         8 + // start_time
         8 + // end_time
         8 + // cliff_time
-        1 + // cancelable_by_sender
-        1 + // cancelable_by_recipient
-        1 + // automatic_withdrawal
         8 + // rate_amount
         8 + // rate_interval_in_seconds
-        1 + // cancelled
         8 + // cancelled_at
-        32 + // cancelled_by
-        1 + // closed
         8 + // closed_at
-        1 + // paused
         8 + // paused_at
         8 + // total_paused_time
         8 + // last_withdrawn_at
-        64 + // name
-        128 + // metadata
+        8 + // cancel_notice_period
+        8 + // cancel_effective_at
+        8 + // amendment_proposed_at
+        8 + // split_count
+        VestingSegment::LEN * MAX_VESTING_SEGMENTS + // vesting_segments
+        LeaverPolicy::LEN + // leaver_policy
+        AccelerationClause::LEN + // acceleration
+        Lien::LEN + // lien
+        StreamTerms::LEN + // pending_amendment
+        MilestoneSchedule::LEN + // milestones
+        EscrowYield::LEN + // escrow_yield
+        2 + // vesting_months
+        1 + // cancelable_by_sender
+        1 + // cancelable_by_recipient
+        1 + // transferable_by_sender
+        1 + // transferable_by_recipient
+        1 + // automatic_withdrawal
+        1 + // cancelled
+        1 + // closed
+        1 + // paused
+        1 + // withdrawals_frozen
+        1 + // vesting_type
+        1 + // vesting_segment_count
         1 + // bump
-        256; // _reserved
+        2 + // _padding
+        248; // _reserved
 
     /// Calculate the amount of tokens that can be withdrawn at the current time
     pub fn withdrawable_amount(&self, current_time: i64) -> Result<u64> {
-        if self.cancelled || self.closed {
+        if self.cancelled.get() || self.closed.get() {
             return Ok(0);
         }
 
+        let streamed_amount = self.streamed_amount(current_time)?;
+
+        Ok(streamed_amount.saturating_sub(self.withdrawn_amount))
+    }
+
+    /// Calculate the total amount that has been streamed so far, including any
+    /// vesting acceleration that has been triggered. Streaming stops at the effective
+    /// time of a requested cancellation.
+    pub fn streamed_amount(&self, current_time: i64) -> Result<u64> {
+        let scheduled_amount = self.scheduled_amount(self.streaming_time(current_time))?;
+
+        self.acceleration.accelerate(scheduled_amount, self.deposited_amount)
+    }
+
+    /// Calculate the amount unlocked by the stream schedule alone between `from`
+    /// and `to`, leaving out any vesting acceleration
+    pub fn scheduled_amount_between(&self, from: i64, to: i64) -> Result<u64> {
+        let unlocked_at_to = self.scheduled_amount(self.streaming_time(to))?;
+        let unlocked_at_from = self.scheduled_amount(self.streaming_time(from))?;
+
+        Ok(unlocked_at_to.saturating_sub(unlocked_at_from))
+    }
+
+    /// Clamp `current_time` to the effective time of a requested cancellation
+    pub(crate) fn streaming_time(&self, current_time: i64) -> i64 {
+        if self.has_pending_cancel() {
+            std::cmp::min(current_time, self.cancel_effective_at)
+        } else {
+            current_time
+        }
+    }
+
+    /// Calculate the amount unlocked by the stream schedule alone
+    fn scheduled_amount(&self, current_time: i64) -> Result<u64> {
         if current_time < self.cliff_time {
             return Ok(0);
         }
 
-        let effective_start_time = std::cmp::max(self.start_time, self.cliff_time);
-        let effective_current_time = if self.paused {
+        let vesting_type = self.vesting_type()?;
+        if vesting_type == VestingType::Custom {
+            return self.custom_vested_amount(current_time);
+        }
+
+        if vesting_type == VestingType::Monthly {
+            return self.monthly_vested_amount(current_time);
+        }
+
+        if vesting_type == VestingType::Milestone {
+            let effective_current_time = if self.paused.get() {
+                self.paused_at
+            } else {
+                current_time
+            };
+            return Ok(self.milestones.approved_amount(effective_current_time));
+        }
+
+        Ok(self.linear_amount(current_time, &StreamTerms::of(self)))
+    }
+
+    /// Calculate the amount unlocked by a linear schedule with `terms` in place of
+    /// the current terms of this stream
+    pub(crate) fn linear_amount(&self, current_time: i64, terms: &StreamTerms) -> u64 {
+        let effective_start_time = std::cmp::max(self.start_time, terms.cliff_time);
+        let effective_current_time = if self.paused.get() {
             self.paused_at
         } else {
-            std::cmp::min(current_time, self.end_time)
+            std::cmp::min(current_time, terms.end_time)
         };
 
         if effective_current_time <= effective_start_time {
-            return Ok(0);
+            return 0;
         }
 
         let elapsed_time = effective_current_time - effective_start_time - self.total_paused_time;
         
         if elapsed_time <= 0 {
-            return Ok(0);
+            return 0;
         }
 
-        let total_intervals = elapsed_time as u64 / self.rate_interval_in_seconds;
-        let streamed_amount = total_intervals * self.rate_amount;
-        
-        let available_amount = std::cmp::min(streamed_amount, self.deposited_amount);
+        let total_intervals = elapsed_time as u64 / terms.rate_interval_in_seconds;
+        let streamed_amount = total_intervals.saturating_mul(terms.rate_amount);
         
-        Ok(available_amount.saturating_sub(self.withdrawn_amount))
+        std::cmp::min(streamed_amount, self.deposited_amount)
     }
 
-    /// Calculate the total amount that has been streamed so far
-    pub fn streamed_amount(&self, current_time: i64) -> Result<u64> {
-        if current_time < self.cliff_time {
-            return Ok(0);
-        }
-
-        let effective_start_time = std::cmp::max(self.start_time, self.cliff_time);
-        let effective_current_time = if self.paused {
+    /// Calculate the amount unlocked by the custom vesting curve, ignoring withdrawals
+    fn custom_vested_amount(&self, current_time: i64) -> Result<u64> {
+        let effective_current_time = if self.paused.get() {
             self.paused_at
         } else {
             std::cmp::min(current_time, self.end_time)
         };
 
-        if effective_current_time <= effective_start_time {
+        let elapsed_time = effective_current_time - self.start_time - self.total_paused_time;
+
+        if elapsed_time <= 0 {
             return Ok(0);
         }
 
-        let elapsed_time = effective_current_time - effective_start_time - self.total_paused_time;
-        
-        if elapsed_time <= 0 {
+        let vested = vesting::vested_amount(self.vesting_segments(), elapsed_time as u64)?;
+
+        Ok(std::cmp::min(vested, self.deposited_amount))
+    }
+
+    /// Calculate the amount unlocked by calendar-month tranches, ignoring withdrawals.
+    ///
+    /// Tranches are anchored to the start date rather than to elapsed seconds, so
+    /// paused time does not move the unlock days.
+    fn monthly_vested_amount(&self, current_time: i64) -> Result<u64> {
+        if self.vesting_months == 0 {
             return Ok(0);
         }
 
-        let total_intervals = elapsed_time as u64 / self.rate_interval_in_seconds;
-        let streamed_amount = total_intervals * self.rate_amount;
-        
-        Ok(std::cmp::min(streamed_amount, self.deposited_amount))
+        let effective_current_time = if self.paused.get() {
+            self.paused_at
+        } else {
+            current_time
+        };
+
+        let months = std::cmp::min(
+            calendar::months_elapsed(self.start_time, effective_current_time)?,
+            self.vesting_months as u32,
+        );
+
+        // The last tranche absorbs any rounding remainder
+        let vested = (self.deposited_amount as u128) * (months as u128)
+            / (self.vesting_months as u128);
+
+        Ok(vested as u64)
+    }
+
+    /// Get how unlocked tokens are calculated
+    pub fn vesting_type(&self) -> Result<VestingType> {
+        VestingType::try_from(self.vesting_type)
+    }
+
+    /// Get the populated custom vesting segments
+    pub fn vesting_segments(&self) -> &[VestingSegment] {
+        &self.vesting_segments[..self.vesting_segment_count as usize]
     }
 
     /// Check if the stream is active (not cancelled, closed, or paused)
     pub fn is_active(&self) -> bool {
-        !self.cancelled && !self.closed && !self.paused
+        !self.cancelled.get() && !self.closed.get() && !self.paused.get()
     }
 
     /// Check if the stream has completed
@@ -170,6 +350,52 @@ impl Stream {
         self.deposited_amount.saturating_sub(self.withdrawn_amount)
     }
 
+    /// Split the remaining balance of a vesting stream terminated under its leaver
+    /// policy at `current_time`. Returns the amount accelerated or clawed back, the
+    /// amount paid to the recipient and the amount returned to the sender.
+    pub fn termination_payout(
+        &self,
+        termination: TerminationKind,
+        current_time: i64,
+    ) -> Result<(u64, u64, u64)> {
+        let policy = self.leaver_policy;
+        let vested = self.streamed_amount(current_time)?;
+        let vested_unwithdrawn = vested.saturating_sub(self.withdrawn_amount);
+        let unvested = self
+            .deposited_amount
+            .checked_sub(vested)
+            .ok_or(StreamFlowError::ArithmeticUnderflow)?;
+
+        match termination {
+            TerminationKind::GoodLeaver => {
+                let accelerated =
+                    utils::calculate_percentage(unvested, policy.good_leaver_acceleration_bps)?;
+                Ok((
+                    accelerated,
+                    vested_unwithdrawn + accelerated,
+                    unvested - accelerated,
+                ))
+            }
+            TerminationKind::BadLeaver => {
+                let clawback = if policy.bad_leaver_clawback_window > 0 {
+                    // Only tokens the schedule unlocked in the window are forfeited, not
+                    // an acceleration that happened to fire during it
+                    let window_start =
+                        current_time.saturating_sub(policy.bad_leaver_clawback_window);
+                    let vested_in_window = self.scheduled_amount_between(window_start, current_time)?;
+                    std::cmp::min(vested_in_window, vested_unwithdrawn)
+                } else {
+                    0
+                };
+                Ok((
+                    clawback,
+                    vested_unwithdrawn - clawback,
+                    unvested + clawback,
+                ))
+            }
+        }
+    }
+
     /// Calculate the stream progress as a percentage (0-100)
     pub fn progress_percentage(&self, current_time: i64) -> Result<u8> {
         if self.deposited_amount == 0 {
@@ -177,7 +403,7 @@ impl Stream {
         }
 
         let streamed = self.streamed_amount(current_time)?;
-        let percentage = (streamed * 100) / self.deposited_amount;
+        let percentage = (streamed as u128 * 100) / self.deposited_amount as u128;
         
         Ok(std::cmp::min(percentage, 100) as u8)
     }
@@ -188,14 +414,220 @@ impl Stream {
         total_duration - self.total_paused_time
     }
 
-    /// Check if the stream can be cancelled by the given authority
-    pub fn can_cancel(&self, authority: &Pubkey) -> bool {
-        if self.cancelled || self.closed {
+    /// Check if the stream is pledged to a lender with an amount still owed
+    pub fn has_lien(&self) -> bool {
+        self.lien.is_active()
+    }
+
+    /// Check if a lien would suspend the sender's cancel or termination rights, in
+    /// which case the sender has to co-sign it
+    pub fn lien_requires_sender(&self) -> bool {
+        self.cancelable_by_sender.get() || self.leaver_policy.enabled.get()
+    }
+
+    /// Check if a cancellation has been requested and not yet finalized
+    pub fn has_pending_cancel(&self) -> bool {
+        self.cancel_effective_at != 0
+    }
+
+    /// Check if the sender has proposed an amendment the recipient has not accepted
+    pub fn has_pending_amendment(&self) -> bool {
+        self.amendment_proposed_at != 0
+    }
+
+    /// Check if a new sender has been nominated and has not accepted yet
+    pub fn has_pending_sender_transfer(&self) -> bool {
+        self.pending_sender != Pubkey::default()
+    }
+
+    /// Check if the stream is bound to a compliance registry
+    pub fn has_compliance_registry(&self) -> bool {
+        self.compliance_registry != Pubkey::default()
+    }
+
+    /// Check if part of the escrow balance is deposited in a yield vault
+    pub fn has_vault_principal(&self) -> bool {
+        self.escrow_yield.deposited_principal > 0
+    }
+
+    /// Balance that has not unlocked yet, which a yield vault may hold
+    pub fn unvested_balance(&self, current_time: i64) -> Result<u64> {
+        Ok(self
+            .remaining_balance()
+            .saturating_sub(self.withdrawable_amount(current_time)?))
+    }
+
+    /// Check if the recipient position is represented by a token
+    pub fn has_position_token(&self) -> bool {
+        self.position_mint != Pubkey::default()
+    }
+
+    /// Owner of the recipient position: the recipient, or for tokenized streams the
+    /// owner of `position_token_account` when it holds the position token
+    pub fn position_holder(&self, position_token_account: Option<&TokenAccount>) -> Option<Pubkey> {
+        if !self.has_position_token() {
+            return Some(self.recipient);
+        }
+
+        match position_token_account {
+            Some(account) if account.mint == self.position_mint && account.amount == 1 => {
+                Some(account.owner)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check if `authority` may withdraw, either as the position holder or as the
+    /// recipient's delegated withdraw authority
+    pub fn is_withdraw_authority(
+        &self,
+        authority: &Pubkey,
+        position_token_account: Option<&TokenAccount>,
+    ) -> bool {
+        if !self.has_position_token()
+            && self.withdraw_authority != Pubkey::default()
+            && *authority == self.withdraw_authority
+        {
+            return true;
+        }
+
+        self.position_holder(position_token_account) == Some(*authority)
+    }
+
+    /// Check if the recipient's share of any payout may be paid into `destination`:
+    /// the fixed withdraw destination when one is set, otherwise an account of the
+    /// position holder
+    pub fn is_withdraw_destination(
+        &self,
+        destination: &Pubkey,
+        destination_owner: &Pubkey,
+        position_token_account: Option<&TokenAccount>,
+    ) -> bool {
+        if self.withdraw_destination != Pubkey::default() {
+            return *destination == self.withdraw_destination;
+        }
+
+        self.position_holder(position_token_account) == Some(*destination_owner)
+    }
+
+    /// Check if the stream can be cancelled by the given authority. The recipient
+    /// side is held by the position holder, see `position_holder`.
+    pub fn can_cancel(
+        &self,
+        authority: &Pubkey,
+        position_token_account: Option<&TokenAccount>,
+    ) -> bool {
+        if self.cancelled.get() || self.closed.get() {
             return false;
         }
 
-        (self.cancelable_by_sender && *authority == self.sender) ||
-        (self.cancelable_by_recipient && *authority == self.recipient)
+        (self.cancelable_by_sender.get() && *authority == self.sender) ||
+        (self.cancelable_by_recipient.get()
+            && self.position_holder(position_token_account) == Some(*authority))
+    }
+
+    /// Check whether `other` can be merged into this stream.
+    ///
+    /// The merged stream keeps the leaver policy and acceleration clause of this
+    /// stream, so both must match exactly, including any triggers already fired.
+    pub fn is_mergeable_with(&self, other: &Stream) -> bool {
+        self.sender == other.sender
+            && self.recipient == other.recipient
+            && self.mint == other.mint
+            && self.cancelable_by_sender == other.cancelable_by_sender
+            && self.cancelable_by_recipient == other.cancelable_by_recipient
+            && self.transferable_by_sender == other.transferable_by_sender
+            && self.transferable_by_recipient == other.transferable_by_recipient
+            && self.compliance_registry == other.compliance_registry
+            && self.leaver_policy == other.leaver_policy
+            && self.acceleration == other.acceleration
+    }
+
+    /// Split off `numerator / denominator` of this stream into a new stream for
+    /// `recipient` with an identical schedule, but without the position token,
+    /// withdraw delegate, pending requests or yield consent of this stream, which
+    /// were all given by or addressed to the current recipient.
+    ///
+    /// Deposited and withdrawn amounts are divided so that both streams keep
+    /// `deposited_amount - withdrawn_amount` equal to their escrow balance. The new
+    /// stream is written to `split` in place; returns the amount that must move
+    /// between the escrows.
+    pub fn split_into(
+        &mut self,
+        split: &mut Stream,
+        recipient: Pubkey,
+        numerator: u64,
+        denominator: u64,
+    ) -> Result<u64> {
+        require!(
+            numerator > 0 && numerator < denominator,
+            StreamFlowError::InvalidAmount
+        );
+
+        let scale = |amount: u64| -> u64 {
+            ((amount as u128) * (numerator as u128) / (denominator as u128)) as u64
+        };
+
+        let moved_amount = scale(self.remaining_balance());
+        let split_withdrawn = scale(self.withdrawn_amount);
+        let split_deposited = split_withdrawn + moved_amount;
+        require!(moved_amount > 0, StreamFlowError::InvalidAmount);
+
+        *split = *self;
+        split.recipient = recipient;
+        split.position_mint = Pubkey::default();
+        split.withdraw_authority = Pubkey::default();
+        split.withdraw_destination = Pubkey::default();
+        split.split_count = 0;
+        split.pending_sender = Pubkey::default();
+        split.cancel_requested_by = Pubkey::default();
+        split.cancel_effective_at = 0;
+        split.pending_amendment = StreamTerms::default();
+        split.amendment_proposed_at = 0;
+        split.escrow_yield.recipient_consented = false.into();
+        split.escrow_yield.harvested_yield = 0;
+        split.deposited_amount = split_deposited;
+        split.withdrawn_amount = split_withdrawn;
+
+        // Round rates up so both streams still complete by the end time
+        let remaining_deposited = self.deposited_amount - split_deposited;
+        let scale_rate = |deposited: u64| -> u64 {
+            let scaled = (self.rate_amount as u128) * (deposited as u128);
+            ((scaled + self.deposited_amount as u128 - 1) / self.deposited_amount as u128) as u64
+        };
+        split.rate_amount = scale_rate(split_deposited);
+        self.rate_amount = scale_rate(remaining_deposited);
+
+        // Scale the curve so each stream's segments still sum to its deposit, placing
+        // the rounding remainder on the largest segment
+        let segment_count = self.vesting_segment_count as usize;
+        if segment_count > 0 {
+            let mut allocated = 0u64;
+            let mut largest = 0;
+            for index in 0..segment_count {
+                let amount = self.vesting_segments[index].amount;
+                let split_amount = ((amount as u128) * (split_deposited as u128)
+                    / (self.deposited_amount as u128)) as u64;
+                split.vesting_segments[index].amount = split_amount;
+                allocated += split_amount;
+                if amount > self.vesting_segments[largest].amount {
+                    largest = index;
+                }
+            }
+            split.vesting_segments[largest].amount += split_deposited - allocated;
+
+            for index in 0..segment_count {
+                self.vesting_segments[index].amount = self.vesting_segments[index]
+                    .amount
+                    .checked_sub(split.vesting_segments[index].amount)
+                    .ok_or(StreamFlowError::ArithmeticUnderflow)?;
+            }
+        }
+
+        self.deposited_amount = remaining_deposited;
+        self.withdrawn_amount -= split_withdrawn;
+
+        Ok(moved_amount)
     }
 
     /// Validate stream parameters
@@ -228,6 +660,33 @@ impl Stream {
     }
 }
 
+/// Signer seeds of a stream PDA, copied out of the account so it need not stay
+/// borrowed across a CPI
+pub struct StreamSeeds {
+    creator: Pubkey,
+    stream_id: [u8; 32],
+    bump: [u8; 1],
+}
+
+impl StreamSeeds {
+    pub fn of(stream: &Stream) -> Self {
+        Self {
+            creator: stream.creator,
+            stream_id: stream.stream_id,
+            bump: [stream.bump],
+        }
+    }
+
+    pub fn signer(&self) -> [&[u8]; 4] {
+        [
+            b"stream",
+            self.creator.as_ref(),
+            self.stream_id.as_ref(),
+            &self.bump,
+        ]
+    }
+}
+
 #[error_code]
 pub enum StreamError {
     #[msg("Invalid time range: start time must be before end time")]
@@ -258,6 +717,28 @@ pub enum StreamError {
     StreamNotPaused,
     #[msg("Cannot cancel stream: not cancelable by this authority")]
     NotCancelable,
+    #[msg("Stream has been cancelled")]
+    StreamCanceled,
+    #[msg("Unauthorized: not allowed to withdraw from this stream")]
+    UnauthorizedWithdrawal,
+    #[msg("Insufficient funds: amount exceeds the withdrawable amount")]
+    InsufficientFunds,
+    #[msg("Stream is already cancelled")]
+    StreamAlreadyCanceled,
+    #[msg("Unauthorized: not allowed to cancel this stream")]
+    UnauthorizedCancellation,
+    #[msg("Unauthorized: not allowed to transfer this stream")]
+    UnauthorizedTransfer,
+    #[msg("Unauthorized: only the sender can update this stream")]
+    UnauthorizedUpdate,
+    #[msg("Name is too long")]
+    NameTooLong,
+    #[msg("Description is too long")]
+    DescriptionTooLong,
+    #[msg("Stream is not owned by the treasury authority")]
+    StreamNotOwnedByTreasury,
+    #[msg("Unauthorized: not the treasury authority")]
+    UnauthorizedTreasuryAccess,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
@@ -295,15 +776,346 @@ impl From<&Stream> for StreamData {
             start_time: stream.start_time,
             end_time: stream.end_time,
             cliff_time: stream.cliff_time,
-            cancelable_by_sender: stream.cancelable_by_sender,
-            cancelable_by_recipient: stream.cancelable_by_recipient,
-            automatic_withdrawal: stream.automatic_withdrawal,
+            cancelable_by_sender: stream.cancelable_by_sender.get(),
+            cancelable_by_recipient: stream.cancelable_by_recipient.get(),
+            automatic_withdrawal: stream.automatic_withdrawal.get(),
             rate_amount: stream.rate_amount,
             rate_interval_in_seconds: stream.rate_interval_in_seconds,
             name,
-            paused: stream.paused,
-            cancelled: stream.cancelled,
+            paused: stream.paused.get(),
+            cancelled: stream.cancelled.get(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::constants::RATE_PRECISION;
+    use crate::state::vesting::AccelerationTrigger;
+    use bytemuck::Zeroable;
+
+    /// Linear stream of 1_000_003 tokens over 1_000 seconds, 333_337 withdrawn
+    fn stream() -> Stream {
+        let mut stream = Stream::zeroed();
+        stream.recipient = Pubkey::new_unique();
+        stream.deposited_amount = 1_000_003;
+        stream.withdrawn_amount = 333_337;
+        stream.end_time = 1_000;
+        stream.rate_amount = 1_001;
+        stream.rate_interval_in_seconds = 1;
+        stream
+    }
+
+    /// The same stream on a three segment custom curve
+    fn custom_stream() -> Stream {
+        let mut stream = stream();
+        stream.vesting_type = VestingType::Custom as u8;
+        stream.vesting_segment_count = 3;
+        for (index, (amount, exponent)) in [(100_001, 2), (600_000, 1), (300_002, 3)]
+            .into_iter()
+            .enumerate()
+        {
+            stream.vesting_segments[index] = VestingSegment {
+                duration: 300,
+                amount,
+                exponent: exponent * RATE_PRECISION as u32,
+                ..Default::default()
+            };
+        }
+        stream
+    }
+
+    fn segment_sum(stream: &Stream) -> u64 {
+        stream.vesting_segments().iter().map(|segment| segment.amount).sum()
+    }
+
+    /// Split `original` and check both halves add up to it and stay fully funded
+    fn assert_conserved(original: &Stream, numerator: u64, denominator: u64) -> u64 {
+        let mut parent = *original;
+        let mut child = Stream::zeroed();
+        let moved = parent
+            .split_into(&mut child, Pubkey::new_unique(), numerator, denominator)
+            .unwrap();
+
+        assert_eq!(
+            parent.deposited_amount + child.deposited_amount,
+            original.deposited_amount
+        );
+        assert_eq!(
+            parent.withdrawn_amount + child.withdrawn_amount,
+            original.withdrawn_amount
+        );
+        assert_eq!(child.remaining_balance(), moved);
+        assert_eq!(
+            parent.remaining_balance() + moved,
+            original.remaining_balance()
+        );
+
+        if original.vesting_segment_count > 0 {
+            assert_eq!(segment_sum(&parent), parent.deposited_amount);
+            assert_eq!(segment_sum(&child), child.deposited_amount);
+        }
+
+        // Rates round up, so both streams still unlock their whole deposit
+        for half in [&parent, &child] {
+            assert!(half.rate_amount * 1_000 >= half.deposited_amount);
+            assert_eq!(half.streamed_amount(1_000).unwrap(), half.deposited_amount);
         }
+
+        moved
+    }
+
+    #[test]
+    fn split_by_percentage_conserves_amounts() {
+        for original in [stream(), custom_stream()] {
+            for bps in [1, 3_333, 5_000, 9_999] {
+                let moved = assert_conserved(&original, bps, 10_000);
+                assert_eq!(moved, original.remaining_balance() * bps / 10_000);
+            }
+        }
+    }
+
+    #[test]
+    fn split_by_amount_conserves_amounts() {
+        for original in [stream(), custom_stream()] {
+            let remaining = original.remaining_balance();
+            for amount in [1, 12_345, remaining - 1] {
+                assert_eq!(assert_conserved(&original, amount, remaining), amount);
+            }
+        }
+    }
+
+    #[test]
+    fn split_rejects_empty_halves() {
+        let mut parent = stream();
+        parent.deposited_amount = 9_999;
+        parent.withdrawn_amount = 0;
+        let mut child = Stream::zeroed();
+        let recipient = Pubkey::new_unique();
+
+        // 1 bps of 9_999 rounds down to nothing
+        assert!(parent.split_into(&mut child, recipient, 1, 10_000).is_err());
+        assert!(parent.split_into(&mut child, recipient, 0, 10_000).is_err());
+        assert!(parent.split_into(&mut child, recipient, 10_000, 10_000).is_err());
+        assert_eq!(parent.deposited_amount, 9_999);
+    }
+
+    #[test]
+    fn split_does_not_carry_recipient_state() {
+        let mut parent = stream();
+        parent.split_count = 2;
+        parent.pending_sender = Pubkey::new_unique();
+        parent.cancel_requested_by = parent.recipient;
+        parent.cancel_effective_at = 500;
+        parent.amendment_proposed_at = 100;
+        parent.pending_amendment.end_time = 2_000;
+        parent.escrow_yield.recipient_consented = true.into();
+        parent.escrow_yield.harvested_yield = 77;
+
+        let mut child = Stream::zeroed();
+        let recipient = Pubkey::new_unique();
+        parent
+            .split_into(&mut child, recipient, 5_000, 10_000)
+            .unwrap();
+
+        assert_eq!(child.recipient, recipient);
+        assert_eq!(child.split_count, 0);
+        assert!(!child.has_pending_sender_transfer());
+        assert!(!child.has_pending_cancel());
+        assert_eq!(child.cancel_requested_by, Pubkey::default());
+        assert!(!child.has_pending_amendment());
+        assert_eq!(child.pending_amendment, StreamTerms::default());
+        assert!(!child.escrow_yield.recipient_consented.get());
+        assert_eq!(child.escrow_yield.harvested_yield, 0);
+    }
+
+    #[test]
+    fn progress_percentage_of_large_deposits() {
+        let mut stream = stream();
+        stream.rate_amount = u64::MAX / 1_000;
+        stream.deposited_amount = stream.rate_amount * 1_000;
+
+        assert_eq!(stream.progress_percentage(0).unwrap(), 0);
+        assert_eq!(stream.progress_percentage(500).unwrap(), 50);
+        assert_eq!(stream.progress_percentage(999).unwrap(), 99);
+        assert_eq!(stream.progress_percentage(1_000).unwrap(), 100);
+        assert_eq!(stream.progress_percentage(2_000).unwrap(), 100);
+    }
+
+    /// Linear stream of 1_000_000 tokens over 1_000 seconds
+    fn linear_stream(withdrawn_amount: u64) -> Stream {
+        let mut stream = Stream::zeroed();
+        stream.deposited_amount = 1_000_000;
+        stream.withdrawn_amount = withdrawn_amount;
+        stream.end_time = 1_000;
+        stream.rate_amount = 1_000;
+        stream.rate_interval_in_seconds = 1;
+        stream
+    }
+
+    /// Split the remaining balance as `finalize_cancel` does at `at`
+    fn cancel_payout(stream: &Stream, at: i64) -> (u64, u64) {
+        let to_recipient = stream.withdrawable_amount(at).unwrap();
+        (to_recipient, stream.remaining_balance() - to_recipient)
+    }
+
+    #[test]
+    fn requested_cancel_settles_at_effective_time() {
+        let mut stream = linear_stream(100_000);
+        stream.cancel_effective_at = 600;
+
+        // The stream keeps unlocking during the notice period
+        assert_eq!(cancel_payout(&stream, 300), (200_000, 700_000));
+        assert_eq!(cancel_payout(&stream, 599), (499_000, 401_000));
+        // and stops at the effective time however late it is finalized
+        assert_eq!(cancel_payout(&stream, 600), (500_000, 400_000));
+        assert_eq!(cancel_payout(&stream, 900), (500_000, 400_000));
+        assert_eq!(cancel_payout(&stream, 5_000), (500_000, 400_000));
+    }
+
+    #[test]
+    fn requested_cancel_at_schedule_boundaries() {
+        let mut stream = linear_stream(0);
+        stream.cancel_effective_at = 1;
+        assert_eq!(cancel_payout(&stream, 1_000), (1_000, 999_000));
+
+        stream.cancel_effective_at = 1_000;
+        assert_eq!(cancel_payout(&stream, 1_000), (1_000_000, 0));
+
+        stream.cancel_effective_at = 1_500;
+        assert_eq!(cancel_payout(&stream, 2_000), (1_000_000, 0));
+
+        // Nothing is paid out when the cancellation takes effect before the cliff,
+        // from which the schedule starts unlocking
+        let mut stream = linear_stream(0);
+        stream.cliff_time = 200;
+        stream.cancel_effective_at = 199;
+        assert_eq!(cancel_payout(&stream, 1_000), (0, 1_000_000));
+        stream.cancel_effective_at = 200;
+        assert_eq!(cancel_payout(&stream, 1_000), (0, 1_000_000));
+        stream.cancel_effective_at = 201;
+        assert_eq!(cancel_payout(&stream, 1_000), (1_000, 999_000));
+    }
+
+    /// `linear_stream` with a leaver policy accelerating half of the unvested amount
+    /// for a good leaver and clawing back the last 100 seconds for a bad leaver
+    fn leaver_stream(withdrawn_amount: u64) -> Stream {
+        let mut stream = linear_stream(withdrawn_amount);
+        stream.leaver_policy = LeaverPolicy {
+            bad_leaver_clawback_window: 100,
+            good_leaver_acceleration_bps: 5_000,
+            enabled: true.into(),
+            ..Default::default()
+        };
+        stream
+    }
+
+    /// Terminate `stream` and check the payout covers exactly its remaining balance
+    fn terminate(stream: &Stream, termination: TerminationKind, at: i64) -> (u64, u64, u64) {
+        let payout = stream.termination_payout(termination, at).unwrap();
+        assert_eq!(payout.1 + payout.2, stream.remaining_balance(), "at {at}");
+        payout
+    }
+
+    #[test]
+    fn good_leaver_payout() {
+        let stream = leaver_stream(0);
+        assert_eq!(
+            terminate(&stream, TerminationKind::GoodLeaver, 0),
+            (500_000, 500_000, 500_000)
+        );
+        assert_eq!(
+            terminate(&stream, TerminationKind::GoodLeaver, 1_000),
+            (0, 1_000_000, 0)
+        );
+        assert_eq!(
+            terminate(&stream, TerminationKind::GoodLeaver, 2_000),
+            (0, 1_000_000, 0)
+        );
+
+        let stream = leaver_stream(100_000);
+        assert_eq!(
+            terminate(&stream, TerminationKind::GoodLeaver, 400),
+            (300_000, 600_000, 300_000)
+        );
+        assert_eq!(
+            terminate(&stream, TerminationKind::GoodLeaver, 999),
+            (500, 899_500, 500)
+        );
+    }
+
+    #[test]
+    fn bad_leaver_payout() {
+        let stream = leaver_stream(0);
+        // The window reaching back before the start only covers what vested since
+        assert_eq!(terminate(&stream, TerminationKind::BadLeaver, 0), (0, 0, 1_000_000));
+        assert_eq!(
+            terminate(&stream, TerminationKind::BadLeaver, 50),
+            (50_000, 0, 1_000_000)
+        );
+        assert_eq!(
+            terminate(&stream, TerminationKind::BadLeaver, 100),
+            (100_000, 0, 1_000_000)
+        );
+        assert_eq!(
+            terminate(&stream, TerminationKind::BadLeaver, 1_000),
+            (100_000, 900_000, 100_000)
+        );
+        // Nothing unlocks in a window that starts at the end time
+        assert_eq!(
+            terminate(&stream, TerminationKind::BadLeaver, 1_100),
+            (0, 1_000_000, 0)
+        );
+
+        // Tokens already withdrawn are not clawed back
+        let stream = leaver_stream(100_000);
+        assert_eq!(
+            terminate(&stream, TerminationKind::BadLeaver, 400),
+            (100_000, 200_000, 700_000)
+        );
+        let stream = leaver_stream(350_000);
+        assert_eq!(
+            terminate(&stream, TerminationKind::BadLeaver, 400),
+            (50_000, 0, 650_000)
+        );
+
+        let mut stream = leaver_stream(100_000);
+        stream.leaver_policy.bad_leaver_clawback_window = 0;
+        assert_eq!(
+            terminate(&stream, TerminationKind::BadLeaver, 400),
+            (0, 300_000, 600_000)
+        );
+    }
+
+    #[test]
+    fn acceleration_completes_at_end_time() {
+        let mut stream = leaver_stream(0);
+        stream.acceleration.percentage_bps = 5_000;
+        assert_eq!(stream.streamed_amount(400).unwrap(), 400_000);
+
+        assert!(stream
+            .acceleration
+            .fire(AccelerationTrigger::ChangeOfControl, 400)
+            .unwrap());
+        assert_eq!(stream.streamed_amount(400).unwrap(), 700_000);
+        assert_eq!(stream.streamed_amount(999).unwrap(), 999_500);
+        assert_eq!(stream.streamed_amount(1_000).unwrap(), 1_000_000);
+        assert_eq!(stream.streamed_amount(2_000).unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn bad_leaver_keeps_accelerated_amount() {
+        let mut stream = leaver_stream(0);
+        stream.acceleration.percentage_bps = 5_000;
+        stream.acceleration.accelerated_at = 350;
+
+        // 400_000 scheduled plus half of the 600_000 unvested, of which only the
+        // 100_000 scheduled in the window is clawed back
+        assert_eq!(
+            terminate(&stream, TerminationKind::BadLeaver, 400),
+            (100_000, 600_000, 400_000)
+        );
     }
 }
 ```
\ No newline at end of file
@@ -0,0 +1,271 @@
+use anchor_lang::prelude::*;
+
+use super::constants::MAX_TREASURY_MANAGERS;
+use super::AccessLevel;
+use crate::error::StreamFlowError;
+
+/// Maximum length of a treasury name in bytes
+pub const MAX_TREASURY_NAME_LENGTH: usize = 50;
+
+/// Maximum length of a treasury description in bytes
+pub const MAX_TREASURY_DESCRIPTION_LENGTH: usize = 200;
+
+/// Seconds covered by each bucket of the daily spending window
+pub const DAILY_WINDOW_BUCKET: i64 = 3_600;
+
+/// Number of buckets in the daily spending window (24 hours)
+pub const DAILY_WINDOW_BUCKETS: usize = 24;
+
+/// Seconds covered by each bucket of the monthly spending window
+pub const MONTHLY_WINDOW_BUCKET: i64 = 86_400;
+
+/// Number of buckets in the monthly spending window (30 days)
+pub const MONTHLY_WINDOW_BUCKETS: usize = 30;
+
+#[account]
+#[derive(Debug, Default)]
+pub struct Treasury {
+    /// The authority that manages the treasury
+    pub authority: Pubkey,
+    /// Display name of the treasury
+    pub name: String,
+    /// Free-form description of the treasury
+    pub description: String,
+    /// Whether the treasury closes itself once all streams are finished
+    pub auto_close: bool,
+    /// Number of streams ever added to the treasury
+    pub total_streams: u64,
+    /// Number of streams that were active when added
+    pub active_streams: u64,
+    /// Total amount deposited into streams of the treasury
+    pub total_deposited: u64,
+    /// Total amount withdrawn from streams of the treasury
+    pub total_withdrawn: u64,
+    /// Creation time (Unix timestamp)
+    pub created_at: i64,
+    /// Audit log recording treasury operations (default when not enabled)
+    pub audit_log: Pubkey,
+    /// Number of entries used in `managers`
+    pub manager_count: u8,
+    /// Signers allowed to act for the treasury besides the authority
+    pub managers: [TreasuryManager; MAX_TREASURY_MANAGERS],
+    /// Caps on the amount committed to new streams
+    pub spending_limits: SpendingLimits,
+    /// Compliance registry approving stream recipients (default when none)
+    pub compliance_registry: Pubkey,
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // authority
+        4 + MAX_TREASURY_NAME_LENGTH + // name
+        4 + MAX_TREASURY_DESCRIPTION_LENGTH + // description
+        1 + // auto_close
+        8 + // total_streams
+        8 + // active_streams
+        8 + // total_deposited
+        8 + // total_withdrawn
+        8 + // created_at
+        32 + // audit_log
+        1 + // manager_count
+        TreasuryManager::LEN * MAX_TREASURY_MANAGERS + // managers
+        SpendingLimits::LEN + // spending_limits
+        32 + // compliance_registry
+        1; // bump
+
+    /// Check if operations on the treasury are being recorded
+    pub fn has_audit_log(&self) -> bool {
+        self.audit_log != Pubkey::default()
+    }
+
+    /// Access level of `key`. The authority has admin access; signers that are not
+    /// managers have none.
+    pub fn access_level(&self, key: &Pubkey) -> Option<AccessLevel> {
+        if *key == self.authority {
+            return Some(AccessLevel::Admin);
+        }
+        self.managers[..self.manager_count as usize]
+            .iter()
+            .find(|manager| manager.key == *key)
+            .map(|manager| manager.access_level)
+    }
+
+    /// Add `key` as a manager or change its access level
+    pub fn set_manager(&mut self, key: Pubkey, access_level: AccessLevel) -> Result<()> {
+        require!(
+            key != Pubkey::default() && key != self.authority,
+            StreamFlowError::InvalidPublicKey
+        );
+
+        let count = self.manager_count as usize;
+        if let Some(manager) = self.managers[..count].iter_mut().find(|m| m.key == key) {
+            manager.access_level = access_level;
+            return Ok(());
+        }
+
+        require!(
+            count < MAX_TREASURY_MANAGERS,
+            StreamFlowError::InvalidTreasuryConfiguration
+        );
+        self.managers[count] = TreasuryManager { key, access_level };
+        self.manager_count += 1;
+        Ok(())
+    }
+
+    /// Remove `key` from the managers, keeping the list contiguous
+    pub fn remove_manager(&mut self, key: &Pubkey) -> Result<()> {
+        let count = self.manager_count as usize;
+        let index = self.managers[..count]
+            .iter()
+            .position(|manager| manager.key == *key)
+            .ok_or(StreamFlowError::UnauthorizedAccess)?;
+
+        self.managers[index] = self.managers[count - 1];
+        self.managers[count - 1] = TreasuryManager::default();
+        self.manager_count -= 1;
+        Ok(())
+    }
+}
+
+/// Signer allowed to act for a treasury
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TreasuryManager {
+    pub key: Pubkey,
+    pub access_level: AccessLevel,
+}
+
+impl TreasuryManager {
+    pub const LEN: usize = 32 + // key
+        1; // access_level
+}
+
+/// Caps on the amount a treasury commits to new streams, with the counters
+/// enforcing them.
+///
+/// Commitments are counted in hourly buckets over the last 24 hours and daily
+/// buckets over the last 30 days, so each window slides one bucket at a time
+/// instead of resetting at midnight or month end. A limit of 0 disables the cap.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpendingLimits {
+    /// Maximum amount committed over the daily window
+    pub daily_limit: u64,
+    /// Maximum amount committed over the monthly window
+    pub monthly_limit: u64,
+    /// Maximum deposit of a single stream created by an admin manager
+    pub admin_max_per_stream: u64,
+    /// Maximum deposit of a single stream created by a manager
+    pub manager_max_per_stream: u64,
+    /// Amount committed per hour, indexed by hour modulo the bucket count
+    pub hourly_committed: [u64; DAILY_WINDOW_BUCKETS],
+    /// Amount committed per day, indexed by day modulo the bucket count
+    pub daily_committed: [u64; MONTHLY_WINDOW_BUCKETS],
+    /// Time of the last commitment
+    pub last_committed_at: i64,
+}
+
+impl SpendingLimits {
+    pub const LEN: usize = 8 + // daily_limit
+        8 + // monthly_limit
+        8 + // admin_max_per_stream
+        8 + // manager_max_per_stream
+        8 * DAILY_WINDOW_BUCKETS + // hourly_committed
+        8 * MONTHLY_WINDOW_BUCKETS + // daily_committed
+        8; // last_committed_at
+
+    /// Amount committed over the daily window ending at `current_time`
+    pub fn committed_last_day(&self, current_time: i64) -> u64 {
+        let mut buckets = self.hourly_committed;
+        roll_window(&mut buckets, DAILY_WINDOW_BUCKET, self.last_committed_at, current_time);
+        buckets.iter().sum()
+    }
+
+    /// Amount committed over the monthly window ending at `current_time`
+    pub fn committed_last_month(&self, current_time: i64) -> u64 {
+        let mut buckets = self.daily_committed;
+        roll_window(&mut buckets, MONTHLY_WINDOW_BUCKET, self.last_committed_at, current_time);
+        buckets.iter().sum()
+    }
+
+    /// Check a new stream deposit of `amount` by a signer with `access_level`
+    /// against every cap and count it in both windows
+    pub fn commit(&mut self, amount: u64, access_level: AccessLevel, current_time: i64) -> Result<()> {
+        let per_stream_limit = match access_level {
+            AccessLevel::Admin => self.admin_max_per_stream,
+            AccessLevel::Manager => self.manager_max_per_stream,
+            AccessLevel::Viewer => return err!(StreamFlowError::UnauthorizedAccess),
+        };
+        require!(
+            per_stream_limit == 0 || amount <= per_stream_limit,
+            StreamFlowError::TransactionLimitExceeded
+        );
+
+        roll_window(
+            &mut self.hourly_committed,
+            DAILY_WINDOW_BUCKET,
+            self.last_committed_at,
+            current_time,
+        );
+        roll_window(
+            &mut self.daily_committed,
+            MONTHLY_WINDOW_BUCKET,
+            self.last_committed_at,
+            current_time,
+        );
+
+        let daily_total = self
+            .hourly_committed
+            .iter()
+            .sum::<u64>()
+            .checked_add(amount)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?;
+        require!(
+            self.daily_limit == 0 || daily_total <= self.daily_limit,
+            StreamFlowError::DailyLimitExceeded
+        );
+
+        let monthly_total = self
+            .daily_committed
+            .iter()
+            .sum::<u64>()
+            .checked_add(amount)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?;
+        require!(
+            self.monthly_limit == 0 || monthly_total <= self.monthly_limit,
+            StreamFlowError::MonthlyLimitExceeded
+        );
+
+        let current_time = std::cmp::max(current_time, self.last_committed_at);
+        let hour = bucket_index(current_time, DAILY_WINDOW_BUCKET, DAILY_WINDOW_BUCKETS);
+        let day = bucket_index(current_time, MONTHLY_WINDOW_BUCKET, MONTHLY_WINDOW_BUCKETS);
+        self.hourly_committed[hour] += amount;
+        self.daily_committed[day] += amount;
+        self.last_committed_at = current_time;
+        Ok(())
+    }
+}
+
+fn bucket_index(time: i64, bucket_length: i64, bucket_count: usize) -> usize {
+    (time.div_euclid(bucket_length) as u64 % bucket_count as u64) as usize
+}
+
+/// Clear the buckets that fell out of the window between the last commitment and
+/// `current_time`
+fn roll_window(buckets: &mut [u64], bucket_length: i64, last_committed_at: i64, current_time: i64) {
+    let last = last_committed_at.div_euclid(bucket_length);
+    let current = current_time.div_euclid(bucket_length);
+    if current <= last {
+        return;
+    }
+
+    if current - last >= buckets.len() as i64 {
+        buckets.fill(0);
+        return;
+    }
+
+    let count = buckets.len() as i64;
+    for bucket in last + 1..=current {
+        buckets[bucket.rem_euclid(count) as usize] = 0;
+    }
+}
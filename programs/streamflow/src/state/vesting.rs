@@ -0,0 +1,556 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use super::{constants, PodBool};
+use crate::error::StreamFlowError;
+
+/// Number of fractional bits used by the fixed-point curve math
+const FRAC_BITS: u32 = 60;
+
+/// 1.0 in fixed-point representation
+const ONE: u128 = 1 << FRAC_BITS;
+
+/// ln(2) in fixed-point representation
+const LN_2: u128 = 799_144_290_325_165_978;
+
+/// How a sender may terminate a vesting stream, fixed when the stream is created
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct LeaverPolicy {
+    /// Seconds before termination whose vested but unwithdrawn tokens a bad leaver
+    /// forfeits back to the sender (0 leaves vested tokens with the recipient)
+    pub bad_leaver_clawback_window: i64,
+    /// Share of the unvested amount released to a good leaver, in basis points
+    pub good_leaver_acceleration_bps: u16,
+    /// Whether the stream can be terminated with `terminate_vesting`
+    pub enabled: PodBool,
+    pub _padding: [u8; 5],
+}
+
+impl LeaverPolicy {
+    pub const LEN: usize = 8 + // bad_leaver_clawback_window
+        2 + // good_leaver_acceleration_bps
+        1 + // enabled
+        5; // _padding
+
+    /// Validate the policy parameters
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.good_leaver_acceleration_bps <= 10_000,
+            StreamFlowError::InvalidVestingSchedule
+        );
+        require!(
+            self.bad_leaver_clawback_window >= 0,
+            StreamFlowError::InvalidVestingSchedule
+        );
+        Ok(())
+    }
+}
+
+/// Kind of termination applied by `terminate_vesting`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum TerminationKind {
+    /// Recipient leaves on good terms and receives accelerated vesting
+    GoodLeaver,
+    /// Recipient leaves on bad terms and forfeits unvested tokens
+    BadLeaver,
+}
+
+/// Events that can fire an acceleration clause
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AccelerationTrigger {
+    /// The company is acquired or otherwise changes control
+    ChangeOfControl,
+    /// The recipient is terminated without cause after a change of control
+    Termination,
+}
+
+impl AccelerationTrigger {
+    fn mask(&self) -> u8 {
+        match self {
+            AccelerationTrigger::ChangeOfControl => 1 << 0,
+            AccelerationTrigger::Termination => 1 << 1,
+        }
+    }
+}
+
+/// Acceleration terms fixed at creation together with their trigger state.
+///
+/// Once the clause fires, `percentage_bps` of the then-unvested amount becomes
+/// withdrawable immediately and the remaining schedule is scaled down by the same
+/// share, so the stream still completes exactly at its end time.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct AccelerationClause {
+    /// Additional authority allowed to fire triggers besides the sender
+    pub trigger_authority: Pubkey,
+    /// Time the clause took effect (0 while pending)
+    pub accelerated_at: i64,
+    /// Share of the unvested amount accelerated, in basis points (0 disables)
+    pub percentage_bps: u16,
+    /// Whether both triggers are required instead of just change of control
+    pub double_trigger: PodBool,
+    /// Bitmask of triggers that have fired
+    pub triggers_fired: u8,
+    pub _padding: [u8; 4],
+}
+
+impl AccelerationClause {
+    pub const LEN: usize = 32 + // trigger_authority
+        8 + // accelerated_at
+        2 + // percentage_bps
+        1 + // double_trigger
+        1 + // triggers_fired
+        4; // _padding
+
+    /// Validate the acceleration terms and clear any trigger state
+    pub fn initialize(&mut self) -> Result<()> {
+        require!(
+            self.percentage_bps <= 10_000,
+            StreamFlowError::InvalidVestingSchedule
+        );
+        self.triggers_fired = 0;
+        self.accelerated_at = 0;
+        Ok(())
+    }
+
+    /// Check whether `authority` may fire triggers for a stream owned by `sender`
+    pub fn can_trigger(&self, authority: &Pubkey, sender: &Pubkey) -> bool {
+        *authority == *sender
+            || (self.trigger_authority != Pubkey::default() && *authority == self.trigger_authority)
+    }
+
+    /// Check whether the clause has taken effect
+    pub fn is_accelerated(&self) -> bool {
+        self.accelerated_at != 0
+    }
+
+    /// Record a trigger and return true if it completed the clause
+    pub fn fire(&mut self, trigger: AccelerationTrigger, current_time: i64) -> Result<bool> {
+        require!(self.percentage_bps > 0, StreamFlowError::FeatureNotAvailable);
+        require!(!self.is_accelerated(), StreamFlowError::AccelerationAlreadyApplied);
+        require!(
+            self.triggers_fired & trigger.mask() == 0,
+            StreamFlowError::AccelerationAlreadyApplied
+        );
+
+        self.triggers_fired |= trigger.mask();
+
+        let required = if self.double_trigger.get() {
+            AccelerationTrigger::ChangeOfControl.mask() | AccelerationTrigger::Termination.mask()
+        } else {
+            AccelerationTrigger::ChangeOfControl.mask()
+        };
+
+        if self.triggers_fired & required == required {
+            self.accelerated_at = current_time;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Apply the acceleration to an amount unlocked by the regular schedule
+    pub fn accelerate(&self, scheduled_amount: u64, deposited_amount: u64) -> Result<u64> {
+        if !self.is_accelerated() {
+            return Ok(scheduled_amount);
+        }
+
+        let unvested = deposited_amount.saturating_sub(scheduled_amount);
+        let accelerated = (unvested as u128) * (self.percentage_bps as u128) / 10_000;
+
+        Ok(scheduled_amount + accelerated as u64)
+    }
+}
+
+/// A single piece of a custom vesting schedule.
+///
+/// Segments are laid out back to back starting at the stream start time. Within a
+/// segment the unlocked portion of `amount` follows `(elapsed / duration) ^ exponent`,
+/// so an exponent of `RATE_PRECISION` is linear, larger exponents back-load the
+/// segment and smaller exponents front-load it. A segment with zero duration
+/// unlocks its whole amount as soon as it is reached, which allows stepped curves.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct VestingSegment {
+    /// Length of the segment in seconds
+    pub duration: u64,
+    /// Amount of tokens unlocked over the segment
+    pub amount: u64,
+    /// Curve exponent scaled by `constants::RATE_PRECISION`
+    pub exponent: u32,
+    pub _padding: [u8; 4],
+}
+
+impl VestingSegment {
+    pub const LEN: usize = 8 + // duration
+        8 + // amount
+        4 + // exponent
+        4; // _padding
+
+    /// Amount of this segment unlocked after `elapsed` seconds into it
+    pub fn unlocked_amount(&self, elapsed: u64) -> Result<u64> {
+        if elapsed >= self.duration {
+            return Ok(self.amount);
+        }
+        if elapsed == 0 || self.amount == 0 {
+            return Ok(0);
+        }
+
+        if self.exponent as u64 == constants::RATE_PRECISION {
+            let unlocked = (self.amount as u128)
+                .checked_mul(elapsed as u128)
+                .ok_or(StreamFlowError::ArithmeticOverflow)?
+                / self.duration as u128;
+            return Ok(unlocked as u64);
+        }
+
+        let fraction = (elapsed as u128) * ONE / self.duration as u128;
+        let curved = pow_fraction(fraction, self.exponent)?;
+        let unlocked = (self.amount as u128)
+            .checked_mul(curved)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?
+            >> FRAC_BITS;
+
+        Ok(std::cmp::min(unlocked as u64, self.amount))
+    }
+}
+
+/// Total amount unlocked by `segments` after `elapsed` seconds since the stream start
+pub fn vested_amount(segments: &[VestingSegment], elapsed: u64) -> Result<u64> {
+    let mut vested: u64 = 0;
+    let mut remaining = elapsed;
+
+    for segment in segments {
+        let unlocked = segment.unlocked_amount(remaining)?;
+        vested = vested
+            .checked_add(unlocked)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?;
+
+        if remaining < segment.duration {
+            break;
+        }
+        remaining -= segment.duration;
+    }
+
+    Ok(vested)
+}
+
+/// Validate a custom vesting schedule and return its total duration and amount
+pub fn validate_segments(segments: &[VestingSegment]) -> Result<(u64, u64)> {
+    require!(
+        !segments.is_empty() && segments.len() <= constants::MAX_VESTING_SEGMENTS,
+        StreamFlowError::InvalidVestingSchedule
+    );
+
+    let mut total_duration: u64 = 0;
+    let mut total_amount: u64 = 0;
+
+    for segment in segments {
+        require!(
+            segment.exponent > 0 && segment.exponent <= constants::MAX_VESTING_EXPONENT,
+            StreamFlowError::InvalidVestingSchedule
+        );
+
+        total_duration = total_duration
+            .checked_add(segment.duration)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?;
+        total_amount = total_amount
+            .checked_add(segment.amount)
+            .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    }
+
+    require!(total_duration > 0, StreamFlowError::InvalidVestingSchedule);
+    require!(total_amount > 0, StreamFlowError::InvalidVestingSchedule);
+
+    Ok((total_duration, total_amount))
+}
+
+/// Raise a fraction in `(0, 1)` to `exponent / RATE_PRECISION`
+fn pow_fraction(fraction: u128, exponent: u32) -> Result<u128> {
+    if fraction == 0 {
+        return Ok(0);
+    }
+
+    // x^e = 2^(-e * -log2(x))
+    let scaled = neg_log2(fraction)
+        .checked_mul(exponent as u128)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?
+        / constants::RATE_PRECISION as u128;
+
+    let whole = scaled >> FRAC_BITS;
+    if whole >= FRAC_BITS as u128 {
+        return Ok(0);
+    }
+    let frac = scaled & (ONE - 1);
+
+    Ok(exp_neg((frac * LN_2) >> FRAC_BITS) >> whole)
+}
+
+/// -log2(x) for a fraction in `(0, 1)`
+fn neg_log2(fraction: u128) -> u128 {
+    // Normalize into [1, 2) and remember how far we shifted
+    let msb = 127 - fraction.leading_zeros();
+    let shift = FRAC_BITS - msb;
+    let mut z = fraction << shift;
+
+    // Extract the fractional bits of log2(z) by repeated squaring
+    let mut log = 0u128;
+    for bit in 1..=FRAC_BITS {
+        z = (z * z) >> FRAC_BITS;
+        if z >= 2 * ONE {
+            z >>= 1;
+            log |= ONE >> bit;
+        }
+    }
+
+    (shift as u128) * ONE - log
+}
+
+/// e^(-a) for `a` in `[0, ln 2)` using its Taylor series
+fn exp_neg(a: u128) -> u128 {
+    let mut positive = ONE;
+    let mut negative = 0u128;
+    let mut term = ONE;
+
+    for k in 1..=24u128 {
+        term = term * a / (k * ONE);
+        if term == 0 {
+            break;
+        }
+        if k % 2 == 1 {
+            negative += term;
+        } else {
+            positive += term;
+        }
+    }
+
+    positive - negative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constants::{MAX_VESTING_SEGMENTS, RATE_PRECISION};
+
+    const LINEAR: u32 = RATE_PRECISION as u32;
+
+    fn segment(duration: u64, amount: u64, exponent: u32) -> VestingSegment {
+        VestingSegment {
+            duration,
+            amount,
+            exponent,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn segment_boundaries() {
+        for exponent in [LINEAR / 4, LINEAR, 3 * LINEAR] {
+            let curve = segment(1_000, 5_000, exponent);
+            assert_eq!(curve.unlocked_amount(0).unwrap(), 0);
+            assert_eq!(curve.unlocked_amount(1_000).unwrap(), 5_000);
+            assert_eq!(curve.unlocked_amount(u64::MAX).unwrap(), 5_000);
+        }
+
+        let step = segment(0, 700, LINEAR);
+        assert_eq!(step.unlocked_amount(0).unwrap(), 700);
+    }
+
+    #[test]
+    fn linear_segment_rounds_down() {
+        let small = segment(3, 10, LINEAR);
+        assert_eq!(small.unlocked_amount(1).unwrap(), 3);
+        assert_eq!(small.unlocked_amount(2).unwrap(), 6);
+        assert_eq!(small.unlocked_amount(3).unwrap(), 10);
+
+        let large = segment(1_000, u64::MAX, LINEAR);
+        assert_eq!(large.unlocked_amount(500).unwrap(), u64::MAX / 2);
+    }
+
+    #[test]
+    fn curved_segment_interpolation() {
+        // Within 0.01% of the exact value at half time
+        let cases = [
+            (2 * LINEAR, 250_000_000),
+            (LINEAR / 2, 707_106_781),
+            (LINEAR + LINEAR / 2, 353_553_390),
+        ];
+        for (exponent, expected) in cases {
+            let unlocked = segment(1_000, 1_000_000_000, exponent)
+                .unlocked_amount(500)
+                .unwrap();
+            assert!(
+                unlocked.abs_diff(expected) <= expected / 10_000,
+                "{exponent}: {unlocked}"
+            );
+            assert!(unlocked <= expected, "{exponent}: {unlocked}");
+        }
+
+        // A very steep curve unlocks nothing until the segment is nearly over
+        let steep = segment(1_000, 1_000_000, constants::MAX_VESTING_EXPONENT);
+        assert_eq!(steep.unlocked_amount(100).unwrap(), 0);
+    }
+
+    #[test]
+    fn curves_are_monotonic() {
+        for exponent in [1, LINEAR / 10, LINEAR / 2, LINEAR, 2 * LINEAR, 7 * LINEAR] {
+            let curve = segment(10_000, u64::MAX / 3, exponent);
+            let mut previous = 0;
+            for elapsed in (0..=10_000).step_by(37).chain([9_999, 10_000]) {
+                let unlocked = curve.unlocked_amount(elapsed).unwrap();
+                assert!(unlocked >= previous, "{exponent} at {elapsed}");
+                assert!(unlocked <= curve.amount);
+                previous = unlocked;
+            }
+        }
+    }
+
+    #[test]
+    fn segments_accumulate_at_boundaries() {
+        let segments = [
+            segment(100, 1_000, LINEAR),
+            segment(0, 500, LINEAR),
+            segment(200, 2_000, 2 * LINEAR),
+        ];
+        assert_eq!(vested_amount(&segments, 0).unwrap(), 0);
+        assert_eq!(vested_amount(&segments, 50).unwrap(), 500);
+        assert_eq!(vested_amount(&segments, 99).unwrap(), 990);
+        assert_eq!(vested_amount(&segments, 100).unwrap(), 1_500);
+        assert_eq!(vested_amount(&segments, 300).unwrap(), 3_500);
+        assert_eq!(vested_amount(&segments, 10_000).unwrap(), 3_500);
+        assert_eq!(validate_segments(&segments).unwrap(), (300, 3_500));
+    }
+
+    #[test]
+    fn twenty_segments() {
+        let segments: Vec<VestingSegment> = (0..MAX_VESTING_SEGMENTS as u64)
+            .map(|index| {
+                segment(
+                    30 + index,
+                    1_000 * (index + 1),
+                    LINEAR / 2 * (index as u32 % 5 + 1),
+                )
+            })
+            .collect();
+        let (total_duration, total_amount) = validate_segments(&segments).unwrap();
+        assert_eq!(total_duration, 790);
+        assert_eq!(total_amount, 210_000);
+
+        let mut previous = 0;
+        for elapsed in 0..=total_duration {
+            let vested = vested_amount(&segments, elapsed).unwrap();
+            assert!(vested >= previous, "at {elapsed}");
+            previous = vested;
+        }
+        assert_eq!(previous, total_amount);
+
+        let mut boundary = 0;
+        let mut cumulative = 0;
+        for piece in &segments {
+            boundary += piece.duration;
+            cumulative += piece.amount;
+            assert_eq!(vested_amount(&segments, boundary).unwrap(), cumulative);
+        }
+
+        let mut too_many = segments.clone();
+        too_many.push(segment(1, 1, LINEAR));
+        assert!(validate_segments(&too_many).is_err());
+    }
+
+    #[test]
+    fn invalid_segments_are_rejected() {
+        assert!(validate_segments(&[]).is_err());
+        assert!(validate_segments(&[segment(100, 100, 0)]).is_err());
+        assert!(
+            validate_segments(&[segment(100, 100, constants::MAX_VESTING_EXPONENT + 1)]).is_err()
+        );
+        assert!(validate_segments(&[segment(0, 100, LINEAR)]).is_err());
+        assert!(validate_segments(&[segment(100, 0, LINEAR)]).is_err());
+        assert!(validate_segments(&[segment(1, u64::MAX, LINEAR), segment(1, 1, LINEAR)]).is_err());
+    }
+
+    fn clause(percentage_bps: u16, double_trigger: bool) -> AccelerationClause {
+        AccelerationClause {
+            percentage_bps,
+            double_trigger: double_trigger.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn single_trigger_fires_on_change_of_control() {
+        let mut acceleration = clause(5_000, false);
+        assert!(!acceleration.fire(AccelerationTrigger::Termination, 10).unwrap());
+        assert!(!acceleration.is_accelerated());
+        assert!(acceleration.fire(AccelerationTrigger::ChangeOfControl, 20).unwrap());
+        assert_eq!(acceleration.accelerated_at, 20);
+
+        // Neither trigger can fire again once the clause took effect
+        assert!(acceleration.fire(AccelerationTrigger::ChangeOfControl, 30).is_err());
+        assert!(acceleration.fire(AccelerationTrigger::Termination, 30).is_err());
+        assert_eq!(acceleration.accelerated_at, 20);
+    }
+
+    #[test]
+    fn double_trigger_needs_both_triggers() {
+        for (first, second) in [
+            (AccelerationTrigger::ChangeOfControl, AccelerationTrigger::Termination),
+            (AccelerationTrigger::Termination, AccelerationTrigger::ChangeOfControl),
+        ] {
+            let mut acceleration = clause(5_000, true);
+            assert!(!acceleration.fire(first, 10).unwrap());
+            assert!(acceleration.fire(first, 15).is_err());
+            assert!(!acceleration.is_accelerated());
+            assert!(acceleration.fire(second, 20).unwrap());
+            assert_eq!(acceleration.accelerated_at, 20);
+        }
+    }
+
+    #[test]
+    fn disabled_clause_cannot_fire() {
+        let mut acceleration = clause(0, false);
+        assert!(acceleration.fire(AccelerationTrigger::ChangeOfControl, 10).is_err());
+        assert_eq!(acceleration.triggers_fired, 0);
+    }
+
+    #[test]
+    fn trigger_authority() {
+        let sender = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mut acceleration = clause(5_000, false);
+        assert!(acceleration.can_trigger(&sender, &sender));
+        assert!(!acceleration.can_trigger(&authority, &sender));
+        assert!(!acceleration.can_trigger(&Pubkey::default(), &sender));
+
+        acceleration.trigger_authority = authority;
+        assert!(acceleration.can_trigger(&sender, &sender));
+        assert!(acceleration.can_trigger(&authority, &sender));
+    }
+
+    #[test]
+    fn acceleration_releases_share_of_unvested() {
+        let mut acceleration = clause(2_500, false);
+        assert_eq!(acceleration.accelerate(400, 1_000).unwrap(), 400);
+
+        acceleration.fire(AccelerationTrigger::ChangeOfControl, 10).unwrap();
+        assert_eq!(acceleration.accelerate(0, 1_000).unwrap(), 250);
+        assert_eq!(acceleration.accelerate(400, 1_000).unwrap(), 550);
+        assert_eq!(acceleration.accelerate(999, 1_000).unwrap(), 999);
+        assert_eq!(acceleration.accelerate(1_000, 1_000).unwrap(), 1_000);
+        assert_eq!(
+            acceleration.accelerate(0, u64::MAX).unwrap(),
+            u64::MAX / 4
+        );
+
+        let mut full = clause(10_000, false);
+        full.fire(AccelerationTrigger::ChangeOfControl, 10).unwrap();
+        assert_eq!(full.accelerate(0, 1_000).unwrap(), 1_000);
+    }
+}
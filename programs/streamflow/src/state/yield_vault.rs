@@ -0,0 +1,421 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{get_return_data, invoke};
+use anchor_spl::token::{self, Approve, Revoke, TokenAccount};
+use bytemuck::{Pod, Zeroable};
+
+use super::stream::{Stream, StreamSeeds};
+use super::PodBool;
+use crate::error::StreamFlowError;
+
+/// Denominator of `EscrowYield::recipient_share_bps`
+pub const YIELD_SHARE_DENOMINATOR: u16 = 10_000;
+
+/// Interface an escrow yield vault program speaks
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum YieldAdapter {
+    /// Program implementing the interface described on `LendingVaultAdapter`
+    LendingVault,
+}
+
+impl Default for YieldAdapter {
+    fn default() -> Self {
+        YieldAdapter::LendingVault
+    }
+}
+
+impl TryFrom<u8> for YieldAdapter {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(YieldAdapter::LendingVault),
+            _ => err!(StreamFlowError::YieldVaultMismatch),
+        }
+    }
+}
+
+/// Vault program the escrow of a stream may be deposited in, approved by the
+/// program upgrade authority. Streams pick their vault at creation and only deposit
+/// while the approval stands; once it is revoked they can only redeem.
+#[account]
+pub struct ApprovedYieldVault {
+    /// Approved vault program
+    pub vault_program: Pubkey,
+    /// `YieldAdapter` used to talk to `vault_program`
+    pub adapter: u8,
+    /// Timestamp of the approval
+    pub approved_at: i64,
+    /// The bump seed for the PDA
+    pub bump: u8,
+}
+
+impl ApprovedYieldVault {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // vault_program
+        1 + // adapter
+        8 + // approved_at
+        1; // bump
+
+    /// Check whether `account`, the approval address derived for a vault program,
+    /// still holds an approval. Revoked approvals are closed.
+    pub fn is_approved(account: &AccountInfo) -> bool {
+        account.owner == &crate::ID && !account.data_is_empty()
+    }
+}
+
+/// Opt-in deposit of the unvested escrow balance into a yield vault.
+///
+/// Principal is tracked separately from the position value, so unlocking and
+/// withdrawals never depend on vault performance. Anything above the principal is
+/// yield, split between recipient and sender. The vault and split are fixed at
+/// creation, nothing is deposited until the recipient accepts them.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable,
+)]
+#[repr(C)]
+pub struct EscrowYield {
+    /// Program of the yield vault (default when yield is disabled)
+    pub vault_program: Pubkey,
+    /// Vault state account
+    pub vault: Pubkey,
+    /// Token account of the vault holding deposited tokens
+    pub vault_token_account: Pubkey,
+    /// Principal currently held by the vault on behalf of the escrow
+    pub deposited_principal: u64,
+    /// Total yield paid out so far
+    pub harvested_yield: u64,
+    /// Share of the yield paid to the recipient in basis points, the sender gets the rest
+    pub recipient_share_bps: u16,
+    /// `YieldAdapter` used to talk to `vault_program`
+    pub adapter: u8,
+    /// Whether the recipient accepted the vault and yield split
+    pub recipient_consented: PodBool,
+    pub _padding: [u8; 4],
+}
+
+impl EscrowYield {
+    pub const LEN: usize = 32 + // vault_program
+        32 + // vault
+        32 + // vault_token_account
+        8 + // deposited_principal
+        8 + // harvested_yield
+        2 + // recipient_share_bps
+        1 + // adapter
+        1 + // recipient_consented
+        4; // _padding
+
+    pub fn is_enabled(&self) -> bool {
+        self.vault_program != Pubkey::default()
+    }
+
+    pub fn adapter(&self) -> Result<YieldAdapter> {
+        YieldAdapter::try_from(self.adapter)
+    }
+
+    /// Divide `amount` of yield into the recipient and sender shares
+    pub fn split_yield(&self, amount: u64) -> (u64, u64) {
+        let to_recipient = (amount as u128 * self.recipient_share_bps as u128
+            / YIELD_SHARE_DENOMINATOR as u128) as u64;
+        (to_recipient, amount - to_recipient)
+    }
+}
+
+/// Yield vault the escrow of a stream can park its unvested balance in.
+///
+/// Vault programs never get the stream signature. A deposit delegates exactly the
+/// deposited amount of the escrow to the vault and revokes it afterwards. They are
+/// not trusted either way: the helpers below check the escrow balance around every
+/// call.
+pub trait YieldVault {
+    /// Current value of the escrow position in underlying tokens
+    fn position_value(&self) -> Result<u64>;
+
+    /// Move `amount` from the escrow into the vault. `signer_seeds` only sign the
+    /// delegation of `amount` to the vault.
+    fn deposit(&self, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()>;
+
+    /// Move `amount` of underlying tokens from the vault back into the escrow
+    fn redeem(&self, amount: u64) -> Result<()>;
+}
+
+/// Adapter for programs implementing a minimal lending vault interface.
+///
+/// Every instruction takes `[vault (w), vault_token_account (w), escrow (w),
+/// stream, token_program]` and uses Anchor style discriminators:
+///
+/// - `deposit(amount: u64)` moves `amount` from the escrow into the vault, using
+///   the `vault` account as the delegate of the escrow
+/// - `redeem(amount: u64)` moves `amount` of underlying tokens back to the escrow
+/// - `position_value()` returns the value of the escrow position as `u64` return data
+///
+/// The stream never signs, `deposit` is authorized by a delegation of `amount` that
+/// is revoked as soon as the call returns.
+pub struct LendingVaultAdapter<'info> {
+    pub program: AccountInfo<'info>,
+    pub vault: AccountInfo<'info>,
+    pub vault_token_account: AccountInfo<'info>,
+    pub escrow: AccountInfo<'info>,
+    pub stream: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> LendingVaultAdapter<'info> {
+    fn instruction(&self, name: &str, amount: Option<u64>) -> Instruction {
+        let preimage = format!("global:{}", name);
+        let mut data = hash(preimage.as_bytes()).to_bytes()[..8].to_vec();
+        if let Some(amount) = amount {
+            data.extend_from_slice(&amount.to_le_bytes());
+        }
+
+        Instruction {
+            program_id: self.program.key(),
+            accounts: vec![
+                AccountMeta::new(self.vault.key(), false),
+                AccountMeta::new(self.vault_token_account.key(), false),
+                AccountMeta::new(self.escrow.key(), false),
+                AccountMeta::new_readonly(self.stream.key(), false),
+                AccountMeta::new_readonly(self.token_program.key(), false),
+            ],
+            data,
+        }
+    }
+
+    fn account_infos(&self) -> [AccountInfo<'info>; 6] {
+        [
+            self.vault.clone(),
+            self.vault_token_account.clone(),
+            self.escrow.clone(),
+            self.stream.clone(),
+            self.token_program.clone(),
+            self.program.clone(),
+        ]
+    }
+}
+
+impl<'info> YieldVault for LendingVaultAdapter<'info> {
+    fn position_value(&self) -> Result<u64> {
+        invoke(
+            &self.instruction("position_value", None),
+            &self.account_infos(),
+        )?;
+
+        let (program_id, data) =
+            get_return_data().ok_or(StreamFlowError::YieldVaultMismatch)?;
+        require!(
+            program_id == self.program.key() && data.len() == 8,
+            StreamFlowError::YieldVaultMismatch
+        );
+        Ok(u64::from_le_bytes(data[..8].try_into().unwrap()))
+    }
+
+    fn deposit(&self, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        token::approve(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                Approve {
+                    to: self.escrow.clone(),
+                    delegate: self.vault.clone(),
+                    authority: self.stream.clone(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        invoke(
+            &self.instruction("deposit", Some(amount)),
+            &self.account_infos(),
+        )?;
+        token::revoke(CpiContext::new_with_signer(
+            self.token_program.clone(),
+            Revoke {
+                source: self.escrow.clone(),
+                authority: self.stream.clone(),
+            },
+            signer_seeds,
+        ))
+    }
+
+    fn redeem(&self, amount: u64) -> Result<()> {
+        invoke(
+            &self.instruction("redeem", Some(amount)),
+            &self.account_infos(),
+        )?;
+        Ok(())
+    }
+}
+
+/// Build the adapter for the vault configured on `stream`, checking the supplied
+/// vault accounts against it
+pub fn load_yield_vault<'info>(
+    stream: &AccountLoader<'info, Stream>,
+    escrow: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    vault_program: Option<&UncheckedAccount<'info>>,
+    vault: Option<&UncheckedAccount<'info>>,
+    vault_token_account: Option<&UncheckedAccount<'info>>,
+) -> Result<Box<dyn YieldVault + 'info>> {
+    let config = stream.load()?.escrow_yield;
+    let (Some(vault_program), Some(vault), Some(vault_token_account)) =
+        (vault_program, vault, vault_token_account)
+    else {
+        return err!(StreamFlowError::YieldVaultMismatch);
+    };
+    require!(
+        config.is_enabled()
+            && vault_program.key() == config.vault_program
+            && vault.key() == config.vault
+            && vault_token_account.key() == config.vault_token_account,
+        StreamFlowError::YieldVaultMismatch
+    );
+
+    match config.adapter()? {
+        YieldAdapter::LendingVault => Ok(Box::new(LendingVaultAdapter {
+            program: vault_program.to_account_info(),
+            vault: vault.to_account_info(),
+            vault_token_account: vault_token_account.to_account_info(),
+            escrow,
+            stream: stream.to_account_info(),
+            token_program,
+        })),
+    }
+}
+
+fn token_balance(token_account: &AccountInfo) -> Result<u64> {
+    let data = token_account.try_borrow_data()?;
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+}
+
+/// Run a vault call and check it moved exactly `amount` into (`inbound`) or out
+/// of the escrow.
+///
+/// The stream account must not be borrowed while the vault is called.
+fn move_through_vault(
+    escrow: &AccountInfo,
+    amount: u64,
+    inbound: bool,
+    call: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    let before = token_balance(escrow)?;
+
+    call()?;
+
+    let after = token_balance(escrow)?;
+    let expected = if inbound {
+        before.checked_add(amount)
+    } else {
+        before.checked_sub(amount)
+    };
+    require!(
+        Some(after) == expected,
+        StreamFlowError::YieldVaultShortfall
+    );
+    Ok(())
+}
+
+/// Deposit `amount` of the escrow into the vault
+pub fn deposit_escrow_principal(
+    stream: &AccountLoader<Stream>,
+    vault: &dyn YieldVault,
+    escrow: &AccountInfo,
+    amount: u64,
+) -> Result<()> {
+    let seeds = StreamSeeds::of(&*stream.load()?);
+    move_through_vault(escrow, amount, false, || {
+        vault.deposit(amount, &[&seeds.signer()[..]])
+    })?;
+
+    let stream = &mut *stream.load_mut()?;
+    stream.escrow_yield.deposited_principal = stream
+        .escrow_yield
+        .deposited_principal
+        .checked_add(amount)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+/// Redeem `amount` of principal from the vault into the escrow
+pub fn redeem_escrow_principal(
+    stream: &AccountLoader<Stream>,
+    vault: &dyn YieldVault,
+    escrow: &AccountInfo,
+    amount: u64,
+) -> Result<()> {
+    let principal = stream
+        .load()?
+        .escrow_yield
+        .deposited_principal
+        .checked_sub(amount)
+        .ok_or(StreamFlowError::InsufficientFunds)?;
+
+    move_through_vault(escrow, amount, true, || vault.redeem(amount))?;
+
+    stream.load_mut()?.escrow_yield.deposited_principal = principal;
+    Ok(())
+}
+
+/// Make sure the escrow holds `amount`, redeeming the shortfall from the vault
+pub fn recall_escrow_principal(
+    stream: &AccountLoader<Stream>,
+    vault: &dyn YieldVault,
+    escrow: &AccountInfo,
+    amount: u64,
+) -> Result<()> {
+    let balance = token_balance(escrow)?;
+    if balance >= amount {
+        return Ok(());
+    }
+    redeem_escrow_principal(stream, vault, escrow, amount - balance)
+}
+
+/// Redeem the yield earned above the deposited principal into the escrow,
+/// returning the amount redeemed
+pub fn redeem_escrow_yield(
+    stream: &AccountLoader<Stream>,
+    vault: &dyn YieldVault,
+    escrow: &AccountInfo,
+) -> Result<u64> {
+    let principal = stream.load()?.escrow_yield.deposited_principal;
+    let earned = vault.position_value()?.saturating_sub(principal);
+    if earned > 0 {
+        move_through_vault(escrow, earned, true, || vault.redeem(earned))?;
+    }
+    Ok(earned)
+}
+
+/// Redeem the whole position ahead of settling a stream and adjust the payouts
+/// of the recipient and sender. Yield is split as agreed, principal lost by the
+/// vault comes out of the sender's refund first.
+pub fn settle_escrow_yield(
+    stream: &AccountLoader<Stream>,
+    vault: &dyn YieldVault,
+    escrow: &AccountInfo,
+    to_recipient: u64,
+    to_sender: u64,
+) -> Result<(u64, u64)> {
+    let value = vault.position_value()?;
+    if value > 0 {
+        move_through_vault(escrow, value, true, || vault.redeem(value))?;
+    }
+
+    let stream = &mut *stream.load_mut()?;
+    let principal = stream.escrow_yield.deposited_principal;
+    let earned = value.saturating_sub(principal);
+    let lost = principal.saturating_sub(value);
+    let recipient_loss = lost.saturating_sub(to_sender);
+    let (recipient_yield, sender_yield) = stream.escrow_yield.split_yield(earned);
+
+    stream.escrow_yield.deposited_principal = 0;
+    stream.escrow_yield.harvested_yield = stream
+        .escrow_yield
+        .harvested_yield
+        .checked_add(earned)
+        .ok_or(StreamFlowError::ArithmeticOverflow)?;
+
+    Ok((
+        to_recipient.saturating_sub(recipient_loss) + recipient_yield,
+        to_sender - (lost - recipient_loss) + sender_yield,
+    ))
+}
@@ -0,0 +1,310 @@
+//! Compute units and rent of the main stream instructions, for streams created
+//! with both `create_stream` and `create_vesting_stream`.
+//!
+//! Create, withdraw and cancel must each fit the default compute budget of an
+//! instruction, and the stream account must be exactly `Stream::LEN` bytes. Runs
+//! against the SBF build of the program, since natively processed programs are not
+//! metered, and is ignored by default:
+//!
+//! ```text
+//! cargo test-sbf --test compute_units -- --ignored
+//! ```
+//!
+//! The Borsh `Stream` replaced by the zero-copy layout was allocated with
+//! `8 + size_of::<Stream>()`, which came to 2_096 bytes and 15_479_040 lamports of
+//! rent, the same as `Stream::LEN` today. There are no compute figures for it,
+//! since the program did not build before the switch.
+
+use anchor_lang::prelude::*;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use streamflow::state::constants::{MAX_VESTING_SEGMENTS, RATE_PRECISION};
+use streamflow::state::{AccelerationClause, LeaverPolicy, Stream, VestingSegment};
+
+mod common;
+
+use common::{
+    create_mint, create_token_account, escrow_address, process_metered, streamflow_instruction,
+    warp_to,
+};
+
+const GRANT: u64 = 1_000_000;
+const DURATION: u64 = 1_000;
+
+/// Default compute budget of a single instruction
+const INSTRUCTION_COMPUTE_LIMIT: u64 = 200_000;
+
+/// Mint, funded sender and recipient shared by the benchmarks
+struct Setup {
+    context: ProgramTestContext,
+    mint: Pubkey,
+    sender: Keypair,
+    recipient: Keypair,
+    sender_tokens: Pubkey,
+    recipient_tokens: Pubkey,
+}
+
+async fn setup() -> Setup {
+    let mut program_test = ProgramTest::new("streamflow", streamflow::ID, None);
+    program_test.prefer_bpf(true);
+    let mut context = program_test.start_with_context().await;
+
+    let mint = create_mint(&mut context).await;
+
+    let sender = Keypair::new();
+    let recipient = Keypair::new();
+    let sender_tokens = create_token_account(&mut context, &mint, &sender.pubkey()).await;
+    let recipient_tokens = create_token_account(&mut context, &mint, &recipient.pubkey()).await;
+
+    let payer = context.payer.pubkey();
+    let instructions = [
+        system_instruction::transfer(&payer, &sender.pubkey(), 1_000_000_000),
+        spl_token::instruction::mint_to(&spl_token::ID, &mint, &sender_tokens, &payer, &[], GRANT)
+            .unwrap(),
+    ];
+    process_metered(&mut context, &instructions, &[]).await;
+
+    Setup {
+        context,
+        mint,
+        sender,
+        recipient,
+        sender_tokens,
+        recipient_tokens,
+    }
+}
+
+/// Withdraw a quarter of the grant, cancel half way through and check the figures
+async fn withdraw_cancel_and_check(
+    setup: &mut Setup,
+    name: &str,
+    stream: Pubkey,
+    start_time: i64,
+    create_units: u64,
+) {
+    let escrow = escrow_address(&stream);
+
+    warp_to(&mut setup.context, start_time + DURATION as i64 / 4).await;
+    let instruction = streamflow_instruction(
+        streamflow::accounts::Withdraw {
+            stream,
+            recipient: setup.recipient.pubkey(),
+            escrow_token_account: escrow,
+            recipient_token_account: setup.recipient_tokens,
+            position_token_account: None,
+            lender_token_account: None,
+            yield_vault_program: None,
+            yield_vault: None,
+            yield_vault_token_account: None,
+            token_program: spl_token::ID,
+        },
+        streamflow::instruction::Withdraw { amount: GRANT / 4 },
+    );
+    let withdraw_units = process_metered(&mut setup.context, &[instruction], &[&setup.recipient]).await;
+
+    warp_to(&mut setup.context, start_time + DURATION as i64 / 2).await;
+    let instruction = streamflow_instruction(
+        streamflow::accounts::CancelStream {
+            stream,
+            authority: setup.sender.pubkey(),
+            escrow_token_account: escrow,
+            sender_token_account: setup.sender_tokens,
+            recipient_token_account: setup.recipient_tokens,
+            position_token_account: None,
+            yield_vault_program: None,
+            yield_vault: None,
+            yield_vault_token_account: None,
+            token_program: spl_token::ID,
+        },
+        streamflow::instruction::CancelStream {},
+    );
+    let cancel_units = process_metered(&mut setup.context, &[instruction], &[&setup.sender]).await;
+
+    let account = setup
+        .context
+        .banks_client
+        .get_account(stream)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), Stream::LEN, "{}: stream account size", name);
+
+    for (instruction, units) in [
+        ("create", create_units),
+        ("withdraw", withdraw_units),
+        ("cancel", cancel_units),
+    ] {
+        assert!(
+            units <= INSTRUCTION_COMPUTE_LIMIT,
+            "{}: {} consumed {} compute units",
+            name,
+            instruction,
+            units
+        );
+    }
+}
+
+fn stream_address(sender: &Pubkey, stream_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"stream", sender.as_ref(), stream_id.as_ref()],
+        &streamflow::ID,
+    )
+    .0
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build, see the module docs"]
+async fn create_withdraw_cancel() {
+    let mut setup = setup().await;
+
+    let clock = setup
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    let start_time = clock.unix_timestamp + 10;
+    let stream_id = [4; 32];
+    let stream = stream_address(&setup.sender.pubkey(), &stream_id);
+
+    let instruction = streamflow_instruction(
+        streamflow::accounts::CreateStream {
+            stream,
+            sender: setup.sender.pubkey(),
+            sender_profile: None,
+            recipient: setup.recipient.pubkey(),
+            mint: setup.mint,
+            sender_token_account: setup.sender_tokens,
+            escrow_token_account: escrow_address(&stream),
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: solana_sdk::sysvar::rent::ID,
+        },
+        streamflow::instruction::CreateStream {
+            stream_id,
+            recipient: setup.recipient.pubkey(),
+            deposited_amount: GRANT,
+            start_time,
+            end_time: start_time + DURATION as i64,
+            cliff_time: None,
+            cancelable_by_sender: true,
+            cancelable_by_recipient: false,
+            cancel_notice_period: 0,
+            transferable_by_sender: false,
+            transferable_by_recipient: false,
+            automatic_withdrawal: false,
+            period: 1,
+        },
+    );
+    let create_units = process_metered(&mut setup.context, &[instruction], &[&setup.sender]).await;
+
+    withdraw_cancel_and_check(
+        &mut setup,
+        "create_stream",
+        stream,
+        start_time,
+        create_units,
+    )
+    .await;
+}
+
+/// Create a vesting stream over `segments`, then withdraw and cancel it
+async fn vesting_create_withdraw_cancel(
+    name: &str,
+    stream_id: [u8; 32],
+    segments: Vec<VestingSegment>,
+) {
+    let mut setup = setup().await;
+
+    let clock = setup
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap();
+    let start_time = clock.unix_timestamp + 10;
+    let stream = stream_address(&setup.sender.pubkey(), &stream_id);
+
+    let instruction = streamflow_instruction(
+        streamflow::accounts::CreateVestingStream {
+            stream,
+            escrow_token_account: escrow_address(&stream),
+            sender_token_account: setup.sender_tokens,
+            sender: setup.sender.pubkey(),
+            sender_profile: None,
+            mint: setup.mint,
+            approved_yield_vault: None,
+            yield_vault: None,
+            yield_vault_token_account: None,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: solana_sdk::sysvar::rent::ID,
+        },
+        streamflow::instruction::CreateVestingStream {
+            stream_id,
+            recipient: setup.recipient.pubkey(),
+            start_time,
+            cliff_time: start_time,
+            segments,
+            cancelable_by_sender: true,
+            cancelable_by_recipient: false,
+            cancel_notice_period: 0,
+            leaver_policy: LeaverPolicy::default(),
+            acceleration: AccelerationClause::default(),
+            stream_name: "benchmark".to_string(),
+            yield_share_bps: None,
+        },
+    );
+    let create_units = process_metered(&mut setup.context, &[instruction], &[&setup.sender]).await;
+
+    withdraw_cancel_and_check(&mut setup, name, stream, start_time, create_units).await;
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build, see the module docs"]
+async fn vesting_linear_create_withdraw_cancel() {
+    vesting_create_withdraw_cancel(
+        "create_vesting_stream, linear",
+        [5; 32],
+        vec![VestingSegment {
+            duration: DURATION,
+            amount: GRANT,
+            exponent: RATE_PRECISION as u32,
+            ..Default::default()
+        }],
+    )
+    .await;
+}
+
+/// Fractional exponents take the logarithm and exponential path of the curve, the
+/// most expensive one. Segments are 45 seconds long, with the last one taking the
+/// rest of the duration, so withdraw and cancel both land inside a segment.
+#[tokio::test]
+#[ignore = "needs the SBF build, see the module docs"]
+async fn vesting_max_segments_create_withdraw_cancel() {
+    let count = MAX_VESTING_SEGMENTS as u64;
+    let segments = (0..count)
+        .map(|index| VestingSegment {
+            duration: if index + 1 < count {
+                45
+            } else {
+                DURATION - 45 * (count - 1)
+            },
+            amount: GRANT / count,
+            exponent: if index % 2 == 0 {
+                (RATE_PRECISION / 3) as u32
+            } else {
+                (RATE_PRECISION * 5 / 2) as u32
+            },
+            ..Default::default()
+        })
+        .collect();
+
+    vesting_create_withdraw_cancel(
+        "create_vesting_stream, max segments",
+        [6; 32],
+        segments,
+    )
+    .await;
+}
@@ -0,0 +1,476 @@
+//! Escrow yield against a mock lending program.
+//!
+//! The mock implements the lending vault interface of `LendingVaultAdapter` for a
+//! single depositor: the position is worth everything in the vault token account,
+//! so yield is simulated by minting into it. Deposits spend the delegation the
+//! stream grants the vault, the stream never signs for the mock.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::program::{invoke_signed, set_return_data};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::AccountSerialize;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use streamflow::state::constants::RATE_PRECISION;
+use streamflow::state::{
+    AccelerationClause, ApprovedYieldVault, LeaverPolicy, Stream, VestingSegment, YieldAdapter,
+};
+
+mod common;
+
+use common::{
+    create_mint, create_token_account, escrow_address, fetch_account, mint_to, process,
+    process_streamflow, streamflow_instruction, token_balance, warp_to,
+};
+
+const MOCK_LENDING_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+const VAULT_SEED: &[u8] = b"vault";
+
+const GRANT: u64 = 1_000_000;
+const DURATION: u64 = 1_000;
+const YIELD: u64 = 100_000;
+const RECIPIENT_SHARE_BPS: u16 = 5_000;
+const LIEN: u64 = 10_000;
+
+fn discriminator(name: &str) -> [u8; 8] {
+    hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+fn process_mock_lending(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let [vault, vault_tokens, escrow, stream, token_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let (vault_address, bump) = Pubkey::find_program_address(&[VAULT_SEED], program_id);
+    if *vault.key != vault_address || data.len() < 8 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (tag, args) = data.split_at(8);
+
+    if tag == discriminator("position_value") {
+        let value = spl_token::state::Account::unpack(&vault_tokens.try_borrow_data()?)?.amount;
+        set_return_data(&value.to_le_bytes());
+        return Ok(());
+    }
+
+    let amount = u64::from_le_bytes(
+        args.try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    );
+    if tag == discriminator("deposit") {
+        if stream.is_signer {
+            return Err(ProgramError::InvalidArgument);
+        }
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                escrow.key,
+                vault_tokens.key,
+                vault.key,
+                &[],
+                amount,
+            )?,
+            &[escrow.clone(), vault_tokens.clone(), vault.clone(), token_program.clone()],
+            &[&[VAULT_SEED, &[bump]]],
+        )
+    } else if tag == discriminator("redeem") {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                vault_tokens.key,
+                escrow.key,
+                vault.key,
+                &[],
+                amount,
+            )?,
+            &[vault_tokens.clone(), escrow.clone(), vault.clone(), token_program.clone()],
+            &[&[VAULT_SEED, &[bump]]],
+        )
+    } else {
+        Err(ProgramError::InvalidInstructionData)
+    }
+}
+
+struct Setup {
+    context: ProgramTestContext,
+    mint: Pubkey,
+    sender: Keypair,
+    sender_tokens: Pubkey,
+    recipient: Keypair,
+    recipient_tokens: Pubkey,
+    vault: Pubkey,
+    vault_tokens: Pubkey,
+}
+
+async fn setup() -> Setup {
+    let mut program_test =
+        ProgramTest::new("streamflow", streamflow::ID, processor!(process_streamflow));
+    program_test.add_program("mock_lending", MOCK_LENDING_ID, processor!(process_mock_lending));
+
+    // The vault state account is the token authority of the vault
+    let vault = Pubkey::find_program_address(&[VAULT_SEED], &MOCK_LENDING_ID).0;
+    program_test.add_account(
+        vault,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            owner: MOCK_LENDING_ID,
+            ..SolanaAccount::default()
+        },
+    );
+
+    // Approving needs the upgradeable loader, so the approval is preloaded
+    let (approval, bump) = approved_yield_vault_address();
+    let mut data = Vec::new();
+    ApprovedYieldVault {
+        vault_program: MOCK_LENDING_ID,
+        adapter: YieldAdapter::LendingVault as u8,
+        approved_at: 0,
+        bump,
+    }
+    .try_serialize(&mut data)
+    .unwrap();
+    program_test.add_account(
+        approval,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            data,
+            owner: streamflow::ID,
+            ..SolanaAccount::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let mint = create_mint(&mut context).await;
+
+    let sender = Keypair::new();
+    let recipient = Keypair::new();
+    let sender_tokens = create_token_account(&mut context, &mint, &sender.pubkey()).await;
+    let recipient_tokens = create_token_account(&mut context, &mint, &recipient.pubkey()).await;
+    let vault_tokens = create_token_account(&mut context, &mint, &vault).await;
+
+    let payer = context.payer.pubkey();
+    let instruction = system_instruction::transfer(&payer, &sender.pubkey(), 1_000_000_000);
+    process(&mut context, &[instruction], &[]).await.unwrap();
+    mint_to(&mut context, &mint, &sender_tokens, GRANT).await;
+
+    Setup {
+        context,
+        mint,
+        sender,
+        sender_tokens,
+        recipient,
+        recipient_tokens,
+        vault,
+        vault_tokens,
+    }
+}
+
+fn approved_yield_vault_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"yield_vault", MOCK_LENDING_ID.as_ref()], &streamflow::ID)
+}
+
+/// Create a linear stream of `GRANT` over `DURATION` with escrow yield enabled,
+/// accepted by the recipient when `accept` is set
+async fn create_yield_stream(setup: &mut Setup, start_time: i64, accept: bool) -> Pubkey {
+    let sender = setup.sender.pubkey();
+    let stream_id = [3; 32];
+    let stream = Pubkey::find_program_address(
+        &[b"stream", sender.as_ref(), stream_id.as_ref()],
+        &streamflow::ID,
+    )
+    .0;
+
+    let create = streamflow_instruction(
+        streamflow::accounts::CreateVestingStream {
+            stream,
+            escrow_token_account: escrow_address(&stream),
+            sender_token_account: setup.sender_tokens,
+            sender,
+            sender_profile: None,
+            mint: setup.mint,
+            approved_yield_vault: Some(approved_yield_vault_address().0),
+            yield_vault: Some(setup.vault),
+            yield_vault_token_account: Some(setup.vault_tokens),
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: solana_sdk::sysvar::rent::ID,
+        },
+        streamflow::instruction::CreateVestingStream {
+            stream_id,
+            recipient: setup.recipient.pubkey(),
+            start_time,
+            cliff_time: start_time,
+            segments: vec![VestingSegment {
+                duration: DURATION,
+                amount: GRANT,
+                exponent: RATE_PRECISION as u32,
+                ..Default::default()
+            }],
+            cancelable_by_sender: true,
+            cancelable_by_recipient: false,
+            cancel_notice_period: 0,
+            leaver_policy: LeaverPolicy::default(),
+            acceleration: AccelerationClause::default(),
+            stream_name: "yield".to_string(),
+            yield_share_bps: Some(RECIPIENT_SHARE_BPS),
+        },
+    );
+    process(&mut setup.context, &[create], &[&setup.sender])
+        .await
+        .unwrap();
+
+    if accept {
+        let instruction = streamflow_instruction(
+            streamflow::accounts::AcceptEscrowYield {
+                stream,
+                recipient: setup.recipient.pubkey(),
+            },
+            streamflow::instruction::AcceptEscrowYield {},
+        );
+        process(&mut setup.context, &[instruction], &[&setup.recipient])
+            .await
+            .unwrap();
+    }
+
+    stream
+}
+
+fn rebalance(setup: &Setup, stream: &Pubkey) -> Instruction {
+    streamflow_instruction(
+        streamflow::accounts::RebalanceEscrowYield {
+            stream: *stream,
+            escrow_token_account: escrow_address(stream),
+            approved_yield_vault: approved_yield_vault_address().0,
+            vault_program: MOCK_LENDING_ID,
+            vault: setup.vault,
+            vault_token_account: setup.vault_tokens,
+            token_program: spl_token::ID,
+        },
+        streamflow::instruction::RebalanceEscrowYield {},
+    )
+}
+
+fn withdraw(setup: &Setup, stream: &Pubkey, amount: u64, with_vault: bool) -> Instruction {
+    streamflow_instruction(
+        streamflow::accounts::Withdraw {
+            stream: *stream,
+            recipient: setup.recipient.pubkey(),
+            escrow_token_account: escrow_address(stream),
+            recipient_token_account: setup.recipient_tokens,
+            position_token_account: None,
+            lender_token_account: None,
+            yield_vault_program: with_vault.then_some(MOCK_LENDING_ID),
+            yield_vault: with_vault.then_some(setup.vault),
+            yield_vault_token_account: with_vault.then_some(setup.vault_tokens),
+            token_program: spl_token::ID,
+        },
+        streamflow::instruction::Withdraw { amount },
+    )
+}
+
+#[tokio::test]
+async fn unvested_escrow_earns_yield_until_cancel() {
+    let mut setup = setup().await;
+    let clock = setup.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let start_time = clock.unix_timestamp + 10;
+    let stream = create_yield_stream(&mut setup, start_time, true).await;
+    let escrow = escrow_address(&stream);
+
+    // Before the start everything is unvested and moves into the vault
+    let instruction = rebalance(&setup, &stream);
+    process(&mut setup.context, &[instruction], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut setup.context, &escrow).await, 0);
+    assert_eq!(
+        fetch_account::<Stream>(&mut setup.context, &stream)
+            .await
+            .escrow_yield
+            .deposited_principal,
+        GRANT
+    );
+
+    let (mint, vault_tokens) = (setup.mint, setup.vault_tokens);
+    mint_to(&mut setup.context, &mint, &vault_tokens, YIELD).await;
+
+    // A quarter unlocked, pulled back from the vault just in time
+    warp_to(&mut setup.context, start_time + DURATION as i64 / 4).await;
+    let instruction = withdraw(&setup, &stream, GRANT / 4, true);
+    process(&mut setup.context, &[instruction], &[&setup.recipient])
+        .await
+        .unwrap();
+
+    let withdrawn = fetch_account::<Stream>(&mut setup.context, &stream).await;
+    assert_eq!(withdrawn.withdrawn_amount, GRANT / 4);
+    assert_eq!(withdrawn.escrow_yield.deposited_principal, GRANT * 3 / 4);
+    assert_eq!(
+        token_balance(&mut setup.context, &setup.recipient_tokens).await,
+        GRANT / 4
+    );
+
+    // Cancelling at half splits the yield and settles the principal exactly
+    warp_to(&mut setup.context, start_time + DURATION as i64 / 2).await;
+    let instruction = streamflow_instruction(
+        streamflow::accounts::CancelStream {
+            stream,
+            authority: setup.sender.pubkey(),
+            escrow_token_account: escrow,
+            sender_token_account: setup.sender_tokens,
+            recipient_token_account: setup.recipient_tokens,
+            position_token_account: None,
+            yield_vault_program: Some(MOCK_LENDING_ID),
+            yield_vault: Some(setup.vault),
+            yield_vault_token_account: Some(setup.vault_tokens),
+            token_program: spl_token::ID,
+        },
+        streamflow::instruction::CancelStream {},
+    );
+    process(&mut setup.context, &[instruction], &[&setup.sender])
+        .await
+        .unwrap();
+
+    let recipient_yield = YIELD * RECIPIENT_SHARE_BPS as u64 / 10_000;
+    assert_eq!(
+        token_balance(&mut setup.context, &setup.recipient_tokens).await,
+        GRANT / 2 + recipient_yield
+    );
+    assert_eq!(
+        token_balance(&mut setup.context, &setup.sender_tokens).await,
+        GRANT / 2 + (YIELD - recipient_yield)
+    );
+    assert_eq!(token_balance(&mut setup.context, &setup.vault_tokens).await, 0);
+    assert_eq!(token_balance(&mut setup.context, &escrow).await, 0);
+
+    let cancelled = fetch_account::<Stream>(&mut setup.context, &stream).await;
+    assert_eq!(cancelled.withdrawn_amount, GRANT / 2);
+    assert_eq!(cancelled.escrow_yield.harvested_yield, YIELD);
+}
+
+#[tokio::test]
+async fn withdrawal_needs_the_vault_while_principal_is_deposited() {
+    let mut setup = setup().await;
+    let clock = setup.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let start_time = clock.unix_timestamp + 10;
+    let stream = create_yield_stream(&mut setup, start_time, true).await;
+
+    let instruction = rebalance(&setup, &stream);
+    process(&mut setup.context, &[instruction], &[]).await.unwrap();
+
+    warp_to(&mut setup.context, start_time + DURATION as i64).await;
+    let instruction = withdraw(&setup, &stream, GRANT, false);
+    assert!(process(&mut setup.context, &[instruction], &[&setup.recipient])
+        .await
+        .is_err());
+
+    let instruction = withdraw(&setup, &stream, GRANT, true);
+    process(&mut setup.context, &[instruction], &[&setup.recipient])
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut setup.context, &setup.recipient_tokens).await,
+        GRANT
+    );
+}
+
+#[tokio::test]
+async fn nothing_is_deposited_before_the_recipient_accepts() {
+    let mut setup = setup().await;
+    let clock = setup.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let start_time = clock.unix_timestamp + 10;
+    let stream = create_yield_stream(&mut setup, start_time, false).await;
+
+    let instruction = rebalance(&setup, &stream);
+    process(&mut setup.context, &[instruction], &[]).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.context, &escrow_address(&stream)).await,
+        GRANT
+    );
+    assert_eq!(
+        fetch_account::<Stream>(&mut setup.context, &stream)
+            .await
+            .escrow_yield
+            .deposited_principal,
+        0
+    );
+}
+
+#[tokio::test]
+async fn harvest_repays_the_lien_before_the_recipient() {
+    let mut setup = setup().await;
+    let clock = setup.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let start_time = clock.unix_timestamp + 10;
+    let stream = create_yield_stream(&mut setup, start_time, true).await;
+
+    let lender = Keypair::new();
+    let mint = setup.mint;
+    let lender_tokens = create_token_account(&mut setup.context, &mint, &lender.pubkey()).await;
+    let instruction = streamflow_instruction(
+        streamflow::accounts::CreateLien {
+            stream,
+            recipient: setup.recipient.pubkey(),
+            sender: Some(setup.sender.pubkey()),
+            lender_token_account: lender_tokens,
+            compliance_registry: None,
+        },
+        streamflow::instruction::CreateLien {
+            lender: lender.pubkey(),
+            amount: LIEN,
+            compliance_proof: Vec::new(),
+        },
+    );
+    process(
+        &mut setup.context,
+        &[instruction],
+        &[&setup.recipient, &setup.sender],
+    )
+    .await
+    .unwrap();
+
+    let instruction = rebalance(&setup, &stream);
+    process(&mut setup.context, &[instruction], &[]).await.unwrap();
+    let vault_tokens = setup.vault_tokens;
+    mint_to(&mut setup.context, &mint, &vault_tokens, YIELD).await;
+
+    let instruction = streamflow_instruction(
+        streamflow::accounts::HarvestEscrowYield {
+            stream,
+            escrow_token_account: escrow_address(&stream),
+            sender_token_account: setup.sender_tokens,
+            recipient_token_account: setup.recipient_tokens,
+            position_token_account: None,
+            lender_token_account: Some(lender_tokens),
+            vault_program: MOCK_LENDING_ID,
+            vault: setup.vault,
+            vault_token_account: setup.vault_tokens,
+            token_program: spl_token::ID,
+        },
+        streamflow::instruction::HarvestEscrowYield {},
+    );
+    process(&mut setup.context, &[instruction], &[]).await.unwrap();
+
+    let recipient_yield = YIELD * RECIPIENT_SHARE_BPS as u64 / 10_000;
+    assert_eq!(token_balance(&mut setup.context, &lender_tokens).await, LIEN);
+    assert_eq!(
+        token_balance(&mut setup.context, &setup.recipient_tokens).await,
+        recipient_yield - LIEN
+    );
+    assert_eq!(
+        token_balance(&mut setup.context, &setup.sender_tokens).await,
+        YIELD - recipient_yield
+    );
+    assert_eq!(
+        fetch_account::<Stream>(&mut setup.context, &stream)
+            .await
+            .lien
+            .outstanding(),
+        0
+    );
+}
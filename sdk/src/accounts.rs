@@ -0,0 +1,45 @@
+//! Deserializers for StreamFlow program accounts
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::{AccountDeserialize, Discriminator};
+
+use crate::error::{Result, SdkError};
+use crate::{ComplianceRegistry, SenderProfile, Stream, Treasury, TreasuryAuditLog};
+
+/// Deserialize raw `Stream` account data, discriminator included.
+///
+/// `Stream` is a zero-copy account, so its bytes are copied out rather than
+/// borrowed: buffers returned by RPC carry no alignment guarantee.
+pub fn deserialize_stream(data: &[u8]) -> Result<Stream> {
+    if data.len() < 8 || data[..8] != Stream::DISCRIMINATOR {
+        return Err(SdkError::Program(ErrorCode::AccountDiscriminatorMismatch.into()));
+    }
+    if data.len() != Stream::LEN {
+        return Err(SdkError::Program(ErrorCode::AccountDidNotDeserialize.into()));
+    }
+    Ok(bytemuck::pod_read_unaligned(&data[8..]))
+}
+
+/// Deserialize raw `Treasury` account data, discriminator included
+pub fn deserialize_treasury(data: &[u8]) -> Result<Treasury> {
+    deserialize(data)
+}
+
+/// Deserialize raw `TreasuryAuditLog` account data, discriminator included
+pub fn deserialize_treasury_audit_log(data: &[u8]) -> Result<TreasuryAuditLog> {
+    deserialize(data)
+}
+
+/// Deserialize raw `ComplianceRegistry` account data, discriminator included
+pub fn deserialize_compliance_registry(data: &[u8]) -> Result<ComplianceRegistry> {
+    deserialize(data)
+}
+
+/// Deserialize raw `SenderProfile` account data, discriminator included
+pub fn deserialize_sender_profile(data: &[u8]) -> Result<SenderProfile> {
+    deserialize(data)
+}
+
+fn deserialize<T: AccountDeserialize>(mut data: &[u8]) -> Result<T> {
+    Ok(T::try_deserialize(&mut data)?)
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::Pubkey;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SdkError {
+    #[error("program error: {0}")]
+    Program(#[from] anchor_lang::error::Error),
+
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("invalid return data: {0}")]
+    ReturnData(String),
+
+    #[error("audit entries from {requested} were overwritten, oldest kept is {oldest}")]
+    AuditLogGap { requested: u64, oldest: u64 },
+
+    #[error("invalid sysvar account {0}")]
+    InvalidSysvar(Pubkey),
+
+    #[cfg(feature = "rpc")]
+    #[error("rpc error: {0}")]
+    Rpc(#[from] solana_client::client_error::ClientError),
+}
+
+pub type Result<T> = std::result::Result<T, SdkError>;
@@ -0,0 +1,1024 @@
+//! Typed builders for every StreamFlow instruction.
+//!
+//! Instruction arguments are the structs Anchor generates in `streamflow::instruction`
+//! (re-exported as `crate::args`). Accounts are derived wherever the program derives
+//! them; sender and recipient token accounts are their associated token accounts.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{bpf_loader_upgradeable, ed25519_program, system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::metadata::mpl_token_metadata;
+use anchor_spl::token;
+use streamflow::accounts;
+use streamflow::state::vesting::{AccelerationTrigger, TerminationKind};
+
+use crate::args;
+use crate::pda::*;
+use crate::{
+    milestone_approval_message, AccessLevel, ComplianceMode, Stream, StreamTerms, YieldAdapter,
+};
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: streamflow::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn sender_profile(sender: &Pubkey, use_sender_profile: bool) -> Option<Pubkey> {
+    use_sender_profile.then(|| find_sender_profile_address(sender).0)
+}
+
+/// Program data account of the upgradeable program, holding its upgrade authority
+fn program_data() -> Pubkey {
+    Pubkey::find_program_address(&[streamflow::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+fn compliance_registry(stream: &Stream) -> Option<Pubkey> {
+    stream
+        .has_compliance_registry()
+        .then_some(stream.compliance_registry)
+}
+
+fn yield_vault(stream: &Stream) -> [Option<Pubkey>; 3] {
+    let config = &stream.escrow_yield;
+    if !config.is_enabled() {
+        return [None; 3];
+    }
+    [
+        Some(config.vault_program),
+        Some(config.vault),
+        Some(config.vault_token_account),
+    ]
+}
+
+/// Account the recipient side of a payout is made to, following the rules of
+/// `withdraw`, and the position token account proving the holder of a tokenized
+/// stream. `position_holder` is only used for tokenized streams and defaults to the
+/// recipient.
+fn recipient_payout(stream: &Stream, position_holder: Option<&Pubkey>) -> (Pubkey, Option<Pubkey>) {
+    if stream.withdraw_destination != Pubkey::default() {
+        return (stream.withdraw_destination, None);
+    }
+    if !stream.has_position_token() {
+        return (get_associated_token_address(&stream.recipient, &stream.mint), None);
+    }
+
+    let holder = position_holder.unwrap_or(&stream.recipient);
+    (
+        get_associated_token_address(holder, &stream.mint),
+        Some(get_associated_token_address(holder, &stream.position_mint)),
+    )
+}
+
+fn treasury_audit_log(treasury: &Pubkey, has_audit_log: bool) -> Option<Pubkey> {
+    has_audit_log.then(|| find_treasury_audit_log_address(treasury).0)
+}
+
+/// Create a linear stream. Pass `use_sender_profile` when `args.stream_id` is the
+/// next nonce id of the sender profile.
+pub fn create_stream(
+    sender: &Pubkey,
+    mint: &Pubkey,
+    use_sender_profile: bool,
+    args: args::CreateStream,
+) -> Instruction {
+    let stream = find_stream_address(sender, &args.stream_id).0;
+
+    instruction(
+        accounts::CreateStream {
+            stream,
+            sender: *sender,
+            sender_profile: sender_profile(sender, use_sender_profile),
+            recipient: args.recipient,
+            mint: *mint,
+            sender_token_account: get_associated_token_address(sender, mint),
+            escrow_token_account: find_escrow_address(&stream).0,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        args,
+    )
+}
+
+/// Withdraw `amount` from `stream` into `destination`, signed by `authority`. While
+/// the stream has a lien the lender is repaid first.
+pub fn withdraw(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    authority: &Pubkey,
+    destination: &Pubkey,
+    position_token_account: Option<Pubkey>,
+    amount: u64,
+) -> Instruction {
+    let [yield_vault_program, yield_vault, yield_vault_token_account] = yield_vault(stream);
+    instruction(
+        accounts::Withdraw {
+            stream: *stream_address,
+            recipient: *authority,
+            escrow_token_account: find_escrow_address(stream_address).0,
+            recipient_token_account: *destination,
+            position_token_account,
+            lender_token_account: stream
+                .has_lien()
+                .then_some(stream.lien.lender_token_account),
+            yield_vault_program,
+            yield_vault,
+            yield_vault_token_account,
+            token_program: token::ID,
+        },
+        args::Withdraw { amount },
+    )
+}
+
+/// Cancel `stream`, signed by its sender or recipient. `position_holder` is the
+/// owner of the position token of a tokenized stream.
+pub fn cancel_stream(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    authority: &Pubkey,
+    position_holder: Option<&Pubkey>,
+) -> Instruction {
+    let [yield_vault_program, yield_vault, yield_vault_token_account] = yield_vault(stream);
+    let (recipient_token_account, position_token_account) =
+        recipient_payout(stream, position_holder);
+    instruction(
+        accounts::CancelStream {
+            stream: *stream_address,
+            authority: *authority,
+            escrow_token_account: find_escrow_address(stream_address).0,
+            sender_token_account: get_associated_token_address(&stream.sender, &stream.mint),
+            recipient_token_account,
+            position_token_account,
+            yield_vault_program,
+            yield_vault,
+            yield_vault_token_account,
+            token_program: token::ID,
+        },
+        args::CancelStream {},
+    )
+}
+
+/// Add `amount` to `stream`, extending its end time at the current rate
+pub fn topup_stream(stream_address: &Pubkey, stream: &Stream, amount: u64) -> Instruction {
+    instruction(
+        accounts::TopupStream {
+            stream: *stream_address,
+            escrow_token_account: find_escrow_address(stream_address).0,
+            sender_token_account: get_associated_token_address(&stream.sender, &stream.mint),
+            sender: stream.sender,
+            token_program: token::ID,
+        },
+        args::TopupStream { amount },
+    )
+}
+
+/// Move `stream` to `new_recipient`. `compliance_proof` is only needed when the
+/// stream is bound to a Merkle allowlist registry.
+pub fn transfer_stream(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    authority: &Pubkey,
+    new_recipient: Pubkey,
+    compliance_proof: Vec<[u8; 32]>,
+) -> Instruction {
+    instruction(
+        accounts::TransferStream {
+            stream: *stream_address,
+            authority: *authority,
+            compliance_registry: compliance_registry(stream),
+        },
+        args::TransferStream {
+            new_recipient,
+            compliance_proof,
+        },
+    )
+}
+
+/// Update the mutable flags of `stream`
+pub fn update_stream(stream: &Pubkey, sender: &Pubkey, args: args::UpdateStream) -> Instruction {
+    instruction(
+        accounts::UpdateStream {
+            stream: *stream,
+            sender: *sender,
+        },
+        args,
+    )
+}
+
+/// Create the treasury of `authority`
+pub fn create_treasury(authority: &Pubkey, args: args::CreateTreasury) -> Instruction {
+    instruction(
+        accounts::CreateTreasury {
+            treasury: find_treasury_address(authority).0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+/// Start recording operations on the treasury of `authority`
+pub fn init_treasury_audit_log(authority: &Pubkey) -> Instruction {
+    let treasury = find_treasury_address(authority).0;
+    instruction(
+        accounts::InitTreasuryAuditLog {
+            treasury,
+            audit_log: find_treasury_audit_log_address(&treasury).0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        args::InitTreasuryAuditLog {},
+    )
+}
+
+/// Record `stream` in the treasury of `authority`. `has_audit_log` and
+/// `compliance_registry` must match the treasury.
+pub fn add_stream_to_treasury(
+    authority: &Pubkey,
+    stream: &Pubkey,
+    has_audit_log: bool,
+    compliance_registry: Option<Pubkey>,
+    compliance_proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let treasury = find_treasury_address(authority).0;
+    instruction(
+        accounts::AddStreamToTreasury {
+            treasury,
+            stream: *stream,
+            authority: *authority,
+            audit_log: treasury_audit_log(&treasury, has_audit_log),
+            compliance_registry,
+        },
+        args::AddStreamToTreasury { compliance_proof },
+    )
+}
+
+/// Set the access level of `manager` in the treasury of `authority`
+pub fn set_treasury_manager(
+    authority: &Pubkey,
+    manager: &Pubkey,
+    access_level: AccessLevel,
+    has_audit_log: bool,
+) -> Instruction {
+    instruction(
+        manage_treasury_accounts(authority, has_audit_log),
+        args::SetTreasuryManager {
+            manager: *manager,
+            access_level,
+        },
+    )
+}
+
+/// Remove `manager` from the treasury of `authority`
+pub fn remove_treasury_manager(
+    authority: &Pubkey,
+    manager: &Pubkey,
+    has_audit_log: bool,
+) -> Instruction {
+    instruction(
+        manage_treasury_accounts(authority, has_audit_log),
+        args::RemoveTreasuryManager { manager: *manager },
+    )
+}
+
+/// Replace the spending limits of the treasury of `authority`
+pub fn set_spending_limits(
+    authority: &Pubkey,
+    has_audit_log: bool,
+    args: args::SetSpendingLimits,
+) -> Instruction {
+    instruction(manage_treasury_accounts(authority, has_audit_log), args)
+}
+
+fn manage_treasury_accounts(authority: &Pubkey, has_audit_log: bool) -> accounts::ManageTreasury {
+    let treasury = find_treasury_address(authority).0;
+    accounts::ManageTreasury {
+        treasury,
+        authority: *authority,
+        audit_log: treasury_audit_log(&treasury, has_audit_log),
+    }
+}
+
+/// Deposit `amount` of `mint` from the associated token account of `depositor`
+/// into the vault of the treasury of `authority`. The vault is the associated
+/// token account of the treasury and must exist.
+pub fn deposit_to_treasury(
+    authority: &Pubkey,
+    depositor: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    has_audit_log: bool,
+) -> Instruction {
+    let treasury = find_treasury_address(authority).0;
+    instruction(
+        accounts::DepositToTreasury {
+            treasury,
+            treasury_vault: get_associated_token_address(&treasury, mint),
+            depositor: *depositor,
+            depositor_token_account: get_associated_token_address(depositor, mint),
+            mint: *mint,
+            audit_log: treasury_audit_log(&treasury, has_audit_log),
+            token_program: token::ID,
+        },
+        args::DepositToTreasury { amount },
+    )
+}
+
+/// Withdraw `amount` of `mint` from the treasury vault of `authority` into
+/// `destination`
+pub fn withdraw_from_treasury(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+    has_audit_log: bool,
+) -> Instruction {
+    let treasury = find_treasury_address(authority).0;
+    instruction(
+        accounts::WithdrawFromTreasury {
+            treasury,
+            treasury_vault: get_associated_token_address(&treasury, mint),
+            authority: *authority,
+            destination_token_account: *destination,
+            mint: *mint,
+            audit_log: treasury_audit_log(&treasury, has_audit_log),
+            token_program: token::ID,
+        },
+        args::WithdrawFromTreasury { amount },
+    )
+}
+
+/// Create a linear stream funded by the treasury of `authority`, signed by the
+/// authority or one of its managers. `compliance_registry` must match the treasury.
+pub fn create_treasury_stream(
+    authority: &Pubkey,
+    manager: &Pubkey,
+    mint: &Pubkey,
+    has_audit_log: bool,
+    compliance_registry: Option<Pubkey>,
+    args: args::CreateTreasuryStream,
+) -> Instruction {
+    let treasury = find_treasury_address(authority).0;
+    let stream = find_stream_address(authority, &args.stream_id).0;
+    instruction(
+        accounts::CreateTreasuryStream {
+            treasury,
+            treasury_vault: get_associated_token_address(&treasury, mint),
+            stream,
+            escrow_token_account: find_escrow_address(&stream).0,
+            manager: *manager,
+            mint: *mint,
+            audit_log: treasury_audit_log(&treasury, has_audit_log),
+            compliance_registry,
+            token_program: token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        args,
+    )
+}
+
+/// Create the compliance registry maintained by `authority`
+pub fn create_compliance_registry(authority: &Pubkey, mode: ComplianceMode) -> Instruction {
+    instruction(
+        accounts::CreateComplianceRegistry {
+            registry: find_compliance_registry_address(authority).0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        args::CreateComplianceRegistry { mode },
+    )
+}
+
+/// Add and remove listed accounts of the registry of `authority`
+pub fn update_compliance_entries(
+    authority: &Pubkey,
+    add: Vec<Pubkey>,
+    remove: Vec<Pubkey>,
+) -> Instruction {
+    instruction(
+        accounts::UpdateComplianceRegistry {
+            registry: find_compliance_registry_address(authority).0,
+            authority: *authority,
+        },
+        args::UpdateComplianceEntries { add, remove },
+    )
+}
+
+/// Replace the Merkle root of approved accounts of the registry of `authority`
+pub fn set_compliance_merkle_root(authority: &Pubkey, merkle_root: [u8; 32]) -> Instruction {
+    instruction(
+        accounts::UpdateComplianceRegistry {
+            registry: find_compliance_registry_address(authority).0,
+            authority: *authority,
+        },
+        args::SetComplianceMerkleRoot { merkle_root },
+    )
+}
+
+/// Bind the treasury of `authority` to `registry`, or unbind it with `None`
+pub fn set_treasury_compliance_registry(
+    authority: &Pubkey,
+    registry: Option<Pubkey>,
+    has_audit_log: bool,
+) -> Instruction {
+    let treasury = find_treasury_address(authority).0;
+    instruction(
+        accounts::SetTreasuryComplianceRegistry {
+            treasury,
+            authority: *authority,
+            registry,
+            audit_log: treasury_audit_log(&treasury, has_audit_log),
+        },
+        args::SetTreasuryComplianceRegistry {},
+    )
+}
+
+/// Freeze or unfreeze withdrawals of `stream`, signed by the compliance authority
+/// of its registry
+pub fn freeze_stream_withdrawals(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    authority: &Pubkey,
+    frozen: bool,
+) -> Instruction {
+    instruction(
+        accounts::FreezeStreamWithdrawals {
+            stream: *stream_address,
+            registry: stream.compliance_registry,
+            authority: *authority,
+        },
+        args::FreezeStreamWithdrawals { frozen },
+    )
+}
+
+/// Approved yield vault a new vesting stream deposits its unvested balance in,
+/// paired with `yield_share_bps` in the creation arguments
+#[derive(Clone, Copy, Debug)]
+pub struct EscrowYieldVault {
+    pub vault_program: Pubkey,
+    pub vault: Pubkey,
+    pub vault_token_account: Pubkey,
+}
+
+fn create_vesting_stream_accounts(
+    sender: &Pubkey,
+    mint: &Pubkey,
+    stream_id: &[u8; 32],
+    use_sender_profile: bool,
+    escrow_yield: Option<&EscrowYieldVault>,
+) -> accounts::CreateVestingStream {
+    let stream = find_stream_address(sender, stream_id).0;
+
+    accounts::CreateVestingStream {
+        stream,
+        escrow_token_account: find_escrow_address(&stream).0,
+        sender_token_account: get_associated_token_address(sender, mint),
+        sender: *sender,
+        sender_profile: sender_profile(sender, use_sender_profile),
+        mint: *mint,
+        approved_yield_vault: escrow_yield
+            .map(|vault| find_approved_yield_vault_address(&vault.vault_program).0),
+        yield_vault: escrow_yield.map(|vault| vault.vault),
+        yield_vault_token_account: escrow_yield.map(|vault| vault.vault_token_account),
+        token_program: token::ID,
+        system_program: system_program::ID,
+        rent: sysvar::rent::ID,
+    }
+}
+
+/// Create a stream following custom vesting segments. With `yield_share_bps` set,
+/// `escrow_yield` names the approved vault the unvested balance is deposited in once
+/// the recipient accepts.
+pub fn create_vesting_stream(
+    sender: &Pubkey,
+    mint: &Pubkey,
+    use_sender_profile: bool,
+    escrow_yield: Option<&EscrowYieldVault>,
+    args: args::CreateVestingStream,
+) -> Instruction {
+    instruction(
+        create_vesting_stream_accounts(
+            sender,
+            mint,
+            &args.stream_id,
+            use_sender_profile,
+            escrow_yield,
+        ),
+        args,
+    )
+}
+
+/// Create a stream unlocking equal monthly tranches
+pub fn create_monthly_vesting_stream(
+    sender: &Pubkey,
+    mint: &Pubkey,
+    use_sender_profile: bool,
+    escrow_yield: Option<&EscrowYieldVault>,
+    args: args::CreateMonthlyVestingStream,
+) -> Instruction {
+    instruction(
+        create_vesting_stream_accounts(
+            sender,
+            mint,
+            &args.stream_id,
+            use_sender_profile,
+            escrow_yield,
+        ),
+        args,
+    )
+}
+
+/// Create a stream unlocking tranches as its attestor approves milestones
+pub fn create_milestone_stream(
+    sender: &Pubkey,
+    mint: &Pubkey,
+    use_sender_profile: bool,
+    escrow_yield: Option<&EscrowYieldVault>,
+    args: args::CreateMilestoneStream,
+) -> Instruction {
+    instruction(
+        create_vesting_stream_accounts(
+            sender,
+            mint,
+            &args.stream_id,
+            use_sender_profile,
+            escrow_yield,
+        ),
+        args,
+    )
+}
+
+/// Approve milestone `milestone_index` of `stream`. Must follow the instruction
+/// returned by `milestone_signature_verification` in the same transaction.
+pub fn approve_milestone(stream: &Pubkey, milestone_index: u8) -> Instruction {
+    instruction(
+        accounts::ApproveMilestone {
+            stream: *stream,
+            instructions: sysvar::instructions::ID,
+        },
+        args::ApproveMilestone { milestone_index },
+    )
+}
+
+/// Ed25519 program instruction verifying the attestor `signature` of
+/// `milestone_approval_message(stream, milestone_index, amount)`, produced
+/// off-chain by `attestor`
+pub fn milestone_signature_verification(
+    attestor: &Pubkey,
+    signature: &[u8; 64],
+    stream: &Pubkey,
+    milestone_index: u8,
+    amount: u64,
+) -> Instruction {
+    const DATA_START: u16 = 2 + 14;
+    const PUBLIC_KEY_OFFSET: u16 = DATA_START;
+    const SIGNATURE_OFFSET: u16 = PUBLIC_KEY_OFFSET + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+    const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+    let message = milestone_approval_message(stream, milestone_index, amount);
+
+    let mut data = vec![1, 0];
+    for field in [
+        SIGNATURE_OFFSET,
+        CURRENT_INSTRUCTION,
+        PUBLIC_KEY_OFFSET,
+        CURRENT_INSTRUCTION,
+        MESSAGE_OFFSET,
+        message.len() as u16,
+        CURRENT_INSTRUCTION,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(attestor.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(&message);
+
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// Terminate a vesting stream under its leaver policy. `position_holder` is the
+/// owner of the position token of a tokenized stream.
+pub fn terminate_vesting(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    position_holder: Option<&Pubkey>,
+    termination: TerminationKind,
+) -> Instruction {
+    let (recipient_token_account, position_token_account) =
+        recipient_payout(stream, position_holder);
+    instruction(
+        accounts::TerminateVesting {
+            stream: *stream_address,
+            sender: stream.sender,
+            sender_token_account: get_associated_token_address(&stream.sender, &stream.mint),
+            recipient_token_account,
+            position_token_account,
+            escrow_token_account: find_escrow_address(stream_address).0,
+            token_program: token::ID,
+        },
+        args::TerminateVesting { termination },
+    )
+}
+
+/// Fire an acceleration trigger of `stream`
+pub fn fire_acceleration_trigger(
+    stream: &Pubkey,
+    authority: &Pubkey,
+    trigger: AccelerationTrigger,
+) -> Instruction {
+    instruction(
+        accounts::FireAccelerationTrigger {
+            stream: *stream,
+            authority: *authority,
+        },
+        args::FireAccelerationTrigger { trigger },
+    )
+}
+
+/// Split part of `stream` into a new stream under the next split id of `stream`
+pub fn split_stream(stream_address: &Pubkey, stream: &Stream, args: args::SplitStream) -> Instruction {
+    let new_stream_id = split_stream_id(stream_address, stream.split_count);
+    let new_stream = find_stream_address(&stream.creator, &new_stream_id).0;
+
+    instruction(
+        accounts::SplitStream {
+            stream: *stream_address,
+            new_stream,
+            escrow_token_account: find_escrow_address(stream_address).0,
+            new_escrow_token_account: find_escrow_address(&new_stream).0,
+            recipient: stream.recipient,
+            mint: stream.mint,
+            compliance_registry: compliance_registry(stream),
+            token_program: token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        args,
+    )
+}
+
+/// Merge `sources` into `target`, restarting it until `end_time`
+pub fn merge_streams(
+    target_address: &Pubkey,
+    target: &Stream,
+    sources: &[Pubkey],
+    end_time: i64,
+) -> Instruction {
+    let mut ix = instruction(
+        accounts::MergeStreams {
+            stream: *target_address,
+            escrow_token_account: find_escrow_address(target_address).0,
+            recipient_token_account: recipient_payout(target, None).0,
+            sender: target.sender,
+            recipient: target.recipient,
+            token_program: token::ID,
+        },
+        args::MergeStreams { end_time },
+    );
+
+    for source in sources {
+        ix.accounts.push(AccountMeta::new(*source, false));
+        ix.accounts
+            .push(AccountMeta::new(find_escrow_address(source).0, false));
+    }
+
+    ix
+}
+
+/// Tokenize the recipient position of `stream`
+pub fn mint_position_token(stream_address: &Pubkey, stream: &Stream) -> Instruction {
+    let position_mint = find_position_mint_address(stream_address).0;
+
+    instruction(
+        accounts::MintPositionToken {
+            stream: *stream_address,
+            position_mint,
+            recipient_position_account: get_associated_token_address(
+                &stream.recipient,
+                &position_mint,
+            ),
+            metadata: find_position_metadata_address(&position_mint).0,
+            sender: stream.sender,
+            recipient: stream.recipient,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            token_metadata_program: mpl_token_metadata::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        args::MintPositionToken {},
+    )
+}
+
+/// Set or clear the delegated withdraw authority and fixed destination. The proofs
+/// are only needed when the stream is bound to a Merkle allowlist registry.
+pub fn set_withdraw_delegate(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    recipient: &Pubkey,
+    withdraw_authority: Option<Pubkey>,
+    destination: Option<Pubkey>,
+    authority_proof: Vec<[u8; 32]>,
+    destination_proof: Vec<[u8; 32]>,
+) -> Instruction {
+    instruction(
+        accounts::SetWithdrawDelegate {
+            stream: *stream_address,
+            recipient: *recipient,
+            destination_token_account: destination,
+            compliance_registry: compliance_registry(stream),
+        },
+        args::SetWithdrawDelegate {
+            withdraw_authority,
+            destination,
+            authority_proof,
+            destination_proof,
+        },
+    )
+}
+
+/// Nominate `new_sender` to take over `stream`
+pub fn propose_sender_transfer(stream: &Pubkey, sender: &Pubkey, new_sender: Pubkey) -> Instruction {
+    instruction(
+        accounts::ProposeSenderTransfer {
+            stream: *stream,
+            sender: *sender,
+        },
+        args::ProposeSenderTransfer { new_sender },
+    )
+}
+
+/// Accept a pending sender transfer of `stream`
+pub fn accept_sender_transfer(stream: &Pubkey, new_sender: &Pubkey) -> Instruction {
+    instruction(
+        accounts::AcceptSenderTransfer {
+            stream: *stream,
+            new_sender: *new_sender,
+        },
+        args::AcceptSenderTransfer {},
+    )
+}
+
+/// Create the sender profile of `sender`
+pub fn create_sender_profile(sender: &Pubkey) -> Instruction {
+    instruction(
+        accounts::CreateSenderProfile {
+            sender_profile: find_sender_profile_address(sender).0,
+            sender: *sender,
+            system_program: system_program::ID,
+        },
+        args::CreateSenderProfile {},
+    )
+}
+
+/// Quote the amount withdrawable from `stream`, returned through return data as
+/// `streamflow::StreamQuote`
+pub fn get_withdrawable(stream: &Pubkey) -> Instruction {
+    instruction(accounts::ViewStream { stream: *stream }, args::GetWithdrawable {})
+}
+
+/// Quote the amount streamed so far by `stream`, returned through return data as
+/// `streamflow::StreamQuote`
+pub fn get_streamed(stream: &Pubkey) -> Instruction {
+    instruction(accounts::ViewStream { stream: *stream }, args::GetStreamed {})
+}
+
+/// Summarize `stream`, returned through return data as `streamflow::StreamSummary`
+pub fn get_stream_summary(stream: &Pubkey) -> Instruction {
+    instruction(accounts::ViewStream { stream: *stream }, args::GetStreamSummary {})
+}
+
+/// Pledge up to `amount` of `stream` to `lender`, repaid into `lender_token_account`.
+/// `compliance_proof` is only needed when the stream is bound to a Merkle allowlist
+/// registry. The sender must co-sign when the stream is cancelable by the sender or
+/// has a leaver policy.
+pub fn create_lien(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    recipient: &Pubkey,
+    lender: Pubkey,
+    lender_token_account: &Pubkey,
+    amount: u64,
+    compliance_proof: Vec<[u8; 32]>,
+) -> Instruction {
+    instruction(
+        accounts::CreateLien {
+            stream: *stream_address,
+            recipient: *recipient,
+            sender: stream.lien_requires_sender().then_some(stream.sender),
+            lender_token_account: *lender_token_account,
+            compliance_registry: compliance_registry(stream),
+        },
+        args::CreateLien {
+            lender,
+            amount,
+            compliance_proof,
+        },
+    )
+}
+
+/// Repay up to `amount` of the lien on `stream` from the associated token account of
+/// `payer`
+pub fn repay_lien(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    payer: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        accounts::RepayLien {
+            stream: *stream_address,
+            payer: *payer,
+            payer_token_account: get_associated_token_address(payer, &stream.mint),
+            lender_token_account: stream.lien.lender_token_account,
+            token_program: token::ID,
+        },
+        args::RepayLien { amount },
+    )
+}
+
+/// Lift the lien on `stream`, forgiving what is still owed
+pub fn release_lien(stream: &Pubkey, lender: &Pubkey) -> Instruction {
+    instruction(
+        accounts::ReleaseLien {
+            stream: *stream,
+            lender: *lender,
+        },
+        args::ReleaseLien {},
+    )
+}
+
+/// Request cancellation of `stream` at `effective_at`, signed by its sender or by
+/// the recipient, which for a tokenized stream is the holder of the position token
+pub fn request_cancel(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    authority: &Pubkey,
+    effective_at: i64,
+) -> Instruction {
+    let position_token_account = (stream.has_position_token() && *authority != stream.sender)
+        .then(|| get_associated_token_address(authority, &stream.position_mint));
+
+    instruction(
+        accounts::RequestCancel {
+            stream: *stream_address,
+            authority: *authority,
+            position_token_account,
+        },
+        args::RequestCancel { effective_at },
+    )
+}
+
+/// Settle `stream` once its requested cancellation has taken effect. Anyone may
+/// send it; the sender is paid into its associated token account and the recipient
+/// side as in `withdraw`. `position_holder` is the owner of the position token of a
+/// tokenized stream.
+pub fn finalize_cancel(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    position_holder: Option<&Pubkey>,
+) -> Instruction {
+    let [yield_vault_program, yield_vault, yield_vault_token_account] = yield_vault(stream);
+    let (recipient_token_account, position_token_account) =
+        recipient_payout(stream, position_holder);
+    instruction(
+        accounts::FinalizeCancel {
+            stream: *stream_address,
+            sender_token_account: get_associated_token_address(&stream.sender, &stream.mint),
+            recipient_token_account,
+            position_token_account,
+            escrow_token_account: find_escrow_address(stream_address).0,
+            yield_vault_program,
+            yield_vault,
+            yield_vault_token_account,
+            token_program: token::ID,
+        },
+        args::FinalizeCancel {},
+    )
+}
+
+/// Propose new terms for `stream`. Terms that strictly favor the recipient apply
+/// immediately; proposing the current terms withdraws a pending proposal.
+pub fn propose_amendment(stream: &Pubkey, sender: &Pubkey, terms: StreamTerms) -> Instruction {
+    instruction(
+        accounts::ProposeAmendment {
+            stream: *stream,
+            sender: *sender,
+        },
+        args::ProposeAmendment { terms },
+    )
+}
+
+/// Countersign the pending amendment of `stream`, which must equal `terms`
+pub fn accept_amendment(stream: &Pubkey, recipient: &Pubkey, terms: StreamTerms) -> Instruction {
+    instruction(
+        accounts::AcceptAmendment {
+            stream: *stream,
+            recipient: *recipient,
+        },
+        args::AcceptAmendment { terms },
+    )
+}
+
+/// Approve `vault_program` for escrow yield, signed by the program upgrade authority
+pub fn approve_yield_vault(
+    authority: &Pubkey,
+    vault_program: &Pubkey,
+    adapter: YieldAdapter,
+) -> Instruction {
+    instruction(
+        accounts::ApproveYieldVault {
+            approved_yield_vault: find_approved_yield_vault_address(vault_program).0,
+            vault_program: *vault_program,
+            authority: *authority,
+            program: streamflow::ID,
+            program_data: program_data(),
+            system_program: system_program::ID,
+        },
+        args::ApproveYieldVault { adapter },
+    )
+}
+
+/// Revoke the approval of `vault_program`, signed by the program upgrade authority
+pub fn revoke_yield_vault(authority: &Pubkey, vault_program: &Pubkey) -> Instruction {
+    instruction(
+        accounts::RevokeYieldVault {
+            approved_yield_vault: find_approved_yield_vault_address(vault_program).0,
+            authority: *authority,
+            program: streamflow::ID,
+            program_data: program_data(),
+        },
+        args::RevokeYieldVault {},
+    )
+}
+
+/// Accept the yield vault and split of `stream`, signed by its recipient
+pub fn accept_escrow_yield(stream: &Pubkey, recipient: &Pubkey) -> Instruction {
+    instruction(
+        accounts::AcceptEscrowYield {
+            stream: *stream,
+            recipient: *recipient,
+        },
+        args::AcceptEscrowYield {},
+    )
+}
+
+/// Move the unvested balance of `stream` into its yield vault and unlocked
+/// principal back into the escrow. Anyone may send it.
+pub fn rebalance_escrow_yield(stream_address: &Pubkey, stream: &Stream) -> Instruction {
+    let config = &stream.escrow_yield;
+    instruction(
+        accounts::RebalanceEscrowYield {
+            stream: *stream_address,
+            escrow_token_account: find_escrow_address(stream_address).0,
+            approved_yield_vault: find_approved_yield_vault_address(&config.vault_program).0,
+            vault_program: config.vault_program,
+            vault: config.vault,
+            vault_token_account: config.vault_token_account,
+            token_program: token::ID,
+        },
+        args::RebalanceEscrowYield {},
+    )
+}
+
+/// Pay out the yield `stream` earned above its principal. Anyone may send it; the
+/// sender is paid into its associated token account and the recipient side as in
+/// `withdraw`, repaying a lien first. `position_holder` is the owner of the position token of a tokenized
+/// stream.
+pub fn harvest_escrow_yield(
+    stream_address: &Pubkey,
+    stream: &Stream,
+    position_holder: Option<&Pubkey>,
+) -> Instruction {
+    let config = &stream.escrow_yield;
+    let (recipient_token_account, position_token_account) =
+        recipient_payout(stream, position_holder);
+    instruction(
+        accounts::HarvestEscrowYield {
+            stream: *stream_address,
+            escrow_token_account: find_escrow_address(stream_address).0,
+            sender_token_account: get_associated_token_address(&stream.sender, &stream.mint),
+            recipient_token_account,
+            position_token_account,
+            lender_token_account: stream
+                .has_lien()
+                .then_some(stream.lien.lender_token_account),
+            vault_program: config.vault_program,
+            vault: config.vault,
+            vault_token_account: config.vault_token_account,
+            token_program: token::ID,
+        },
+        args::HarvestEscrowYield {},
+    )
+}
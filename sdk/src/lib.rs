@@ -0,0 +1,24 @@
+//! Rust client SDK for the StreamFlow program
+//!
+//! Provides instruction builders, PDA derivation, account deserialization and the
+//! stream unlock math for off-chain services. The SDK links the program crate
+//! itself, so account layouts and amounts always agree with the deployed program.
+//! Async RPC helpers are available behind the `rpc` feature.
+
+pub mod accounts;
+pub mod error;
+pub mod instructions;
+pub mod math;
+pub mod pda;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+
+pub use error::SdkError;
+pub use streamflow::instruction as args;
+pub use streamflow::state::{
+    milestone_approval_message, AccessLevel, ApprovedYieldVault, AuditEntry, ComplianceMode,
+    ComplianceRegistry, EscrowYield, MilestoneSchedule, SenderProfile, Stream, StreamData,
+    StreamTerms, Treasury, TreasuryAuditLog, TreasuryOperation, YieldAdapter,
+};
+pub use streamflow::{StreamQuote, StreamSummary};
+pub use streamflow::ID as PROGRAM_ID;
@@ -0,0 +1,23 @@
+//! Off-chain stream amounts.
+//!
+//! These call the same `Stream` methods the program executes instead of
+//! re-implementing them, so an amount computed here for a given timestamp is
+//! exactly what the program computes for that `Clock::unix_timestamp`.
+
+use crate::error::Result;
+use crate::Stream;
+
+/// Amount the recipient can withdraw at `current_time`
+pub fn withdrawable_amount(stream: &Stream, current_time: i64) -> Result<u64> {
+    Ok(stream.withdrawable_amount(current_time)?)
+}
+
+/// Amount unlocked by the schedule at `current_time`, withdrawn or not
+pub fn streamed_amount(stream: &Stream, current_time: i64) -> Result<u64> {
+    Ok(stream.streamed_amount(current_time)?)
+}
+
+/// Amount still held in the stream escrow
+pub fn remaining_balance(stream: &Stream) -> u64 {
+    stream.remaining_balance()
+}
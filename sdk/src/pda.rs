@@ -0,0 +1,18 @@
+//! Program derived addresses used by the StreamFlow program
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::metadata::mpl_token_metadata;
+
+pub use streamflow_interface::pda::*;
+
+/// Token metadata account of a position mint
+pub fn find_position_metadata_address(position_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            mpl_token_metadata::ID.as_ref(),
+            position_mint.as_ref(),
+        ],
+        &mpl_token_metadata::ID,
+    )
+}
@@ -0,0 +1,260 @@
+//! Async RPC helpers, enabled with the `rpc` feature
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use anchor_spl::token::TokenAccount;
+use base64::Engine;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::account::from_account;
+use solana_sdk::clock::Clock;
+use solana_sdk::message::Message;
+use solana_sdk::sysvar;
+use solana_sdk::transaction::Transaction;
+
+use crate::accounts::*;
+use crate::error::{Result, SdkError};
+use crate::pda::{find_sender_profile_address, find_treasury_audit_log_address, nonce_stream_id};
+use crate::{
+    instructions, math, AuditEntry, ComplianceRegistry, SenderProfile, Stream, StreamQuote,
+    StreamSummary, Treasury, TreasuryAuditLog,
+};
+
+/// Offset of `Stream::sender` in the account data
+const STREAM_SENDER_OFFSET: usize = 8;
+
+/// Offset of `Stream::recipient` in the account data
+const STREAM_RECIPIENT_OFFSET: usize = 8 + 32;
+
+async fn fetch_data(client: &RpcClient, address: &Pubkey) -> Result<Vec<u8>> {
+    client
+        .get_account_with_commitment(address, client.commitment())
+        .await?
+        .value
+        .map(|account| account.data)
+        .ok_or(SdkError::AccountNotFound(*address))
+}
+
+/// Fetch and deserialize a stream
+pub async fn fetch_stream(client: &RpcClient, address: &Pubkey) -> Result<Stream> {
+    deserialize_stream(&fetch_data(client, address).await?)
+}
+
+/// Owner of the position token of a tokenized stream, `None` for other streams
+pub async fn fetch_position_holder(client: &RpcClient, stream: &Stream) -> Result<Option<Pubkey>> {
+    if !stream.has_position_token() {
+        return Ok(None);
+    }
+
+    // The position mint has a supply of one, so a single account holds a balance
+    let holding = client
+        .get_token_largest_accounts(&stream.position_mint)
+        .await?
+        .into_iter()
+        .find(|account| account.amount.amount == "1")
+        .and_then(|account| account.address.parse().ok())
+        .ok_or(SdkError::AccountNotFound(stream.position_mint))?;
+    let data = fetch_data(client, &holding).await?;
+
+    Ok(Some(TokenAccount::try_deserialize(&mut &data[..])?.owner))
+}
+
+/// Fetch and deserialize a treasury
+pub async fn fetch_treasury(client: &RpcClient, address: &Pubkey) -> Result<Treasury> {
+    deserialize_treasury(&fetch_data(client, address).await?)
+}
+
+/// Fetch and deserialize a compliance registry
+pub async fn fetch_compliance_registry(
+    client: &RpcClient,
+    address: &Pubkey,
+) -> Result<ComplianceRegistry> {
+    deserialize_compliance_registry(&fetch_data(client, address).await?)
+}
+
+/// Fetch the audit log of `treasury`, if it has one
+pub async fn fetch_treasury_audit_log(
+    client: &RpcClient,
+    treasury: &Pubkey,
+) -> Result<Option<TreasuryAuditLog>> {
+    match fetch_data(client, &find_treasury_audit_log_address(treasury).0).await {
+        Ok(data) => Ok(Some(deserialize_treasury_audit_log(&data)?)),
+        Err(SdkError::AccountNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Audit entries of `treasury` with a sequence number of at least `from_sequence`,
+/// oldest first, together with the sequence number to resume from.
+///
+/// Returns `SdkError::AuditLogGap` when entries after `from_sequence` have already
+/// been overwritten, in which case the caller has missed part of the history.
+pub async fn fetch_treasury_audit_entries(
+    client: &RpcClient,
+    treasury: &Pubkey,
+    from_sequence: u64,
+) -> Result<(Vec<AuditEntry>, u64)> {
+    let Some(audit_log) = fetch_treasury_audit_log(client, treasury).await? else {
+        return Ok((Vec::new(), from_sequence));
+    };
+
+    let entries = audit_log.entries();
+    let oldest = entries.first().map_or(audit_log.next_sequence, |entry| entry.sequence);
+    if from_sequence < oldest {
+        return Err(SdkError::AuditLogGap {
+            requested: from_sequence,
+            oldest,
+        });
+    }
+
+    let entries = entries
+        .into_iter()
+        .filter(|entry| entry.sequence >= from_sequence)
+        .collect();
+    Ok((entries, audit_log.next_sequence))
+}
+
+/// Fetch the sender profile of `sender`, if it has one
+pub async fn fetch_sender_profile(
+    client: &RpcClient,
+    sender: &Pubkey,
+) -> Result<Option<SenderProfile>> {
+    match fetch_data(client, &find_sender_profile_address(sender).0).await {
+        Ok(data) => Ok(Some(deserialize_sender_profile(&data)?)),
+        Err(SdkError::AccountNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Stream id the next stream created through the sender profile must use
+pub async fn next_stream_id(client: &RpcClient, sender: &Pubkey) -> Result<Option<[u8; 32]>> {
+    Ok(fetch_sender_profile(client, sender)
+        .await?
+        .map(|profile| nonce_stream_id(profile.next_nonce)))
+}
+
+/// All streams currently sent by `sender`
+pub async fn fetch_streams_by_sender(
+    client: &RpcClient,
+    sender: &Pubkey,
+) -> Result<Vec<(Pubkey, Stream)>> {
+    fetch_streams(client, STREAM_SENDER_OFFSET, sender).await
+}
+
+/// All streams currently paying `recipient`
+pub async fn fetch_streams_by_recipient(
+    client: &RpcClient,
+    recipient: &Pubkey,
+) -> Result<Vec<(Pubkey, Stream)>> {
+    fetch_streams(client, STREAM_RECIPIENT_OFFSET, recipient).await
+}
+
+async fn fetch_streams(
+    client: &RpcClient,
+    offset: usize,
+    key: &Pubkey,
+) -> Result<Vec<(Pubkey, Stream)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, Stream::DISCRIMINATOR.to_vec())),
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, key.to_bytes().to_vec())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    client
+        .get_program_accounts_with_config(&streamflow::ID, config)
+        .await?
+        .into_iter()
+        .map(|(address, account)| Ok((address, deserialize_stream(&account.data)?)))
+        .collect()
+}
+
+/// Cluster time from the Clock sysvar, the same time the program sees
+pub async fn fetch_cluster_time(client: &RpcClient) -> Result<i64> {
+    let account = client.get_account(&sysvar::clock::ID).await?;
+    let clock: Clock = from_account(&account).ok_or(SdkError::InvalidSysvar(sysvar::clock::ID))?;
+
+    Ok(clock.unix_timestamp)
+}
+
+/// Amount withdrawable from a stream at the current cluster time
+pub async fn fetch_withdrawable_amount(client: &RpcClient, address: &Pubkey) -> Result<u64> {
+    let stream = fetch_stream(client, address).await?;
+    let current_time = fetch_cluster_time(client).await?;
+
+    math::withdrawable_amount(&stream, current_time)
+}
+
+/// Withdrawable amount quoted by the program itself through a simulated
+/// `get_withdrawable`. `fee_payer` must be an existing account; nothing is signed.
+pub async fn simulate_withdrawable(
+    client: &RpcClient,
+    fee_payer: &Pubkey,
+    stream: &Pubkey,
+) -> Result<StreamQuote> {
+    simulate_view(client, fee_payer, instructions::get_withdrawable(stream)).await
+}
+
+/// Streamed amount quoted by the program itself through a simulated `get_streamed`
+pub async fn simulate_streamed(
+    client: &RpcClient,
+    fee_payer: &Pubkey,
+    stream: &Pubkey,
+) -> Result<StreamQuote> {
+    simulate_view(client, fee_payer, instructions::get_streamed(stream)).await
+}
+
+/// Stream summary computed by the program itself through a simulated
+/// `get_stream_summary`
+pub async fn simulate_stream_summary(
+    client: &RpcClient,
+    fee_payer: &Pubkey,
+    stream: &Pubkey,
+) -> Result<StreamSummary> {
+    simulate_view(client, fee_payer, instructions::get_stream_summary(stream)).await
+}
+
+async fn simulate_view<T: AnchorDeserialize>(
+    client: &RpcClient,
+    fee_payer: &Pubkey,
+    instruction: Instruction,
+) -> Result<T> {
+    let transaction = Transaction::new_unsigned(Message::new(&[instruction], Some(fee_payer)));
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(client.commitment()),
+        ..RpcSimulateTransactionConfig::default()
+    };
+    let result = client
+        .simulate_transaction_with_config(&transaction, config)
+        .await?
+        .value;
+
+    if let Some(err) = result.err {
+        return Err(SdkError::ReturnData(format!("simulation failed: {}", err)));
+    }
+    let return_data = result
+        .return_data
+        .ok_or_else(|| SdkError::ReturnData("no return data".to_string()))?;
+    if return_data.program_id != streamflow::ID.to_string() {
+        return Err(SdkError::ReturnData(format!(
+            "returned by {}",
+            return_data.program_id
+        )));
+    }
+
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&return_data.data.0)
+        .map_err(|err| SdkError::ReturnData(err.to_string()))?;
+    T::try_from_slice(&bytes).map_err(|err| SdkError::ReturnData(err.to_string()))
+}